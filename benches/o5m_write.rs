@@ -0,0 +1,85 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use vadeen_osm::osm_io::{create_writer, FileFormat};
+use vadeen_osm::{AuthorInformation, Meta, Node, Osm, Timestamp, Way};
+
+const NODES: i64 = 100_000;
+const NODES_PER_WAY: i64 = 10;
+
+/// A map with recurring tags and users, like real data, but with more distinct strings than fit
+/// in the o5m string table.
+fn synthetic_map() -> Osm {
+    let mut osm = Osm::default();
+    for id in 1..=NODES {
+        osm.add_node(Node {
+            id,
+            coordinate: ((id % 1800) as f64 / 20.0, (id / 1800) as f64 / 20.0).into(),
+            meta: meta(
+                id,
+                vec![
+                    (
+                        "amenity",
+                        ["bench", "cafe", "waste_basket"][id as usize % 3].to_owned(),
+                    ),
+                    ("name", format!("Place {}", id % 20_000)),
+                ],
+            ),
+        });
+    }
+
+    for id in 1..=(NODES / NODES_PER_WAY) {
+        let first = (id - 1) * NODES_PER_WAY + 1;
+        osm.add_way(Way {
+            id,
+            refs: (first..first + NODES_PER_WAY).collect(),
+            meta: meta(
+                id,
+                vec![
+                    (
+                        "highway",
+                        ["residential", "primary", "footway"][id as usize % 3].to_owned(),
+                    ),
+                    ("ref", format!("{}", id % 5_000)),
+                ],
+            ),
+        });
+    }
+    osm
+}
+
+fn meta(id: i64, tags: Vec<(&str, String)>) -> Meta {
+    Meta {
+        tags: tags
+            .into_iter()
+            .map(|(k, v)| (k.to_owned(), v).into())
+            .collect(),
+        version: Some(1),
+        author: Some(AuthorInformation {
+            created: Timestamp(1_500_000_000 + id),
+            change_set: id as u64 / 100,
+            uid: id as u64 % 3_000,
+            user: format!("user{}", id % 3_000),
+        }),
+        ..Meta::default()
+    }
+}
+
+fn o5m_write(c: &mut Criterion) {
+    let osm = synthetic_map();
+
+    let mut group = c.benchmark_group("o5m");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(
+        (osm.nodes.len() + osm.ways.len()) as u64,
+    ));
+    group.bench_function("write", |b| {
+        b.iter(|| {
+            let mut writer = create_writer(Vec::new(), FileFormat::O5m);
+            writer.write(&osm).unwrap();
+            writer.into_inner()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, o5m_write);
+criterion_main!(benches);
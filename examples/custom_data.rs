@@ -1,7 +1,7 @@
+use vadeen_osm::geo::Coordinate;
 use vadeen_osm::osm_io::error::Result;
 use vadeen_osm::osm_io::write;
 use vadeen_osm::{OsmBuilder, Tag};
-use vadeen_osm::geo::Coordinate;
 
 struct MyTag {
     key: String,
@@ -28,17 +28,17 @@ impl MyCoordinate {
     }
 }
 
-impl Into<Coordinate> for MyCoordinate {
-    fn into(self) -> Coordinate {
-        Coordinate::new(self.lat, self.lon)
+impl From<MyCoordinate> for Coordinate {
+    fn from(val: MyCoordinate) -> Self {
+        Coordinate::new(val.lat, val.lon)
     }
 }
 
-impl Into<Tag> for MyTag {
-    fn into(self) -> Tag {
+impl From<MyTag> for Tag {
+    fn from(val: MyTag) -> Self {
         Tag {
-            key: self.key,
-            value: self.value,
+            key: val.key,
+            value: val.value,
         }
     }
 }
@@ -1,4 +1,4 @@
-use vadeen_osm::{AuthorInformation, Meta, Node, Osm, Relation, RelationMember, Way};
+use vadeen_osm::{AuthorInformation, Meta, Node, Osm, Relation, RelationMember, Timestamp, Way};
 
 fn main() {
     let mut osm = Osm::default();
@@ -11,11 +11,12 @@ fn main() {
             tags: vec![("key", "value").into()],
             version: Some(3),
             author: Some(AuthorInformation {
-                created: 12345678,
+                created: Timestamp(12345678),
                 change_set: 1,
                 uid: 1234,
                 user: "Username".to_string(),
             }),
+            ..Meta::default()
         },
     });
 
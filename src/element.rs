@@ -3,14 +3,15 @@
 //! See: https://wiki.openstreetmap.org/wiki/Elements
 
 use crate::geo::Coordinate;
+use crate::Timestamp;
 
 type RelationRole = String;
-type TimeStamp = i64;
 
 /// A coordinate with meta data. See OSM docs for [`Node`].
 ///
 /// [`Node`]: https://wiki.openstreetmap.org/wiki/Node
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Node {
     pub id: i64,
     pub coordinate: Coordinate,
@@ -20,7 +21,8 @@ pub struct Node {
 /// Group of nodes and meta data. See OSM docs for [`Way`].
 ///
 /// [`Way`]: https://wiki.openstreetmap.org/wiki/Way
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Way {
     pub id: i64,
     pub refs: Vec<i64>,
@@ -30,7 +32,8 @@ pub struct Way {
 /// Group of elements (node, way or relation). See OSM docs for [`Relation`].
 ///
 /// [`Relation`]: https://wiki.openstreetmap.org/wiki/Relation
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Relation {
     pub id: i64,
     pub members: Vec<RelationMember>,
@@ -41,6 +44,7 @@ pub struct Relation {
 ///
 /// [`Tags`]: https://wiki.openstreetmap.org/wiki/Tags
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tag {
     pub key: String,
     pub value: String,
@@ -48,29 +52,86 @@ pub struct Tag {
 
 /// Common meta data used by multiple entities.
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Meta {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::tags"))]
     pub tags: Vec<Tag>,
     pub version: Option<u32>,
     pub author: Option<AuthorInformation>,
+
+    /// False if this version deletes the element, only used in history data. Deleted elements
+    /// have no tags, and deleted nodes have no coordinate.
+    pub visible: bool,
+
+    /// Pending change not yet uploaded to OSM, used by editors like JOSM. New elements have
+    /// negative ids and `Action::Modify`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub action: Option<Action>,
+}
+
+/// Pending change of an element, the `action` attribute of JOSM files.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Action {
+    /// The element is new or has been changed.
+    Modify,
+
+    /// The element has been deleted. Unlike deleted versions in history data, the element still
+    /// has all its data.
+    Delete,
 }
 
 /// Author information is used to identify what nodes, ways and relation a specific user has
 /// added. When working on non osm maps, this data is irrelevant.
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AuthorInformation {
-    pub created: TimeStamp,
+    pub created: Timestamp,
     pub change_set: u64,
     pub uid: u64,
     pub user: String,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "crate::serialize::Member", from = "crate::serialize::Member")
+)]
 pub enum RelationMember {
     Node(i64, RelationRole),
     Way(i64, RelationRole),
     Relation(i64, RelationRole),
 }
 
+/// Id of a node, returned by the `OsmBuilder`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct NodeId(pub i64);
+
+/// Id of a way, returned by the `OsmBuilder`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct WayId(pub i64);
+
+/// Id of a relation, returned by the `OsmBuilder`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct RelationId(pub i64);
+
+/// Id of any element type.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum ElementId {
+    Node(NodeId),
+    Way(WayId),
+    Relation(RelationId),
+}
+
 impl From<(String, String)> for Tag {
     fn from((key, value): (String, String)) -> Self {
         Tag { key, value }
@@ -104,32 +165,32 @@ impl RelationMember {
     }
 }
 
-impl Default for Node {
-    fn default() -> Self {
-        Node {
-            id: 0,
-            coordinate: Coordinate { lat: 0, lon: 0 },
-            meta: Default::default(),
-        }
+impl From<NodeId> for ElementId {
+    fn from(id: NodeId) -> Self {
+        ElementId::Node(id)
     }
 }
 
-impl Default for Way {
-    fn default() -> Self {
-        Way {
-            id: 0,
-            refs: vec![],
-            meta: Default::default(),
-        }
+impl From<WayId> for ElementId {
+    fn from(id: WayId) -> Self {
+        ElementId::Way(id)
     }
 }
 
-impl Default for Relation {
-    fn default() -> Self {
-        Relation {
-            id: 0,
-            members: vec![],
-            meta: Default::default(),
+impl From<RelationId> for ElementId {
+    fn from(id: RelationId) -> Self {
+        ElementId::Relation(id)
+    }
+}
+
+/// Create a member from an element id and a role.
+impl<I: Into<ElementId>> From<(I, &str)> for RelationMember {
+    fn from((id, role): (I, &str)) -> Self {
+        let role = role.to_owned();
+        match id.into() {
+            ElementId::Node(NodeId(id)) => RelationMember::Node(id, role),
+            ElementId::Way(WayId(id)) => RelationMember::Way(id, role),
+            ElementId::Relation(RelationId(id)) => RelationMember::Relation(id, role),
         }
     }
 }
@@ -137,9 +198,21 @@ impl Default for Relation {
 impl Default for Meta {
     fn default() -> Self {
         Meta {
-            tags: vec![],
+            tags: Vec::new(),
             version: None,
             author: None,
+            visible: true,
+            action: None,
+        }
+    }
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Node {
+            id: 0,
+            coordinate: Coordinate { lat: 0, lon: 0 },
+            meta: Default::default(),
         }
     }
 }
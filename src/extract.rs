@@ -0,0 +1,683 @@
+//! Extraction of parts of an OSM map, and splitting of maps into tiles.
+//!
+//! See the osmium documentation over [`strategies`] for the semantics of each strategy.
+//!
+//! [`strategies`]: https://osmcode.org/osmium-tool/manual.html#creating-geographic-extracts
+use crate::geo::{Boundary, Coordinate, MultiPolygon, PolyFile, Polygon, Tile, MAX_ZOOM};
+use crate::{Osm, Relation, RelationMember};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The area to extract from a map.
+#[derive(Debug, Clone)]
+pub enum ExtractArea {
+    Boundary(Boundary),
+    Polygon(MultiPolygon),
+}
+
+/// Determines which elements are included in an extract.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum ExtractStrategy {
+    /// Only nodes inside the area are included. Ways are reduced to the nodes inside the area, ways
+    /// with less than two nodes left are dropped.
+    Simple,
+
+    /// Same as `Simple`, but all nodes of ways with at least one node inside the area are included,
+    /// so the ways are complete.
+    CompleteWays,
+
+    /// Same as `CompleteWays`, but all member ways (and their nodes) of multipolygon relations
+    /// with at least one way in the extract are included as well.
+    Smart,
+}
+
+impl ExtractArea {
+    /// Check if a coordinate is inside the area.
+    pub fn contains(&self, c: Coordinate) -> bool {
+        match self {
+            ExtractArea::Boundary(boundary) => boundary.contains(c),
+            ExtractArea::Polygon(polygon) => polygon.contains(c),
+        }
+    }
+}
+
+impl From<Boundary> for ExtractArea {
+    fn from(boundary: Boundary) -> Self {
+        ExtractArea::Boundary(boundary)
+    }
+}
+
+impl From<Polygon> for ExtractArea {
+    fn from(polygon: Polygon) -> Self {
+        ExtractArea::Polygon(polygon.into())
+    }
+}
+
+impl From<MultiPolygon> for ExtractArea {
+    fn from(polygon: MultiPolygon) -> Self {
+        ExtractArea::Polygon(polygon)
+    }
+}
+
+impl From<PolyFile> for ExtractArea {
+    fn from(file: PolyFile) -> Self {
+        ExtractArea::Polygon(file.area)
+    }
+}
+
+impl Osm {
+    /// Extract the elements within an area into a new map.
+    ///
+    /// The result has referential integrity, i.e. ways and relations only reference elements
+    /// present in the result. Relations are included if any of their members are included, and
+    /// members that are not included are removed. The boundary of the result covers all included
+    /// nodes, it is `None` if no nodes are included. The header is copied.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::{ExtractStrategy, OsmBuilder};
+    /// # use vadeen_osm::geo::Boundary;
+    /// let mut builder = OsmBuilder::default();
+    /// builder.add_polyline(vec![(1.0, 1.0), (1.0, 3.0)], vec![("highway", "track")]);
+    /// let osm = builder.build();
+    ///
+    /// let area = Boundary::new((0.0, 0.0), (2.0, 2.0));
+    /// let extract = osm.extract(area.clone(), ExtractStrategy::Simple);
+    /// assert_eq!(extract.nodes.len(), 1);
+    /// assert_eq!(extract.ways.len(), 0);
+    ///
+    /// let extract = osm.extract(area, ExtractStrategy::CompleteWays);
+    /// assert_eq!(extract.nodes.len(), 2);
+    /// assert_eq!(extract.ways.len(), 1);
+    /// assert_eq!(extract.boundary, Some(Boundary::new((1.0, 1.0), (1.0, 3.0))));
+    /// ```
+    pub fn extract<A: Into<ExtractArea>>(&self, area: A, strategy: ExtractStrategy) -> Osm {
+        let area = area.into();
+        let inside: HashSet<i64> = self
+            .nodes
+            .iter()
+            .filter(|n| n.meta.visible && area.contains(n.coordinate))
+            .map(|n| n.id)
+            .collect();
+
+        let mut way_ids: HashSet<i64> = self
+            .ways
+            .iter()
+            .filter(|w| w.refs.iter().any(|r| inside.contains(r)))
+            .map(|w| w.id)
+            .collect();
+
+        if strategy == ExtractStrategy::Smart {
+            for rel in self.relations.iter().filter(|r| is_multipolygon(r)) {
+                if rel.members.iter().any(|m| is_way_in(m, &way_ids)) {
+                    way_ids.extend(member_way_ids(rel));
+                }
+            }
+        }
+
+        let mut node_ids = inside;
+        if strategy != ExtractStrategy::Simple {
+            for way in self.ways.iter().filter(|w| way_ids.contains(&w.id)) {
+                node_ids.extend(&way.refs);
+            }
+        }
+
+        let mut osm = self.empty_extract();
+        for node in self.nodes.iter().filter(|n| node_ids.contains(&n.id)) {
+            osm.add_node(node.clone());
+        }
+
+        // Only nodes actually present in the map can be referenced.
+        let node_ids: HashSet<i64> = osm.nodes.iter().map(|n| n.id).collect();
+        for way in self.ways.iter().filter(|w| way_ids.contains(&w.id)) {
+            let mut way = way.clone();
+            way.refs.retain(|r| node_ids.contains(r));
+            if way.refs.len() >= 2 {
+                osm.add_way(way);
+            }
+        }
+
+        let way_ids: HashSet<i64> = osm.ways.iter().map(|w| w.id).collect();
+        let relation_ids = self.included_relations(&node_ids, &way_ids);
+        for rel in self.relations.iter() {
+            if relation_ids.contains(&rel.id) {
+                let mut rel = rel.clone();
+                rel.members.retain(|m| match m {
+                    RelationMember::Node(id, _) => node_ids.contains(id),
+                    RelationMember::Way(id, _) => way_ids.contains(id),
+                    RelationMember::Relation(id, _) => relation_ids.contains(id),
+                });
+                osm.add_relation(rel);
+            }
+        }
+
+        clear_empty_boundary(&mut osm);
+        osm
+    }
+
+    /// Split the map into one map per tile at a zoom level. Tiles without any nodes are left out.
+    ///
+    /// Every node belongs to exactly one tile. With the `Simple` strategy ways are reduced to the
+    /// nodes in each tile they touch, like in [`extract`]. With `CompleteWays` and `Smart` a way
+    /// crossing tile edges is duplicated into every tile that has any of its nodes, together with
+    /// all of its nodes, so each tile is self-contained. Relations are included in every tile
+    /// that has any of their members. Each tile gets a copy of the header.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::{ExtractStrategy, OsmBuilder};
+    /// # use vadeen_osm::geo::Tile;
+    /// let mut builder = OsmBuilder::default();
+    /// builder.add_polyline(vec![(1.0, -1.0), (1.0, 1.0)], vec![("highway", "track")]);
+    /// let osm = builder.build();
+    ///
+    /// let tiles = osm.split_tiles(1, ExtractStrategy::CompleteWays);
+    /// assert_eq!(tiles.len(), 2);
+    ///
+    /// let tile = &tiles[&Tile::new(1, 0, 0).unwrap()];
+    /// assert_eq!(tile.nodes.len(), 2);
+    /// assert_eq!(tile.ways.len(), 1);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if zoom is above [`MAX_ZOOM`].
+    ///
+    /// [`extract`]: #method.extract
+    /// [`MAX_ZOOM`]: geo/constant.MAX_ZOOM.html
+    pub fn split_tiles(&self, zoom: u8, strategy: ExtractStrategy) -> BTreeMap<Tile, Osm> {
+        assert!(
+            zoom <= MAX_ZOOM,
+            "Zoom must be 0-{}, got {}",
+            MAX_ZOOM,
+            zoom
+        );
+
+        // Deleted nodes in history data have no coordinate, they follow their visible versions.
+        let node_tiles: HashMap<i64, Tile> = self
+            .nodes
+            .iter()
+            .filter(|n| n.meta.visible)
+            .filter_map(|n| Some((n.id, Tile::from_coordinate(n.coordinate, zoom)?)))
+            .collect();
+
+        let mut way_tiles: HashMap<i64, HashSet<Tile>> = self
+            .ways
+            .iter()
+            .map(|w| {
+                let tiles = w.refs.iter().filter_map(|r| node_tiles.get(r)).cloned();
+                (w.id, tiles.collect())
+            })
+            .collect();
+
+        if strategy == ExtractStrategy::Smart {
+            for rel in self.relations.iter().filter(|r| is_multipolygon(r)) {
+                let ways = member_way_ids(rel);
+                let tiles: HashSet<Tile> = ways
+                    .iter()
+                    .filter_map(|id| way_tiles.get(id))
+                    .flatten()
+                    .cloned()
+                    .collect();
+
+                for id in ways {
+                    if let Some(way_tiles) = way_tiles.get_mut(&id) {
+                        way_tiles.extend(&tiles);
+                    }
+                }
+            }
+        }
+
+        let mut tiles: BTreeMap<Tile, Osm> = BTreeMap::new();
+        let mut tile_nodes: HashMap<Tile, HashSet<i64>> = HashMap::new();
+        for node in &self.nodes {
+            let tile = match node_tiles.get(&node.id) {
+                Some(tile) => *tile,
+                None => continue,
+            };
+            tile_nodes.entry(tile).or_default().insert(node.id);
+            tiles
+                .entry(tile)
+                .or_insert_with(|| self.empty_extract())
+                .add_node(node.clone());
+        }
+
+        // Nodes of ways crossing into the tile.
+        if strategy != ExtractStrategy::Simple {
+            for way in &self.ways {
+                for tile in &way_tiles[&way.id] {
+                    let nodes = tile_nodes.get_mut(tile).unwrap();
+                    for node in way.refs.iter().filter_map(|r| self.find_node(*r)) {
+                        if nodes.insert(node.id) {
+                            tiles.get_mut(tile).unwrap().add_node(node.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut member_tiles: HashMap<i64, HashSet<Tile>> = HashMap::new();
+        for way in &self.ways {
+            for tile in &way_tiles[&way.id] {
+                let mut way = way.clone();
+                way.refs.retain(|r| tile_nodes[tile].contains(r));
+                if way.refs.len() >= 2 {
+                    member_tiles.entry(way.id).or_default().insert(*tile);
+                    tiles.get_mut(tile).unwrap().add_way(way);
+                }
+            }
+        }
+
+        let relation_tiles = self.relation_tiles(&tile_nodes, &member_tiles);
+        for rel in &self.relations {
+            for tile in &relation_tiles[&rel.id] {
+                let mut rel = rel.clone();
+                rel.members.retain(|m| match m {
+                    RelationMember::Node(id, _) => tile_nodes[tile].contains(id),
+                    RelationMember::Way(id, _) => in_tile(&member_tiles, *id, tile),
+                    RelationMember::Relation(id, _) => in_tile(&relation_tiles, *id, tile),
+                });
+                tiles.get_mut(tile).unwrap().add_relation(rel);
+            }
+        }
+
+        for osm in tiles.values_mut() {
+            clear_empty_boundary(osm);
+        }
+        tiles
+    }
+
+    /// A map with the same header but without elements, to extract elements into.
+    fn empty_extract(&self) -> Osm {
+        Osm {
+            header: self.header.clone(),
+            ..Osm::default()
+        }
+    }
+
+    /// Relations with any node or way member among the included ones, and the relations that
+    /// (recursively) has any of those relations as members.
+    fn included_relations(&self, node_ids: &HashSet<i64>, way_ids: &HashSet<i64>) -> HashSet<i64> {
+        let mut relation_ids: HashSet<i64> = self
+            .relations
+            .iter()
+            .filter(|r| {
+                r.members.iter().any(|m| match m {
+                    RelationMember::Node(id, _) => node_ids.contains(id),
+                    RelationMember::Way(id, _) => way_ids.contains(id),
+                    RelationMember::Relation(_, _) => false,
+                })
+            })
+            .map(|r| r.id)
+            .collect();
+
+        loop {
+            let parents: Vec<i64> = self
+                .relations
+                .iter()
+                .filter(|r| !relation_ids.contains(&r.id))
+                .filter(|r| {
+                    r.members.iter().any(|m| match m {
+                        RelationMember::Relation(id, _) => relation_ids.contains(id),
+                        _ => false,
+                    })
+                })
+                .map(|r| r.id)
+                .collect();
+
+            if parents.is_empty() {
+                return relation_ids;
+            }
+            relation_ids.extend(parents);
+        }
+    }
+
+    /// The tiles of each relation, same rules as `included_relations`. Way tiles are the tiles
+    /// where each way was included.
+    fn relation_tiles(
+        &self,
+        tile_nodes: &HashMap<Tile, HashSet<i64>>,
+        way_tiles: &HashMap<i64, HashSet<Tile>>,
+    ) -> HashMap<i64, HashSet<Tile>> {
+        let mut node_tiles: HashMap<i64, HashSet<Tile>> = HashMap::new();
+        for (tile, nodes) in tile_nodes {
+            for id in nodes {
+                node_tiles.entry(*id).or_default().insert(*tile);
+            }
+        }
+
+        let mut relation_tiles: HashMap<i64, HashSet<Tile>> = HashMap::new();
+        for rel in &self.relations {
+            let tiles = rel
+                .members
+                .iter()
+                .filter_map(|m| match m {
+                    RelationMember::Node(id, _) => node_tiles.get(id),
+                    RelationMember::Way(id, _) => way_tiles.get(id),
+                    RelationMember::Relation(_, _) => None,
+                })
+                .flatten()
+                .cloned();
+            relation_tiles.insert(rel.id, tiles.collect());
+        }
+
+        // Propagate tiles to parent relations until nothing changes.
+        loop {
+            let mut changed = false;
+            for rel in &self.relations {
+                let tiles: HashSet<Tile> = rel
+                    .members
+                    .iter()
+                    .filter_map(|m| match m {
+                        RelationMember::Relation(id, _) => relation_tiles.get(id),
+                        _ => None,
+                    })
+                    .flatten()
+                    .cloned()
+                    .collect();
+
+                let entry = relation_tiles.entry(rel.id).or_default();
+                let len = entry.len();
+                entry.extend(tiles);
+                changed |= entry.len() != len;
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        relation_tiles
+    }
+}
+
+/// Nothing expands the boundary if no visible nodes are added, leaving it inverted.
+fn clear_empty_boundary(osm: &mut Osm) {
+    if osm.boundary.as_ref().is_some_and(|b| b.is_empty()) {
+        osm.boundary = None;
+    }
+}
+
+fn is_multipolygon(rel: &Relation) -> bool {
+    rel.meta
+        .tags
+        .iter()
+        .any(|t| t.key == "type" && t.value == "multipolygon")
+}
+
+fn is_way_in(member: &RelationMember, way_ids: &HashSet<i64>) -> bool {
+    match member {
+        RelationMember::Way(id, _) => way_ids.contains(id),
+        _ => false,
+    }
+}
+
+fn in_tile(tiles: &HashMap<i64, HashSet<Tile>>, id: i64, tile: &Tile) -> bool {
+    tiles.get(&id).is_some_and(|tiles| tiles.contains(tile))
+}
+
+fn member_way_ids(rel: &Relation) -> Vec<i64> {
+    rel.members
+        .iter()
+        .filter_map(|m| match m {
+            RelationMember::Way(id, _) => Some(*id),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geo::{Boundary, Polygon, Tile};
+    use crate::{ExtractStrategy, Meta, Node, Osm, OsmHeader, Relation, RelationMember, Way};
+
+    /// Nodes 1-3 are inside (0,0)-(2,2), 4-6 outside. Way 10 crosses the edge, way 11 is outside
+    /// but part of the same multipolygon as way 10. Relation 21 contains relation 20.
+    fn create_osm() -> Osm {
+        let mut osm = Osm::default();
+        let coordinates = [
+            (1.0, 1.0),
+            (1.5, 1.0),
+            (1.5, 1.5),
+            (1.0, 3.0),
+            (5.0, 5.0),
+            (5.0, 6.0),
+        ];
+        for (i, c) in coordinates.iter().enumerate() {
+            osm.add_node(Node {
+                id: i as i64 + 1,
+                coordinate: (*c).into(),
+                meta: Meta::default(),
+            });
+        }
+
+        osm.add_way(Way {
+            id: 10,
+            refs: vec![1, 2, 4],
+            meta: Meta::default(),
+        });
+        osm.add_way(Way {
+            id: 11,
+            refs: vec![5, 6, 5],
+            meta: Meta::default(),
+        });
+        osm.add_relation(Relation {
+            id: 20,
+            members: vec![
+                RelationMember::Way(10, "outer".to_owned()),
+                RelationMember::Way(11, "outer".to_owned()),
+                RelationMember::Node(6, "".to_owned()),
+            ],
+            meta: Meta {
+                tags: vec![("type", "multipolygon").into()],
+                ..Meta::default()
+            },
+        });
+        osm.add_relation(Relation {
+            id: 21,
+            members: vec![RelationMember::Relation(20, "".to_owned())],
+            meta: Meta::default(),
+        });
+        osm.add_relation(Relation {
+            id: 22,
+            members: vec![RelationMember::Node(5, "".to_owned())],
+            meta: Meta::default(),
+        });
+        osm
+    }
+
+    fn ids<T>(elements: &[T], id: fn(&T) -> i64) -> Vec<i64> {
+        elements.iter().map(id).collect()
+    }
+
+    #[test]
+    fn extract_simple() {
+        let osm = create_osm();
+        let extract = osm.extract(
+            Boundary::new((0.0, 0.0), (2.0, 2.0)),
+            ExtractStrategy::Simple,
+        );
+
+        assert_eq!(ids(&extract.nodes, |n| n.id), vec![1, 2, 3]);
+        assert_eq!(extract.ways[0].refs, vec![1, 2]);
+        assert_eq!(ids(&extract.relations, |r| r.id), vec![20, 21]);
+        assert_eq!(
+            extract.relations[0].members,
+            vec![RelationMember::Way(10, "outer".to_owned())]
+        );
+        assert_eq!(
+            extract.boundary,
+            Some(Boundary::new((1.0, 1.0), (1.5, 1.5)))
+        );
+    }
+
+    #[test]
+    fn extract_complete_ways() {
+        let osm = create_osm();
+        let extract = osm.extract(
+            Boundary::new((0.0, 0.0), (2.0, 2.0)),
+            ExtractStrategy::CompleteWays,
+        );
+
+        assert_eq!(ids(&extract.nodes, |n| n.id), vec![1, 2, 3, 4]);
+        assert_eq!(ids(&extract.ways, |w| w.id), vec![10]);
+        assert_eq!(extract.ways[0].refs, vec![1, 2, 4]);
+        assert_eq!(ids(&extract.relations, |r| r.id), vec![20, 21]);
+    }
+
+    #[test]
+    fn extract_smart() {
+        let osm = create_osm();
+        let extract = osm.extract(
+            Boundary::new((0.0, 0.0), (2.0, 2.0)),
+            ExtractStrategy::Smart,
+        );
+
+        assert_eq!(ids(&extract.nodes, |n| n.id), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(ids(&extract.ways, |w| w.id), vec![10, 11]);
+        assert_eq!(ids(&extract.relations, |r| r.id), vec![20, 21, 22]);
+        assert_eq!(extract.relations[0].members.len(), 3);
+        assert_eq!(extract.find_way(11).unwrap().refs, vec![5, 6, 5]);
+    }
+
+    #[test]
+    fn extract_polygon() {
+        let osm = create_osm();
+        let triangle = Polygon::new(
+            vec![(0.0, 0.0).into(), (2.0, 0.0).into(), (0.0, 2.0).into()],
+            vec![],
+        );
+        let extract = osm.extract(triangle, ExtractStrategy::Simple);
+
+        assert_eq!(ids(&extract.nodes, |n| n.id), vec![1]);
+    }
+
+    #[test]
+    fn extract_empty() {
+        let osm = create_osm();
+        let extract = osm.extract(
+            Boundary::new((10.0, 10.0), (12.0, 12.0)),
+            ExtractStrategy::Simple,
+        );
+        assert!(extract.nodes.is_empty());
+        assert_eq!(extract.boundary, None);
+    }
+
+    #[test]
+    fn extract_keeps_header() {
+        let mut osm = create_osm();
+        osm.header = OsmHeader {
+            source: Some("https://example.com".to_owned()),
+            ..OsmHeader::default()
+        };
+
+        let extract = osm.extract(
+            Boundary::new((0.0, 0.0), (2.0, 2.0)),
+            ExtractStrategy::Simple,
+        );
+        assert_eq!(extract.header, osm.header);
+
+        for tile in osm.split_tiles(7, ExtractStrategy::Simple).values() {
+            assert_eq!(tile.header, osm.header);
+        }
+    }
+
+    #[test]
+    fn split_tiles_simple() {
+        let osm = create_osm();
+        let tiles = osm.split_tiles(7, ExtractStrategy::Simple);
+        let keys: Vec<Tile> = tiles.keys().cloned().collect();
+        assert_eq!(
+            keys,
+            vec![
+                Tile::new(7, 64, 63).unwrap(),
+                Tile::new(7, 65, 62).unwrap(),
+                Tile::new(7, 65, 63).unwrap(),
+                Tile::new(7, 66, 62).unwrap(),
+            ]
+        );
+
+        let tile = &tiles[&Tile::new(7, 64, 63).unwrap()];
+        assert_eq!(ids(&tile.nodes, |n| n.id), vec![1, 2, 3]);
+        assert_eq!(tile.ways[0].refs, vec![1, 2]);
+        assert_eq!(ids(&tile.relations, |r| r.id), vec![20, 21]);
+
+        let tile = &tiles[&Tile::new(7, 65, 63).unwrap()];
+        assert_eq!(ids(&tile.nodes, |n| n.id), vec![4]);
+        assert!(tile.ways.is_empty());
+        assert!(tile.relations.is_empty());
+
+        let tile = &tiles[&Tile::new(7, 66, 62).unwrap()];
+        assert_eq!(ids(&tile.relations, |r| r.id), vec![20, 21]);
+        assert_eq!(
+            tile.relations[0].members,
+            vec![RelationMember::Node(6, "".to_owned())]
+        );
+    }
+
+    #[test]
+    fn split_tiles_complete_ways() {
+        let osm = create_osm();
+        let tiles = osm.split_tiles(7, ExtractStrategy::CompleteWays);
+
+        let tile = &tiles[&Tile::new(7, 65, 63).unwrap()];
+        assert_eq!(ids(&tile.nodes, |n| n.id), vec![4, 1, 2]);
+        assert_eq!(tile.ways[0].refs, vec![1, 2, 4]);
+        assert_eq!(ids(&tile.relations, |r| r.id), vec![20, 21]);
+        assert_eq!(
+            tile.relations[0].members,
+            vec![RelationMember::Way(10, "outer".to_owned())]
+        );
+    }
+
+    #[test]
+    fn split_tiles_smart() {
+        let osm = create_osm();
+        let tiles = osm.split_tiles(7, ExtractStrategy::Smart);
+
+        for tile in tiles.values() {
+            assert_eq!(ids(&tile.ways, |w| w.id), vec![10, 11]);
+            for id in &[1, 2, 4, 5, 6] {
+                assert!(tile.find_node(*id).is_some());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Zoom must be 0-31, got 32")]
+    fn split_tiles_invalid_zoom() {
+        create_osm().split_tiles(32, ExtractStrategy::Simple);
+    }
+
+    #[test]
+    fn deleted_history_versions() {
+        let mut osm = create_osm();
+        osm.add_node(Node {
+            id: 1,
+            meta: Meta {
+                visible: false,
+                ..Meta::default()
+            },
+            ..Node::default()
+        });
+
+        let origin = Boundary::new((-0.5, -0.5), (0.5, 0.5));
+        assert!(osm
+            .extract(origin, ExtractStrategy::Simple)
+            .nodes
+            .is_empty());
+
+        let extract = osm.extract(
+            Boundary::new((0.0, 0.0), (2.0, 2.0)),
+            ExtractStrategy::Simple,
+        );
+        assert_eq!(ids(&extract.nodes, |n| n.id), vec![1, 2, 3, 1]);
+        assert_eq!(
+            extract.boundary,
+            Some(Boundary::new((1.0, 1.0), (1.5, 1.5)))
+        );
+
+        let tiles = osm.split_tiles(7, ExtractStrategy::Simple);
+        assert_eq!(tiles.len(), 4);
+        let tile = &tiles[&Tile::new(7, 64, 63).unwrap()];
+        assert_eq!(ids(&tile.nodes, |n| n.id), vec![1, 2, 3, 1]);
+    }
+}
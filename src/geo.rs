@@ -1,8 +1,28 @@
 //! General geographic data structures.
+mod poly;
+mod polygon;
+mod projection;
+mod simplify;
+mod tile;
+
+pub use self::poly::*;
+pub use self::polygon::*;
+pub use self::projection::*;
+pub use self::simplify::*;
+pub use self::tile::*;
+use std::cmp::{max, min};
 use std::ops::{Add, Sub};
 
 const COORD_PRECISION: f64 = 10_000_000.0;
 
+/// Mean radius of the earth in metres, used for distance calculations.
+pub(crate) const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// 180 and 360 degrees in the internal coordinate format.
+const LON_180: i64 = 1_800_000_000;
+const LON_360: i64 = 2 * LON_180;
+const LAT_90: i32 = 900_000_000;
+
 /// Represents a coordinate containing latitude and longitude.
 ///
 /// Coordinates are usually represented by floating point numbers, for coordinates in the osm system
@@ -64,10 +84,26 @@ pub struct Coordinate {
 /// assert_eq!(bounds.max.lat(), 30.0);
 /// assert_eq!(bounds.max.lon(), 40.0);
 /// ```
+///
+/// A boundary crossing the antimeridian has a min longitude greater than its max longitude, i.e.
+/// `min` is always the south west corner and `max` the north east corner.
+/// ```
+/// # use vadeen_osm::geo::Boundary;
+/// let fiji = Boundary::new((-21.0, 177.0), (-12.0, -178.0));
+/// assert!(fiji.crosses_antimeridian());
+/// assert!(fiji.contains((-18.0, 179.0).into()));
+/// assert!(fiji.contains((-18.0, -179.0).into()));
+/// assert!(!fiji.contains((-18.0, 0.0).into()));
+/// assert_eq!(fiji.width(), 5.0);
+/// ```
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Boundary {
     pub min: Coordinate,
     pub max: Coordinate,
+
+    /// A frozen boundary is not changed by `expand`. Readers freeze the boundary read from the
+    /// file while the nodes are added, so the boundary is kept as it was in the file.
     pub freeze: bool,
 }
 
@@ -82,6 +118,15 @@ impl Coordinate {
         }
     }
 
+    /// Like `new` but rounds to the nearest representable coordinate instead of truncating, so
+    /// degrees converted from a coordinate convert back to the same coordinate.
+    pub(crate) fn rounded(lat: f64, lon: f64) -> Coordinate {
+        Coordinate {
+            lat: (lat * COORD_PRECISION).round() as i32,
+            lon: (lon * COORD_PRECISION).round() as i32,
+        }
+    }
+
     pub fn lat(self) -> f64 {
         self.lat as f64 / COORD_PRECISION
     }
@@ -89,6 +134,23 @@ impl Coordinate {
     pub fn lon(self) -> f64 {
         self.lon as f64 / COORD_PRECISION
     }
+
+    /// Great circle distance in metres to another coordinate, using the haversine formula.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::geo::Coordinate;
+    /// let a = Coordinate::new(0.0, 0.0);
+    /// assert_eq!(a.distance(Coordinate::new(1.0, 0.0)).round(), 111195.0);
+    /// ```
+    pub fn distance(self, other: Coordinate) -> f64 {
+        let (lat1, lat2) = (self.lat().to_radians(), other.lat().to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.lon() - self.lon()).to_radians();
+
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().asin()
+    }
 }
 
 impl Sub for Coordinate {
@@ -138,25 +200,344 @@ impl Boundary {
         }
     }
 
-    /// Expand boundary if necessary to include a coordinate.
+    /// Create the smallest boundary containing all coordinates. The boundary is empty if there
+    /// are no coordinates. The result never crosses the antimeridian.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::geo::Boundary;
+    /// let boundary = Boundary::from_coordinates(vec![(1.0, 5.0), (3.0, 2.0)]);
+    /// assert_eq!(boundary, Boundary::new((1.0, 2.0), (3.0, 5.0)));
+    ///
+    /// let boundary = Boundary::from_coordinates(Vec::<(f64, f64)>::new());
+    /// assert!(boundary.is_empty());
+    /// ```
+    pub fn from_coordinates<I, C>(coordinates: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<Coordinate>,
+    {
+        let mut boundary = Boundary::inverted();
+        for c in coordinates {
+            boundary.expand(c.into());
+        }
+        boundary
+    }
+
+    /// An empty boundary contains nothing, e.g. an `inverted()` boundary that has not been
+    /// expanded.
+    pub fn is_empty(&self) -> bool {
+        self.min.lat > self.max.lat
+    }
+
+    /// True if the boundary crosses the antimeridian, i.e. min longitude is greater than max.
+    pub fn crosses_antimeridian(&self) -> bool {
+        !self.is_empty() && self.min.lon > self.max.lon
+    }
+
+    /// Check if a coordinate is within the boundary. Coordinates on the edge are included.
+    pub fn contains(&self, c: Coordinate) -> bool {
+        c.lat >= self.min.lat && c.lat <= self.max.lat && self.lon_range().contains(c.lon)
+    }
+
+    /// Check if two boundaries share any area. Touching edges counts as intersecting.
+    pub fn intersects(&self, other: &Boundary) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// The area covered by both boundaries, or `None` if they do not intersect.
+    ///
+    /// If one of the boundaries crosses the antimeridian, they can overlap in two separate areas.
+    /// The returned boundary covers both of them in that case.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::geo::Boundary;
+    /// let a = Boundary::new((0.0, 0.0), (10.0, 10.0));
+    /// let b = Boundary::new((5.0, 5.0), (20.0, 20.0));
+    /// assert_eq!(a.intersection(&b), Some(Boundary::new((5.0, 5.0), (10.0, 10.0))));
+    ///
+    /// let c = Boundary::new((15.0, 15.0), (20.0, 20.0));
+    /// assert_eq!(a.intersection(&c), None);
+    /// ```
+    pub fn intersection(&self, other: &Boundary) -> Option<Boundary> {
+        if self.is_empty() || other.is_empty() {
+            return None;
+        }
+
+        let min_lat = max(self.min.lat, other.min.lat);
+        let max_lat = min(self.max.lat, other.max.lat);
+        if min_lat > max_lat {
+            return None;
+        }
+
+        let (a, b) = (self.lon_range(), other.lon_range());
+        let lon = match (a.overlap(b), b.overlap(a)) {
+            (Some(x), Some(y)) if x == y => x,
+            (Some(x), Some(y)) => x.union(y),
+            (Some(x), None) | (None, Some(x)) => x,
+            (None, None) => return None,
+        };
+        Some(lon.boundary(min_lat, max_lat))
+    }
+
+    /// The smallest boundary covering both boundaries. Crosses the antimeridian if that gives a
+    /// smaller boundary.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::geo::Boundary;
+    /// let a = Boundary::new((0.0, 170.0), (10.0, 175.0));
+    /// let b = Boundary::new((5.0, -175.0), (20.0, -170.0));
+    /// assert_eq!(a.union(&b), Boundary::new((0.0, 170.0), (20.0, -170.0)));
+    /// ```
+    pub fn union(&self, other: &Boundary) -> Boundary {
+        if self.is_empty() {
+            return Boundary::new(other.min, other.max);
+        }
+        if other.is_empty() {
+            return Boundary::new(self.min, self.max);
+        }
+
+        let min_lat = min(self.min.lat, other.min.lat);
+        let max_lat = max(self.max.lat, other.max.lat);
+        self.lon_range()
+            .union(other.lon_range())
+            .boundary(min_lat, max_lat)
+    }
+
+    /// The center of the boundary.
+    pub fn center(&self) -> Coordinate {
+        let range = self.lon_range();
+        Coordinate {
+            lat: ((i64::from(self.min.lat) + i64::from(self.max.lat)) / 2) as i32,
+            lon: normalize_lon(range.start + range.width / 2) as i32,
+        }
+    }
+
+    /// Width in degrees longitude.
+    pub fn width(&self) -> f64 {
+        max(self.lon_range().width, 0) as f64 / COORD_PRECISION
+    }
+
+    /// Height in degrees latitude.
+    pub fn height(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        (i64::from(self.max.lat) - i64::from(self.min.lat)) as f64 / COORD_PRECISION
+    }
+
+    /// Width in metres, measured along the widest parallel, i.e. the one closest to equator.
+    pub fn width_metres(&self) -> f64 {
+        let lat = if self.min.lat > 0 {
+            self.min.lat()
+        } else if self.max.lat < 0 {
+            self.max.lat()
+        } else {
+            0.0
+        };
+        self.width().to_radians() * lat.to_radians().cos() * EARTH_RADIUS
+    }
+
+    /// Height in metres.
+    pub fn height_metres(&self) -> f64 {
+        self.height().to_radians() * EARTH_RADIUS
+    }
+
+    /// Create a new boundary expanded by a distance in metres in every direction. Latitude is
+    /// limited to the poles, and the boundary covers all longitudes if it would wrap around the
+    /// world.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::geo::Boundary;
+    /// let boundary = Boundary::new((0.0, 0.0), (0.0, 0.0)).buffer(1000.0);
+    /// assert_eq!(boundary.min, (-0.0089932, -0.0089932).into());
+    /// assert_eq!(boundary.max, (0.0089932, 0.0089932).into());
+    /// ```
+    pub fn buffer(&self, metres: f64) -> Boundary {
+        if self.is_empty() {
+            return Boundary::new(self.min, self.max);
+        }
+
+        let lat_delta = (metres / EARTH_RADIUS).to_degrees();
+        let min_lat = (self.min.lat() - lat_delta).max(-90.0);
+        let max_lat = (self.max.lat() + lat_delta).min(90.0);
+
+        // Longitude degrees are shortest at the parallel closest to the poles.
+        let cos = min_lat.abs().max(max_lat.abs()).to_radians().cos();
+        let range = self.lon_range();
+        let lon_delta = (lat_delta / cos * COORD_PRECISION).min(LON_360 as f64) as i64;
+        let lon = LonRange {
+            start: range.start - lon_delta,
+            width: range.width + 2 * lon_delta,
+        };
+
+        lon.boundary(
+            (min_lat * COORD_PRECISION).round() as i32,
+            (max_lat * COORD_PRECISION).round() as i32,
+        )
+    }
+
+    /// Expand boundary if necessary to include a coordinate. Nothing is done if the boundary is
+    /// frozen.
+    ///
+    /// A boundary crossing the antimeridian is expanded in the direction giving the smallest
+    /// boundary.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::geo::Boundary;
+    /// let mut boundary = Boundary::new((0.0, 0.0), (1.0, 1.0));
+    /// boundary.freeze = true;
+    /// boundary.expand((5.0, 5.0).into());
+    /// assert_eq!(boundary, Boundary { freeze: true, ..Boundary::new((0.0, 0.0), (1.0, 1.0)) });
+    /// ```
     pub fn expand(&mut self, c: Coordinate) {
         if self.freeze {
             return;
         }
 
+        if self.crosses_antimeridian() {
+            if !self.lon_range().contains(c.lon) {
+                let east = (i64::from(c.lon) - i64::from(self.max.lon)).rem_euclid(LON_360);
+                let west = (i64::from(self.min.lon) - i64::from(c.lon)).rem_euclid(LON_360);
+                if east < west {
+                    self.max.lon = c.lon;
+                } else {
+                    self.min.lon = c.lon;
+                }
+            }
+        } else {
+            if c.lon > self.max.lon {
+                self.max.lon = c.lon;
+            }
+            if c.lon < self.min.lon {
+                self.min.lon = c.lon;
+            }
+        }
+
         if c.lat > self.max.lat {
             self.max.lat = c.lat;
         }
         if c.lat < self.min.lat {
             self.min.lat = c.lat;
         }
-        if c.lon > self.max.lon {
-            self.max.lon = c.lon;
+    }
+
+    fn lon_range(&self) -> LonRange {
+        let (min, max) = (i64::from(self.min.lon), i64::from(self.max.lon));
+        if self.is_empty() {
+            LonRange {
+                start: min,
+                width: -1,
+            }
+        } else if min > max {
+            LonRange {
+                start: min,
+                width: max - min + LON_360,
+            }
+        } else {
+            LonRange {
+                start: min,
+                width: max - min,
+            }
         }
-        if c.lon < self.min.lon {
-            self.min.lon = c.lon;
+    }
+}
+
+/// A longitude interval going east from `start`, may wrap around the antimeridian. A negative
+/// width is empty.
+#[derive(Debug, PartialEq, Copy, Clone)]
+struct LonRange {
+    start: i64,
+    width: i64,
+}
+
+impl LonRange {
+    fn contains(self, lon: i32) -> bool {
+        offset(self.start, i64::from(lon)) <= self.width || self.width >= LON_360
+    }
+
+    /// The part of `other` that starts within this range.
+    fn overlap(self, other: LonRange) -> Option<LonRange> {
+        let offset = offset(self.start, other.start);
+        if offset > self.width && self.width < LON_360 {
+            return None;
         }
+
+        Some(LonRange {
+            start: other.start,
+            width: min(other.width, self.width - offset),
+        })
     }
+
+    /// The smallest range covering both ranges.
+    fn union(self, other: LonRange) -> LonRange {
+        let candidates = [
+            self,
+            other,
+            LonRange {
+                start: self.start,
+                width: offset(self.start, other.start) + other.width,
+            },
+            LonRange {
+                start: other.start,
+                width: offset(other.start, self.start) + self.width,
+            },
+        ];
+
+        candidates
+            .iter()
+            .filter(|c| c.covers(self) && c.covers(other))
+            .min_by_key(|c| c.width)
+            .cloned()
+            .unwrap_or(LonRange {
+                start: -LON_180,
+                width: LON_360,
+            })
+    }
+
+    fn covers(self, other: LonRange) -> bool {
+        self.width >= LON_360 || offset(self.start, other.start) + other.width <= self.width
+    }
+
+    fn boundary(self, min_lat: i32, max_lat: i32) -> Boundary {
+        let (min_lon, max_lon) = if self.width >= LON_360 {
+            (-LON_180, LON_180)
+        } else {
+            let min_lon = normalize_lon(self.start);
+            let max_lon = normalize_lon(self.start + self.width);
+            if max_lon == -LON_180 && self.width > 0 {
+                (min_lon, LON_180)
+            } else {
+                (min_lon, max_lon)
+            }
+        };
+
+        Boundary {
+            min: Coordinate {
+                lat: max(min_lat, -LAT_90),
+                lon: min_lon as i32,
+            },
+            max: Coordinate {
+                lat: min(max_lat, LAT_90),
+                lon: max_lon as i32,
+            },
+            freeze: false,
+        }
+    }
+}
+
+/// Distance going east from one longitude to another.
+fn offset(from: i64, to: i64) -> i64 {
+    (to - from).rem_euclid(LON_360)
+}
+
+/// Normalize longitude to -180 <= lon < 180.
+fn normalize_lon(lon: i64) -> i64 {
+    (lon + LON_180).rem_euclid(LON_360) - LON_180
 }
 
 impl Default for Boundary {
@@ -168,3 +549,111 @@ impl Default for Boundary {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::geo::Boundary;
+
+    #[test]
+    fn boundary_contains() {
+        let boundary = Boundary::new((0.0, 0.0), (10.0, 10.0));
+        assert!(boundary.contains((0.0, 0.0).into()));
+        assert!(boundary.contains((10.0, 10.0).into()));
+        assert!(!boundary.contains((10.0, 10.1).into()));
+        assert!(!Boundary::inverted().contains((0.0, 0.0).into()));
+        assert!(Boundary::default().contains((90.0, 180.0).into()));
+    }
+
+    #[test]
+    fn boundary_intersection() {
+        let a = Boundary::new((0.0, 170.0), (10.0, -170.0));
+        let b = Boundary::new((5.0, 175.0), (20.0, -160.0));
+        assert_eq!(
+            a.intersection(&b),
+            Some(Boundary::new((5.0, 175.0), (10.0, -170.0)))
+        );
+
+        let c = Boundary::new((0.0, -175.0), (10.0, 0.0));
+        assert_eq!(
+            a.intersection(&c),
+            Some(Boundary::new((0.0, -175.0), (10.0, -170.0)))
+        );
+        assert!(a.intersects(&c));
+
+        let d = Boundary::new((0.0, -160.0), (10.0, 160.0));
+        assert_eq!(a.intersection(&d), None);
+        assert!(!a.intersects(&d));
+
+        // Overlaps on both sides of the antimeridian.
+        let e = Boundary::new((0.0, -175.0), (10.0, 175.0));
+        assert_eq!(
+            a.intersection(&e),
+            Some(Boundary::new((0.0, 170.0), (10.0, -170.0)))
+        );
+
+        assert_eq!(a.intersection(&Boundary::inverted()), None);
+    }
+
+    #[test]
+    fn boundary_union() {
+        let a = Boundary::new((0.0, 0.0), (10.0, 10.0));
+        let b = Boundary::new((-5.0, 20.0), (5.0, 30.0));
+        assert_eq!(a.union(&b), Boundary::new((-5.0, 0.0), (10.0, 30.0)));
+        assert_eq!(a.union(&Boundary::inverted()), a);
+        assert_eq!(Boundary::inverted().union(&a), a);
+
+        let c = Boundary::new((0.0, 160.0), (10.0, -100.0));
+        let d = Boundary::new((0.0, -110.0), (10.0, 170.0));
+        assert_eq!(c.union(&d), Boundary::new((0.0, -180.0), (10.0, 180.0)));
+    }
+
+    #[test]
+    fn boundary_size() {
+        let boundary = Boundary::new((10.0, 170.0), (20.0, -175.0));
+        assert_eq!(boundary.center(), (15.0, 177.5).into());
+        assert_eq!(boundary.width(), 15.0);
+        assert_eq!(boundary.height(), 10.0);
+        assert_eq!(boundary.height_metres().round(), 1_111_951.0);
+        assert_eq!(boundary.width_metres().round(), 1_642_587.0);
+
+        let boundary = Boundary::new((10.0, 170.0), (20.0, 179.0));
+        assert_eq!(boundary.center(), (15.0, 174.5).into());
+        assert_eq!(Boundary::inverted().width(), 0.0);
+        assert_eq!(Boundary::inverted().height(), 0.0);
+    }
+
+    #[test]
+    fn boundary_buffer() {
+        let boundary = Boundary::new((89.0, 179.0), (89.5, 179.5)).buffer(100_000.0);
+        assert_eq!(boundary.max.lat(), 90.0);
+        assert_eq!(boundary.min.lon(), -180.0);
+        assert_eq!(boundary.max.lon(), 180.0);
+
+        let boundary = Boundary::new((0.0, 179.9), (0.0, 179.9)).buffer(100_000.0);
+        assert!(boundary.crosses_antimeridian());
+        assert!(boundary.contains((0.0, -179.9).into()));
+    }
+
+    #[test]
+    fn boundary_expand() {
+        let mut boundary = Boundary::new((0.0, 170.0), (0.0, -170.0));
+        boundary.expand((1.0, -160.0).into());
+        assert_eq!(boundary, Boundary::new((0.0, 170.0), (1.0, -160.0)));
+        boundary.expand((1.0, 165.0).into());
+        assert_eq!(boundary, Boundary::new((0.0, 165.0), (1.0, -160.0)));
+        boundary.expand((1.0, 180.0).into());
+        assert_eq!(boundary, Boundary::new((0.0, 165.0), (1.0, -160.0)));
+    }
+
+    #[test]
+    fn boundary_freeze() {
+        let mut boundary = Boundary::inverted();
+        boundary.freeze = true;
+        boundary.expand((1.0, 1.0).into());
+        assert!(boundary.is_empty());
+
+        boundary.freeze = false;
+        boundary.expand((1.0, 1.0).into());
+        assert_eq!(boundary, Boundary::new((1.0, 1.0), (1.0, 1.0)));
+    }
+}
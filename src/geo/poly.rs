@@ -0,0 +1,362 @@
+//! Reading and writing of polygon filter files (.poly).
+//! See: https://wiki.openstreetmap.org/wiki/Osmosis/Polygon_Filter_File_Format
+use crate::geo::{ring_contains, Coordinate, MultiPolygon, Polygon};
+use crate::osm_io::error::{Error, ErrorKind, Result};
+use crate::{Osm, Relation};
+use std::io::{BufRead, Write};
+
+/// A polygon filter file, describes an area as a name and a multipolygon.
+///
+/// Each section of the file is a ring. Sections with a name starting with `!` are holes, they are
+/// assigned to the outer ring they are located in.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::geo::PolyFile;
+/// let poly = "\
+/// australia_v
+/// first_area
+///      0.1446763E+03    -0.3825659E+02
+///      0.1446693E+03    -0.3826255E+02
+///      0.1446627E+03    -0.3825661E+02
+/// END
+/// END
+/// ";
+///
+/// let file = PolyFile::read(poly.as_bytes()).unwrap();
+/// assert_eq!(file.name, "australia_v");
+/// assert_eq!(file.area.polygons.len(), 1);
+/// assert!(file.area.contains((-38.258, 144.668).into()));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct PolyFile {
+    pub name: String,
+    pub area: MultiPolygon,
+}
+
+impl PolyFile {
+    pub fn new(name: &str, area: MultiPolygon) -> Self {
+        PolyFile {
+            name: name.to_owned(),
+            area,
+        }
+    }
+
+    /// Create a polygon filter from a multipolygon relation, e.g. an administrative boundary.
+    /// The name is taken from the `name` tag, or the relation id if there is no name.
+    ///
+    /// Returns an error if the multipolygon can not be assembled, see
+    /// [`MultiPolygon::from_relation`].
+    ///
+    /// [`MultiPolygon::from_relation`]: struct.MultiPolygon.html#method.from_relation
+    pub fn from_relation(osm: &Osm, relation: &Relation) -> Result<Self> {
+        let area = MultiPolygon::from_relation(osm, relation)?;
+        let name = relation
+            .meta
+            .tags
+            .iter()
+            .find(|t| t.key == "name")
+            .map(|t| t.value.clone())
+            .unwrap_or_else(|| relation.id.to_string());
+
+        Ok(PolyFile { name, area })
+    }
+
+    /// Parse a polygon filter file.
+    pub fn read<R: BufRead>(reader: R) -> Result<Self> {
+        let mut parser = PolyParser {
+            lines: reader.lines(),
+            line: 0,
+        };
+
+        parser.parse().map_err(|mut error| {
+            let location = error.location_mut();
+            if location.line.is_none() && parser.line > 0 {
+                location.line = Some(parser.line);
+            }
+            error
+        })
+    }
+
+    /// Write the polygon filter file. Outer rings are named by their number and holes by their
+    /// number prefixed by `!`.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, "{}", self.name)?;
+
+        let mut section = 1;
+        for polygon in &self.area.polygons {
+            write_section(&mut writer, &section.to_string(), &polygon.outer)?;
+            section += 1;
+
+            for ring in &polygon.inner {
+                write_section(&mut writer, &format!("!{}", section), ring)?;
+                section += 1;
+            }
+        }
+
+        writeln!(writer, "END")?;
+        Ok(())
+    }
+}
+
+/// Keeps track of the current line while parsing.
+struct PolyParser<R: BufRead> {
+    lines: std::io::Lines<R>,
+    line: u32,
+}
+
+impl<R: BufRead> PolyParser<R> {
+    fn parse(&mut self) -> Result<PolyFile> {
+        let name = self.expect_line()?;
+
+        let mut outer = Vec::new();
+        let mut inner = Vec::new();
+        loop {
+            let section = self.expect_line()?;
+            if section == "END" {
+                break;
+            }
+
+            let start = self.line;
+            let ring = self.parse_ring(&section, start)?;
+            if section.starts_with('!') {
+                inner.push((outer.len(), ring, start));
+            } else {
+                outer.push(Polygon::new(ring, vec![]));
+            }
+        }
+
+        for (previous, ring, start) in inner {
+            let containing = outer
+                .iter()
+                .position(|p| ring.iter().all(|c| ring_contains(&p.outer, *c)));
+
+            match containing.or_else(|| previous.checked_sub(1)) {
+                Some(i) => outer[i].inner.push(ring),
+                None => {
+                    let mut error = parse_error("Hole without outer ring.".to_owned());
+                    error.location_mut().line = Some(start);
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(PolyFile {
+            name,
+            area: MultiPolygon::new(outer),
+        })
+    }
+
+    /// Parse coordinates until the `END` of the section, which starts at line `start`.
+    fn parse_ring(&mut self, section: &str, start: u32) -> Result<Vec<Coordinate>> {
+        let mut ring = Vec::new();
+        loop {
+            let line = match self.next_line()? {
+                Some(line) => line,
+                None => {
+                    let message = format!("Section '{}' has no END.", section);
+                    let mut error = parse_error(message);
+                    error.location_mut().line = Some(start);
+                    return Err(error);
+                }
+            };
+            if line == "END" {
+                return Ok(ring);
+            }
+
+            let values: Vec<&str> = line.split_whitespace().collect();
+            match values.as_slice() {
+                [lon, lat] => ring.push(Coordinate::rounded(parse_f64(lat)?, parse_f64(lon)?)),
+                _ => {
+                    return Err(parse_error(format!(
+                        "Expected coordinate pair, found '{}'.",
+                        line
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Next non empty line, trimmed. Returns `None` at the end of the file.
+    fn next_line(&mut self) -> Result<Option<String>> {
+        loop {
+            match self.lines.next() {
+                Some(line) => {
+                    self.line += 1;
+                    let line = line?;
+                    let line = line.trim();
+                    if !line.is_empty() {
+                        return Ok(Some(line.to_owned()));
+                    }
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Same as `next_line`, but the end of the file is an error.
+    fn expect_line(&mut self) -> Result<String> {
+        self.next_line()?
+            .ok_or_else(|| parse_error("Unexpected end of file.".to_owned()))
+    }
+}
+
+fn parse_f64(s: &str) -> Result<f64> {
+    s.parse()
+        .map_err(|_| parse_error(format!("Invalid coordinate value '{}'.", s)))
+}
+
+fn parse_error(message: String) -> Error {
+    Error::new(ErrorKind::ParseError, Some(message))
+}
+
+/// Write one ring. Rings are always written closed.
+fn write_section<W: Write>(writer: &mut W, name: &str, ring: &[Coordinate]) -> Result<()> {
+    writeln!(writer, "{}", name)?;
+    for c in ring {
+        writeln!(writer, "\t{}\t{}", c.lon(), c.lat())?;
+    }
+
+    if let (Some(first), Some(last)) = (ring.first(), ring.last()) {
+        if first != last {
+            writeln!(writer, "\t{}\t{}", first.lon(), first.lat())?;
+        }
+    }
+
+    writeln!(writer, "END")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geo::{MultiPolygon, PolyFile, Polygon};
+    use crate::{Meta, Node, Osm, Relation, RelationMember, Way};
+
+    const POLY: &str = "area
+1
+\t0\t0
+\t10\t0
+\t10\t10
+\t0\t10
+\t0\t0
+END
+!2
+\t4\t4
+\t6\t4
+\t6\t6
+\t4\t4
+END
+3
+\t20\t20
+\t21\t20
+\t21\t21
+\t20\t20
+END
+END
+";
+
+    #[test]
+    fn read_poly() {
+        let file = PolyFile::read(POLY.as_bytes()).unwrap();
+        assert_eq!(file.name, "area");
+        assert_eq!(file.area.polygons.len(), 2);
+        assert_eq!(file.area.polygons[0].inner.len(), 1);
+        assert_eq!(file.area.polygons[1].inner.len(), 0);
+        assert_eq!(file.area.polygons[0].outer[1], (0.0, 10.0).into());
+
+        assert!(file.area.contains((2.0, 8.0).into()));
+        assert!(!file.area.contains((4.5, 5.5).into()));
+        assert!(file.area.contains((20.1, 20.5).into()));
+    }
+
+    #[test]
+    fn write_poly() {
+        let file = PolyFile::read(POLY.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        file.write(&mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), POLY);
+    }
+
+    #[test]
+    fn read_write_seven_decimals() {
+        let poly = "area\n1\n\t-179.9518987\t65.0344851\n\t-179.9518987\t65.1\n\
+                    \t-179.8\t65.1\n\t-179.9518987\t65.0344851\nEND\nEND\n";
+        let file = PolyFile::read(poly.as_bytes()).unwrap();
+        assert_eq!(file.area.polygons[0].outer[0].lon, -1_799_518_987);
+
+        let mut output = Vec::new();
+        file.write(&mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), poly);
+    }
+
+    #[test]
+    fn write_closes_rings() {
+        let area = MultiPolygon::new(vec![Polygon::new(
+            vec![(0.0, 0.0).into(), (0.0, 1.0).into(), (1.0, 1.0).into()],
+            vec![],
+        )]);
+
+        let mut output = Vec::new();
+        PolyFile::new("open", area).write(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "open\n1\n\t0\t0\n\t1\t0\n\t1\t1\n\t0\t0\nEND\nEND\n"
+        );
+    }
+
+    #[test]
+    fn read_invalid_poly() {
+        let data = vec![
+            ("", "Unexpected end of file."),
+            ("name\n1\n\t1 2\n", "Line 2: Section '1' has no END."),
+            ("name\n1\n\t1 2\nEND\n", "Line 4: Unexpected end of file."),
+            (
+                "name\n1\n\t1\nEND\nEND",
+                "Line 3: Expected coordinate pair, found '1'.",
+            ),
+            (
+                "name\n1\n\t1 x\nEND\nEND",
+                "Line 3: Invalid coordinate value 'x'.",
+            ),
+            (
+                "name\n\n!1\n\t1 1\nEND\nEND",
+                "Line 3: Hole without outer ring.",
+            ),
+        ];
+
+        for (poly, message) in data {
+            let error = PolyFile::read(poly.as_bytes()).unwrap_err();
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn from_relation() {
+        let mut osm = Osm::default();
+        for (id, c) in [(1, (0.0, 0.0)), (2, (0.0, 1.0)), (3, (1.0, 1.0))].iter() {
+            osm.add_node(Node {
+                id: *id,
+                coordinate: (*c).into(),
+                meta: Meta::default(),
+            });
+        }
+        osm.add_way(Way {
+            id: 4,
+            refs: vec![1, 2, 3, 1],
+            meta: Meta::default(),
+        });
+
+        let relation = Relation {
+            id: 5,
+            members: vec![RelationMember::Way(4, "outer".to_owned())],
+            meta: Meta {
+                tags: vec![("name", "Triangle").into()],
+                ..Meta::default()
+            },
+        };
+
+        let file = PolyFile::from_relation(&osm, &relation).unwrap();
+        assert_eq!(file.name, "Triangle");
+        assert_eq!(file.area.polygons[0].outer.len(), 4);
+    }
+}
@@ -0,0 +1,917 @@
+//! Polygons and containment tests.
+//!
+//! All calculations are done on the internal integer representation of `Coordinate`, so there are
+//! no floating point rounding issues for points close to an edge. Longitude is treated as x and
+//! latitude as y, i.e. the coordinates are treated as planar.
+use crate::geo::{Boundary, Coordinate};
+use crate::osm_io::error::{Error, ErrorKind, Result};
+use crate::{Osm, Relation, RelationMember, Way};
+use std::collections::{HashMap, HashSet};
+
+/// A polygon with one outer ring and zero or more inner rings (holes).
+///
+/// The rings may be closed (first coordinate equal to the last) or open, they are treated the same.
+/// Coordinates on the edge of the polygon, including the edges of the holes, are considered to be
+/// inside the polygon.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::geo::Polygon;
+/// let polygon = Polygon::new(
+///     vec![(0.0, 0.0).into(), (0.0, 10.0).into(), (10.0, 10.0).into(), (10.0, 0.0).into()],
+///     vec![vec![(4.0, 4.0).into(), (4.0, 6.0).into(), (6.0, 6.0).into(), (6.0, 4.0).into()]],
+/// );
+///
+/// assert!(polygon.contains((2.0, 2.0).into()));
+/// assert!(!polygon.contains((5.0, 5.0).into()));
+/// assert!(!polygon.contains((12.0, 2.0).into()));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Polygon {
+    pub outer: Vec<Coordinate>,
+    pub inner: Vec<Vec<Coordinate>>,
+}
+
+/// A collection of polygons, corresponds to a multipolygon relation in OSM.
+///
+/// See OSM docs for [`Multipolygon`].
+///
+/// [`Multipolygon`]: https://wiki.openstreetmap.org/wiki/Relation:multipolygon
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct MultiPolygon {
+    pub polygons: Vec<Polygon>,
+}
+
+/// The direction of a ring, with longitude as x and latitude as y.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Location of a point relative to a ring or polygon.
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Integer point used for the calculations. x is longitude and y is latitude.
+#[derive(Debug, Copy, Clone)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+impl Polygon {
+    pub fn new(outer: Vec<Coordinate>, inner: Vec<Vec<Coordinate>>) -> Self {
+        Polygon { outer, inner }
+    }
+
+    /// Create a polygon from a closed way. Returns `None` if the way is not closed or if any of
+    /// the nodes are missing in `osm`.
+    pub fn from_way(osm: &Osm, way: &Way) -> Option<Self> {
+        if way.refs.len() < 4 || way.refs.first() != way.refs.last() {
+            return None;
+        }
+
+        let outer = osm.way_coordinates(way)?;
+        Some(Polygon::new(outer, vec![]))
+    }
+
+    /// Check if a coordinate is inside the polygon.
+    pub fn contains(&self, c: Coordinate) -> bool {
+        self.location(c.into(), 1) != Location::Outside
+    }
+
+    /// Check if a line, i.e. a sequence of coordinates, is completely inside the polygon.
+    /// The line may touch the edges of the polygon but not cross them.
+    pub fn contains_line(&self, line: &[Coordinate]) -> bool {
+        if line.is_empty() || !line.iter().all(|c| self.contains(*c)) {
+            return false;
+        }
+
+        line.windows(2).all(|segment| {
+            let (a, b) = (segment[0], segment[1]);
+            let middle = Point {
+                x: a.lon as i64 + b.lon as i64,
+                y: a.lat as i64 + b.lat as i64,
+            };
+
+            self.location(middle, 2) != Location::Outside
+                && self.rings().all(|ring| !crosses_ring(a, b, ring))
+        })
+    }
+
+    /// Check if a way is completely inside the polygon. The way is treated as a line, see
+    /// `contains_line`. Returns false if any of the nodes are missing in `osm`.
+    pub fn contains_way(&self, osm: &Osm, way: &Way) -> bool {
+        match osm.way_coordinates(way) {
+            Some(line) => self.contains_line(&line),
+            None => false,
+        }
+    }
+
+    /// Check if a boundary is completely inside the polygon.
+    pub fn contains_boundary(&self, boundary: &Boundary) -> bool {
+        let (min, max) = (boundary.min, boundary.max);
+        let corners = [
+            min,
+            Coordinate {
+                lat: min.lat,
+                lon: max.lon,
+            },
+            max,
+            Coordinate {
+                lat: max.lat,
+                lon: min.lon,
+            },
+            min,
+        ];
+
+        // A hole that is completely inside the boundary does not cross any of its edges.
+        let hole_inside = self
+            .inner
+            .iter()
+            .flatten()
+            .any(|c| c.lat > min.lat && c.lat < max.lat && c.lon > min.lon && c.lon < max.lon);
+
+        !hole_inside && self.contains_line(&corners)
+    }
+
+    /// The boundary of the outer ring.
+    pub fn boundary(&self) -> Boundary {
+        let mut boundary = Boundary::inverted();
+        for c in &self.outer {
+            boundary.expand(*c);
+        }
+        boundary
+    }
+
+    fn rings(&self) -> impl Iterator<Item = &Vec<Coordinate>> {
+        std::iter::once(&self.outer).chain(self.inner.iter())
+    }
+
+    fn location(&self, p: Point, scale: i64) -> Location {
+        match ring_location(&self.outer, p, scale) {
+            Location::Inside => {}
+            location => return location,
+        }
+
+        for ring in &self.inner {
+            match ring_location(ring, p, scale) {
+                Location::Inside => return Location::Outside,
+                Location::Boundary => return Location::Boundary,
+                Location::Outside => {}
+            }
+        }
+
+        Location::Inside
+    }
+}
+
+impl MultiPolygon {
+    pub fn new(polygons: Vec<Polygon>) -> Self {
+        MultiPolygon { polygons }
+    }
+
+    /// Create a multipolygon from a multipolygon relation.
+    ///
+    /// The rings may consist of several ways, they are joined on their end nodes. Members with the
+    /// role `inner` are holes, other way members are treated as outer rings. Each hole is assigned
+    /// to the outer ring it is located in.
+    ///
+    /// Returns an `InvalidGeometry` error if any member or node is missing in `osm`, if a ring can
+    /// not be closed, if two rings are identical or if a hole is not inside any outer ring.
+    pub fn from_relation(osm: &Osm, relation: &Relation) -> Result<Self> {
+        let mut outer = Vec::new();
+        let mut inner = Vec::new();
+        for member in &relation.members {
+            if let RelationMember::Way(id, role) = member {
+                let way = osm
+                    .find_way(*id)
+                    .ok_or_else(|| invalid_geometry(format!("Member way {} is missing.", id)))?;
+                if role == "inner" {
+                    inner.push(way.refs.clone());
+                } else {
+                    outer.push(way.refs.clone());
+                }
+            }
+        }
+
+        let outer = resolve_rings(osm, relation, outer)?;
+        let inner = resolve_rings(osm, relation, inner)?;
+
+        let rings: Vec<&(i64, Vec<Coordinate>)> = outer.iter().chain(&inner).collect();
+        let coordinates: Vec<&[Coordinate]> = rings.iter().map(|(_, r)| &r[..]).collect();
+        if let Some((i, j)) = find_duplicate(&coordinates) {
+            let message = format!(
+                "Rings starting at node {} and node {} are identical.",
+                rings[i].0, rings[j].0
+            );
+            return Err(invalid_geometry(message));
+        }
+
+        let mut polygons: Vec<Polygon> = outer
+            .into_iter()
+            .map(|(_, ring)| Polygon::new(ring, vec![]))
+            .collect();
+
+        for (start, ring) in inner {
+            let polygon = polygons
+                .iter_mut()
+                .find(|p| ring.iter().all(|c| ring_contains(&p.outer, *c)));
+
+            match polygon {
+                Some(polygon) => polygon.inner.push(ring),
+                None => {
+                    let message = format!(
+                        "Inner ring starting at node {} is not inside any outer ring.",
+                        start
+                    );
+                    return Err(invalid_geometry(message));
+                }
+            }
+        }
+
+        Ok(MultiPolygon { polygons })
+    }
+
+    /// Create a multipolygon from rings with unknown roles, the roles are worked out from
+    /// containment. Rings inside an even number of other rings are outer rings, the others are
+    /// holes in the ring directly containing them. This handles e.g. islands in lakes.
+    ///
+    /// Returns an `InvalidGeometry` error if two rings are identical or if a hole has no outer ring
+    /// directly containing it, e.g. because the rings overlap.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::geo::MultiPolygon;
+    /// let square = |min: f64, max: f64| {
+    ///     vec![(min, min).into(), (min, max).into(), (max, max).into(), (max, min).into()]
+    /// };
+    ///
+    /// // An island in a lake in an island.
+    /// let rings = vec![square(2.0, 3.0), square(0.0, 5.0), square(1.0, 4.0)];
+    /// let polygon = MultiPolygon::from_rings(rings).unwrap();
+    /// assert_eq!(polygon.polygons.len(), 2);
+    /// assert_eq!(polygon.polygons[0].outer, square(2.0, 3.0));
+    /// assert_eq!(polygon.polygons[1].outer, square(0.0, 5.0));
+    /// assert_eq!(polygon.polygons[1].inner, vec![square(1.0, 4.0)]);
+    /// ```
+    pub fn from_rings(rings: Vec<Vec<Coordinate>>) -> Result<Self> {
+        let refs: Vec<&[Coordinate]> = rings.iter().map(|r| &r[..]).collect();
+        if let Some((i, j)) = find_duplicate(&refs) {
+            let message = format!("Ring {} and ring {} are identical.", i + 1, j + 1);
+            return Err(invalid_geometry(message));
+        }
+
+        let parents: Vec<Vec<usize>> = rings
+            .iter()
+            .enumerate()
+            .map(|(i, ring)| {
+                (0..rings.len())
+                    .filter(|j| *j != i && ring.iter().all(|c| ring_contains(&rings[*j], *c)))
+                    .collect()
+            })
+            .collect();
+
+        let mut polygons = Vec::new();
+        let mut polygon_index = vec![None; rings.len()];
+        for (i, ring) in rings.iter().enumerate() {
+            let depth = parents[i].len();
+            if depth % 2 == 1 {
+                continue;
+            }
+            polygon_index[i] = Some(polygons.len());
+            polygons.push(Polygon::new(ring.clone(), vec![]));
+        }
+
+        for (i, ring) in rings.into_iter().enumerate() {
+            let depth = parents[i].len();
+            if depth % 2 == 1 {
+                let parent = parents[i]
+                    .iter()
+                    .find(|j| parents[**j].len() == depth - 1)
+                    .and_then(|j| polygon_index[*j]);
+
+                match parent {
+                    Some(parent) => polygons[parent].inner.push(ring),
+                    None => {
+                        let message = format!("Ring {} is not inside any outer ring.", i + 1);
+                        return Err(invalid_geometry(message));
+                    }
+                }
+            }
+        }
+
+        Ok(MultiPolygon { polygons })
+    }
+
+    /// Check if a coordinate is inside any of the polygons.
+    pub fn contains(&self, c: Coordinate) -> bool {
+        self.polygons.iter().any(|p| p.contains(c))
+    }
+
+    /// Check if a line is completely inside one of the polygons.
+    pub fn contains_line(&self, line: &[Coordinate]) -> bool {
+        self.polygons.iter().any(|p| p.contains_line(line))
+    }
+
+    /// Check if a way is completely inside one of the polygons.
+    pub fn contains_way(&self, osm: &Osm, way: &Way) -> bool {
+        match osm.way_coordinates(way) {
+            Some(line) => self.contains_line(&line),
+            None => false,
+        }
+    }
+
+    /// Check if a boundary is completely inside one of the polygons.
+    pub fn contains_boundary(&self, boundary: &Boundary) -> bool {
+        self.polygons.iter().any(|p| p.contains_boundary(boundary))
+    }
+
+    /// The boundary of all outer rings.
+    pub fn boundary(&self) -> Boundary {
+        let mut boundary = Boundary::inverted();
+        for c in self.polygons.iter().flat_map(|p| &p.outer) {
+            boundary.expand(*c);
+        }
+        boundary
+    }
+}
+
+impl From<Polygon> for MultiPolygon {
+    fn from(polygon: Polygon) -> Self {
+        MultiPolygon::new(vec![polygon])
+    }
+}
+
+impl From<Coordinate> for Point {
+    fn from(c: Coordinate) -> Self {
+        Point {
+            x: c.lon as i64,
+            y: c.lat as i64,
+        }
+    }
+}
+
+/// Check if a coordinate is inside a ring. Coordinates on the edge are considered inside.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::geo::ring_contains;
+/// let ring = vec![(0.0, 0.0).into(), (0.0, 1.0).into(), (1.0, 1.0).into(), (0.0, 0.0).into()];
+///
+/// assert!(ring_contains(&ring, (0.5, 0.75).into()));
+/// assert!(ring_contains(&ring, (0.5, 0.5).into()));
+/// assert!(!ring_contains(&ring, (0.75, 0.5).into()));
+/// ```
+pub fn ring_contains(ring: &[Coordinate], c: Coordinate) -> bool {
+    ring_location(ring, c.into(), 1) != Location::Outside
+}
+
+/// The winding of a ring, or `None` if the ring has no area.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::geo::{ring_winding, Coordinate, Winding};
+/// let ring: Vec<Coordinate> = vec![(0.0, 0.0).into(), (0.0, 1.0).into(), (1.0, 1.0).into()];
+/// assert_eq!(ring_winding(&ring), Some(Winding::CounterClockwise));
+/// ```
+pub fn ring_winding(ring: &[Coordinate]) -> Option<Winding> {
+    let points: Vec<Point> = ring.iter().map(|c| Point::from(*c)).collect();
+    let area: i128 = (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a.x as i128 * b.y as i128 - b.x as i128 * a.y as i128
+        })
+        .sum();
+
+    match area {
+        0 => None,
+        a if a > 0 => Some(Winding::CounterClockwise),
+        _ => Some(Winding::Clockwise),
+    }
+}
+
+/// Normalize a ring: consecutive duplicate coordinates are removed, the ring is closed and
+/// reversed if needed to get the requested winding.
+///
+/// Returns `None` if the ring has less than 3 distinct coordinates.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::geo::{normalize_ring, Coordinate, Winding};
+/// let ring: Vec<Coordinate> = vec![(0.0, 0.0).into(), (1.0, 1.0).into(), (1.0, 1.0).into(), (0.0, 1.0).into()];
+/// let expected: Vec<Coordinate> =
+///     vec![(0.0, 0.0).into(), (0.0, 1.0).into(), (1.0, 1.0).into(), (0.0, 0.0).into()];
+///
+/// assert_eq!(normalize_ring(&ring, Some(Winding::CounterClockwise)), Some(expected));
+/// assert_eq!(normalize_ring(&ring[..2], None), None);
+/// ```
+pub fn normalize_ring(ring: &[Coordinate], winding: Option<Winding>) -> Option<Vec<Coordinate>> {
+    let mut result: Vec<Coordinate> = Vec::with_capacity(ring.len() + 1);
+    for c in ring {
+        if result.last() != Some(c) {
+            result.push(*c);
+        }
+    }
+
+    let distinct: HashSet<&Coordinate> = result.iter().collect();
+    if distinct.len() < 3 {
+        return None;
+    }
+
+    if result.first() != result.last() {
+        result.push(result[0]);
+    }
+
+    if let Some(winding) = winding {
+        if ring_winding(&result).is_some_and(|w| w != winding) {
+            result.reverse();
+        }
+    }
+    Some(result)
+}
+
+/// Find all polygons that contains each point.
+///
+/// The boundaries of the polygons are calculated once and used to filter out polygons before the
+/// more expensive containment test is done. The returned vector has one entry per point, with the
+/// indices of the polygons that contains it.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::geo::{assign_points, MultiPolygon, Polygon};
+/// let square = |lat: f64, lon: f64| -> MultiPolygon {
+///     Polygon::new(
+///         vec![(lat, lon).into(), (lat, lon + 1.0).into(), (lat + 1.0, lon + 1.0).into(),
+///              (lat + 1.0, lon).into()],
+///         vec![],
+///     ).into()
+/// };
+/// let districts = vec![square(0.0, 0.0), square(0.0, 2.0)];
+/// let points = vec![(0.5, 0.5).into(), (0.5, 2.5).into(), (0.5, 1.5).into()];
+///
+/// assert_eq!(assign_points(&points, &districts), vec![vec![0], vec![1], vec![]]);
+/// ```
+pub fn assign_points(points: &[Coordinate], polygons: &[MultiPolygon]) -> Vec<Vec<usize>> {
+    let boundaries: Vec<Boundary> = polygons.iter().map(|p| p.boundary()).collect();
+    points
+        .iter()
+        .map(|c| {
+            boundaries
+                .iter()
+                .enumerate()
+                .filter(|(i, b)| b.contains(*c) && polygons[*i].contains(*c))
+                .map(|(i, _)| i)
+                .collect()
+        })
+        .collect()
+}
+
+/// Join way segments into closed rings. The segments are represented as node ids.
+/// Returns `None` if a ring can not be closed.
+fn assemble_rings(mut segments: Vec<Vec<i64>>) -> Option<Vec<Vec<i64>>> {
+    segments.retain(|s| !s.is_empty());
+
+    let mut rings = Vec::new();
+    while let Some(mut ring) = segments.pop() {
+        while ring.len() < 2 || ring.first() != ring.last() {
+            let end = *ring.last().unwrap();
+            let pos = segments
+                .iter()
+                .position(|s| s.first() == Some(&end) || s.last() == Some(&end))?;
+
+            let mut next = segments.swap_remove(pos);
+            if next[0] != end {
+                next.reverse();
+            }
+            ring.extend_from_slice(&next[1..]);
+        }
+        rings.push(ring);
+    }
+    Some(rings)
+}
+
+/// Assemble way segments into rings and resolve their coordinates. Each ring is returned with the
+/// id of its first node, to be able to point out the ring in error messages.
+fn resolve_rings(
+    osm: &Osm,
+    relation: &Relation,
+    segments: Vec<Vec<i64>>,
+) -> Result<Vec<(i64, Vec<Coordinate>)>> {
+    let rings = assemble_rings(segments).ok_or_else(|| {
+        let message = format!(
+            "Relation {} has a ring that can not be closed.",
+            relation.id
+        );
+        invalid_geometry(message)
+    })?;
+
+    rings
+        .into_iter()
+        .map(|ring| {
+            let coordinates = osm.resolve_refs(&ring).ok_or_else(|| {
+                let message = format!("Ring starting at node {} has missing nodes.", ring[0]);
+                invalid_geometry(message)
+            })?;
+            Ok((ring[0], coordinates))
+        })
+        .collect()
+}
+
+/// Find the first pair of rings that consist of the same coordinates, regardless of start and
+/// direction.
+fn find_duplicate(rings: &[&[Coordinate]]) -> Option<(usize, usize)> {
+    let mut seen = HashMap::new();
+    for (i, ring) in rings.iter().enumerate() {
+        let mut key: Vec<(i32, i32)> = ring.iter().map(|c| (c.lat, c.lon)).collect();
+        key.sort_unstable();
+        key.dedup();
+        if let Some(first) = seen.insert(key, i) {
+            return Some((first, i));
+        }
+    }
+    None
+}
+
+fn invalid_geometry(message: String) -> Error {
+    Error::new(ErrorKind::InvalidGeometry, Some(message))
+}
+
+/// Crossing number test with exact boundary detection. The point and the ring are multiplied by
+/// `scale` to allow testing points between integer coordinates.
+fn ring_location(ring: &[Coordinate], p: Point, scale: i64) -> Location {
+    let mut inside = false;
+    for i in 0..ring.len() {
+        let a = scaled(ring[i], scale);
+        let b = scaled(ring[(i + 1) % ring.len()], scale);
+
+        if on_segment(a, b, p) {
+            return Location::Boundary;
+        }
+
+        if (a.y > p.y) != (b.y > p.y) {
+            let cross = orientation(a, b, p);
+            if (cross > 0) == (b.y > a.y) {
+                inside = !inside;
+            }
+        }
+    }
+
+    if inside {
+        Location::Inside
+    } else {
+        Location::Outside
+    }
+}
+
+/// Check if the segment a-b properly crosses any edge of the ring, i.e. intersects in a single
+/// point which is not an end point of any of the segments.
+fn crosses_ring(a: Coordinate, b: Coordinate, ring: &[Coordinate]) -> bool {
+    let (a, b) = (Point::from(a), Point::from(b));
+    (0..ring.len()).any(|i| {
+        let c = ring[i].into();
+        let d = ring[(i + 1) % ring.len()].into();
+
+        let o1 = orientation(a, b, c).signum();
+        let o2 = orientation(a, b, d).signum();
+        let o3 = orientation(c, d, a).signum();
+        let o4 = orientation(c, d, b).signum();
+        o1 * o2 < 0 && o3 * o4 < 0
+    })
+}
+
+fn scaled(c: Coordinate, scale: i64) -> Point {
+    Point {
+        x: c.lon as i64 * scale,
+        y: c.lat as i64 * scale,
+    }
+}
+
+/// Cross product of (b - a) and (p - a). Positive if p is to the left of a-b.
+/// Calculated with i128 since the product of two coordinate differences may overflow i64.
+fn orientation(a: Point, b: Point, p: Point) -> i128 {
+    (b.x - a.x) as i128 * (p.y - a.y) as i128 - (p.x - a.x) as i128 * (b.y - a.y) as i128
+}
+
+fn on_segment(a: Point, b: Point, p: Point) -> bool {
+    orientation(a, b, p) == 0
+        && p.x >= a.x.min(b.x)
+        && p.x <= a.x.max(b.x)
+        && p.y >= a.y.min(b.y)
+        && p.y <= a.y.max(b.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geo::{
+        normalize_ring, ring_contains, ring_winding, Boundary, Coordinate, MultiPolygon, Polygon,
+        Winding,
+    };
+    use crate::{Meta, Node, Osm, Relation, RelationMember, Way};
+
+    fn square(min: f64, max: f64) -> Vec<Coordinate> {
+        vec![
+            (min, min).into(),
+            (min, max).into(),
+            (max, max).into(),
+            (max, min).into(),
+            (min, min).into(),
+        ]
+    }
+
+    #[test]
+    fn ring_contains_edges() {
+        let ring = square(0.0, 10.0);
+        assert!(ring_contains(&ring, (0.0, 5.0).into()));
+        assert!(ring_contains(&ring, (10.0, 10.0).into()));
+        assert!(ring_contains(&ring, (5.0, 5.0).into()));
+        assert!(!ring_contains(&ring, (10.0, 10.0000001).into()));
+        assert!(!ring_contains(&ring, (-0.0000001, 5.0).into()));
+    }
+
+    #[test]
+    fn ring_contains_close_to_diagonal() {
+        let ring = vec![
+            Coordinate { lat: 0, lon: 0 },
+            Coordinate {
+                lat: 900_000_000,
+                lon: 1_799_999_999,
+            },
+            Coordinate {
+                lat: 0,
+                lon: 1_799_999_999,
+            },
+        ];
+
+        // Exactly on the diagonal, and one step above it.
+        assert!(ring_contains(&ring, Coordinate { lat: 1, lon: 2 }));
+        assert!(!ring_contains(&ring, Coordinate { lat: 2, lon: 2 }));
+        assert!(ring_contains(&ring, Coordinate { lat: 1, lon: 3 }));
+    }
+
+    #[test]
+    fn polygon_with_hole() {
+        let polygon = Polygon::new(square(0.0, 10.0), vec![square(4.0, 6.0)]);
+        assert!(polygon.contains((1.0, 1.0).into()));
+        assert!(polygon.contains((4.0, 5.0).into()));
+        assert!(!polygon.contains((5.0, 5.0).into()));
+        assert!(!polygon.contains((11.0, 5.0).into()));
+    }
+
+    #[test]
+    fn polygon_contains_line() {
+        let polygon = Polygon::new(square(0.0, 10.0), vec![square(4.0, 6.0)]);
+        assert!(polygon.contains_line(&[(1.0, 1.0).into(), (1.0, 9.0).into()]));
+        assert!(polygon.contains_line(&[(0.0, 0.0).into(), (0.0, 10.0).into()]));
+        assert!(polygon.contains_line(&[(4.0, 1.0).into(), (4.0, 9.0).into()]));
+        assert!(!polygon.contains_line(&[(5.0, 1.0).into(), (5.0, 9.0).into()]));
+        assert!(!polygon.contains_line(&[(1.0, 1.0).into(), (1.0, 11.0).into()]));
+        assert!(!polygon.contains_line(&[]));
+    }
+
+    #[test]
+    fn polygon_contains_concave_line() {
+        // U-shaped polygon, the line between the legs goes outside.
+        let polygon = Polygon::new(
+            vec![
+                (0.0, 0.0).into(),
+                (0.0, 3.0).into(),
+                (3.0, 3.0).into(),
+                (3.0, 2.0).into(),
+                (1.0, 2.0).into(),
+                (1.0, 1.0).into(),
+                (3.0, 1.0).into(),
+                (3.0, 0.0).into(),
+            ],
+            vec![],
+        );
+        assert!(polygon.contains_line(&[(0.5, 0.5).into(), (0.5, 2.5).into()]));
+        assert!(!polygon.contains_line(&[(2.0, 0.5).into(), (2.0, 2.5).into()]));
+        assert!(!polygon.contains_line(&[(3.0, 0.0).into(), (3.0, 3.0).into()]));
+    }
+
+    #[test]
+    fn polygon_contains_boundary() {
+        let polygon = Polygon::new(square(0.0, 10.0), vec![square(4.0, 6.0)]);
+        assert!(polygon.contains_boundary(&Boundary::new((1.0, 1.0), (3.0, 3.0))));
+        assert!(polygon.contains_boundary(&Boundary::new((0.0, 0.0), (4.0, 10.0))));
+        assert!(!polygon.contains_boundary(&Boundary::new((1.0, 1.0), (9.0, 9.0))));
+        assert!(!polygon.contains_boundary(&Boundary::new((5.0, 5.0), (9.0, 9.0))));
+        assert!(!polygon.contains_boundary(&Boundary::new((1.0, 1.0), (11.0, 3.0))));
+    }
+
+    #[test]
+    fn multipolygon_from_relation() {
+        let mut osm = Osm::default();
+        let coordinates = [
+            (0.0, 0.0),
+            (0.0, 10.0),
+            (10.0, 10.0),
+            (10.0, 0.0),
+            (4.0, 4.0),
+            (4.0, 6.0),
+            (6.0, 6.0),
+            (6.0, 4.0),
+        ];
+        for (i, c) in coordinates.iter().enumerate() {
+            osm.add_node(Node {
+                id: i as i64 + 1,
+                coordinate: (*c).into(),
+                meta: Meta::default(),
+            });
+        }
+
+        // The outer ring is split in two ways, one of them in reverse order.
+        let ways = vec![
+            (10, vec![1, 2, 3]),
+            (11, vec![1, 4, 3]),
+            (12, vec![5, 6, 7, 8, 5]),
+        ];
+        for (id, refs) in ways {
+            osm.add_way(Way {
+                id,
+                refs,
+                meta: Meta::default(),
+            });
+        }
+
+        let relation = Relation {
+            id: 20,
+            members: vec![
+                RelationMember::Way(10, "outer".to_owned()),
+                RelationMember::Way(12, "inner".to_owned()),
+                RelationMember::Way(11, "outer".to_owned()),
+            ],
+            meta: Meta::default(),
+        };
+
+        let multipolygon = MultiPolygon::from_relation(&osm, &relation).unwrap();
+        assert_eq!(multipolygon.polygons.len(), 1);
+        assert_eq!(multipolygon.polygons[0].inner.len(), 1);
+        assert!(multipolygon.contains((2.0, 2.0).into()));
+        assert!(!multipolygon.contains((5.0, 5.0).into()));
+
+        let way = osm.find_way(12).unwrap();
+        assert!(multipolygon.contains_way(&osm, way));
+
+        let mut open = relation.clone();
+        open.members.pop();
+        let error = MultiPolygon::from_relation(&osm, &open).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Relation 20 has a ring that can not be closed."
+        );
+    }
+
+    #[test]
+    fn multipolygon_from_relation_invalid_rings() {
+        let mut osm = Osm::default();
+        let coordinates = [
+            (0.0, 0.0),
+            (0.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 0.0),
+            (5.0, 5.0),
+            (5.0, 6.0),
+            (6.0, 6.0),
+        ];
+        for (i, c) in coordinates.iter().enumerate() {
+            osm.add_node(Node {
+                id: i as i64 + 1,
+                coordinate: (*c).into(),
+                meta: Meta::default(),
+            });
+        }
+
+        // Way 11 is way 10 in reverse, starting at another node.
+        let ways = vec![
+            (10, vec![1, 2, 3, 4, 1]),
+            (11, vec![2, 1, 4, 3, 2]),
+            (12, vec![5, 6, 7, 5]),
+        ];
+        for (id, refs) in ways {
+            osm.add_way(Way {
+                id,
+                refs,
+                meta: Meta::default(),
+            });
+        }
+
+        let relation = |members: Vec<(i64, &str)>| Relation {
+            id: 20,
+            members: members
+                .into_iter()
+                .map(|(id, role)| RelationMember::Way(id, role.to_owned()))
+                .collect(),
+            meta: Meta::default(),
+        };
+
+        let data = vec![
+            (
+                relation(vec![(10, "outer"), (11, "outer")]),
+                "Rings starting at node 2 and node 1 are identical.",
+            ),
+            (
+                relation(vec![(10, "outer"), (11, "inner")]),
+                "Rings starting at node 1 and node 2 are identical.",
+            ),
+            (
+                relation(vec![(10, "outer"), (12, "inner")]),
+                "Inner ring starting at node 5 is not inside any outer ring.",
+            ),
+            (
+                relation(vec![(10, "outer"), (13, "inner")]),
+                "Member way 13 is missing.",
+            ),
+        ];
+
+        for (relation, message) in data {
+            let error = MultiPolygon::from_relation(&osm, &relation).unwrap_err();
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn multipolygon_from_rings() {
+        let square = |lat: f64, lon: f64, size: f64| -> Vec<Coordinate> {
+            vec![
+                (lat, lon).into(),
+                (lat, lon + size).into(),
+                (lat + size, lon + size).into(),
+                (lat + size, lon).into(),
+            ]
+        };
+
+        let polygon = MultiPolygon::from_rings(vec![
+            square(1.0, 1.0, 1.0),
+            square(0.0, 0.0, 4.0),
+            square(10.0, 10.0, 1.0),
+            square(2.5, 2.5, 1.0),
+        ])
+        .unwrap();
+
+        assert_eq!(polygon.polygons.len(), 2);
+        assert_eq!(polygon.polygons[0].outer, square(0.0, 0.0, 4.0));
+        assert_eq!(
+            polygon.polygons[0].inner,
+            vec![square(1.0, 1.0, 1.0), square(2.5, 2.5, 1.0)]
+        );
+        assert_eq!(polygon.polygons[1].outer, square(10.0, 10.0, 1.0));
+        assert!(polygon.polygons[1].inner.is_empty());
+
+        let mut reversed = square(0.0, 0.0, 4.0);
+        reversed.reverse();
+        let error = MultiPolygon::from_rings(vec![square(0.0, 0.0, 4.0), reversed]).unwrap_err();
+        assert_eq!(error.to_string(), "Ring 1 and ring 2 are identical.");
+
+        // The second ring crosses the notch of the first ring, the third ring is in the notch.
+        // The third ring is only inside the second ring, which is not an outer ring.
+        let notched = vec![
+            (0.0, 0.0).into(),
+            (0.0, 10.0).into(),
+            (4.0, 10.0).into(),
+            (4.0, 5.0).into(),
+            (6.0, 5.0).into(),
+            (6.0, 10.0).into(),
+            (10.0, 10.0).into(),
+            (10.0, 0.0).into(),
+        ];
+        let overlapping = vec![notched, square(1.0, 1.0, 8.0), square(4.5, 6.5, 1.0)];
+        let error = MultiPolygon::from_rings(overlapping).unwrap_err();
+        assert_eq!(error.to_string(), "Ring 3 is not inside any outer ring.");
+    }
+
+    #[test]
+    fn normalize_ring_winding() {
+        let ring: Vec<Coordinate> = vec![
+            (0.0, 0.0).into(),
+            (1.0, 0.0).into(),
+            (1.0, 1.0).into(),
+            (0.0, 0.0).into(),
+        ];
+        assert_eq!(ring_winding(&ring), Some(Winding::Clockwise));
+
+        let normalized = normalize_ring(&ring, Some(Winding::Clockwise)).unwrap();
+        assert_eq!(normalized, ring);
+
+        let normalized = normalize_ring(&ring, Some(Winding::CounterClockwise)).unwrap();
+        assert_eq!(ring_winding(&normalized), Some(Winding::CounterClockwise));
+        assert_eq!(normalized.len(), 4);
+
+        let line: Vec<Coordinate> = vec![(0.0, 0.0).into(), (1.0, 1.0).into(), (2.0, 2.0).into()];
+        assert_eq!(ring_winding(&line), None);
+        assert_eq!(
+            normalize_ring(&line, Some(Winding::Clockwise))
+                .unwrap()
+                .len(),
+            4
+        );
+        assert_eq!(normalize_ring(&line[..2], None), None);
+        assert_eq!(normalize_ring(&[], None), None);
+    }
+}
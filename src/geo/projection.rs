@@ -0,0 +1,381 @@
+//! Map projections, used to convert projected x/y coordinates to and from WGS84.
+//!
+//! See: https://en.wikipedia.org/wiki/Map_projection
+use crate::geo::Coordinate;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+/// WGS84 semi-major axis in metres.
+const WGS84_A: f64 = 6_378_137.0;
+
+/// WGS84 flattening.
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+/// Latitude limit of Web Mercator, makes the projected world square.
+pub(crate) const WEB_MERCATOR_MAX_LAT: f64 = 85.051_128_779_806_59;
+
+const UTM_SCALE: f64 = 0.9996;
+const UTM_FALSE_EASTING: f64 = 500_000.0;
+const UTM_FALSE_NORTHING: f64 = 10_000_000.0;
+
+/// A projection between WGS84 coordinates and projected x/y coordinates.
+///
+/// Implement this trait to let the [`OsmBuilder`] import data in other coordinate systems.
+/// Projections must be `Send` and `Sync` so builders can be used on other threads.
+///
+/// [`OsmBuilder`]: ../struct.OsmBuilder.html
+pub trait Projection: Send + Sync {
+    /// Forward transform, from coordinate to projected x and y.
+    fn project(&self, coordinate: Coordinate) -> (f64, f64);
+
+    /// Inverse transform, from projected x and y to coordinate.
+    fn unproject(&self, x: f64, y: f64) -> Coordinate;
+}
+
+/// A position as either a coordinate or projected x/y values. This is the input type of the
+/// [`OsmBuilder`], which converts projected positions with its projection.
+///
+/// Everything that can be converted into a `Coordinate` can be converted into a position.
+///
+/// [`OsmBuilder`]: ../struct.OsmBuilder.html
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Position {
+    Coordinate(Coordinate),
+    Projected(f64, f64),
+}
+
+/// The identity projection, x is longitude and y is latitude in degrees (EPSG:4326).
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Wgs84;
+
+/// Web Mercator projection (EPSG:3857), x and y are in metres. Used by most web maps.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::geo::{Projection, WebMercator};
+/// let (x, y) = WebMercator.project((0.0, 180.0).into());
+/// assert_eq!(x.round(), 20037508.0);
+/// assert_eq!(y.round(), 0.0);
+///
+/// assert_eq!(WebMercator.unproject(x, y), (0.0, 180.0).into());
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct WebMercator;
+
+/// Universal Transverse Mercator projection, x (easting) and y (northing) are in metres.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::geo::{Projection, Utm};
+/// let eiffel_tower = (48.858222, 2.2945).into();
+/// let utm = Utm::from_coordinate(eiffel_tower);
+/// assert_eq!(Some(utm), Utm::new(31, true));
+/// assert_eq!(Utm::new(61, true), None);
+///
+/// let (x, y) = utm.project(eiffel_tower);
+/// assert_eq!((x.round(), y.round()), (448252.0, 5411935.0));
+/// ```
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Utm {
+    pub zone: u8,
+    pub north: bool,
+}
+
+impl Projection for Wgs84 {
+    fn project(&self, coordinate: Coordinate) -> (f64, f64) {
+        (coordinate.lon(), coordinate.lat())
+    }
+
+    fn unproject(&self, x: f64, y: f64) -> Coordinate {
+        Coordinate::rounded(y, x)
+    }
+}
+
+impl Projection for WebMercator {
+    fn project(&self, coordinate: Coordinate) -> (f64, f64) {
+        let lat = coordinate
+            .lat()
+            .clamp(-WEB_MERCATOR_MAX_LAT, WEB_MERCATOR_MAX_LAT)
+            .to_radians();
+
+        let x = WGS84_A * coordinate.lon().to_radians();
+        let y = WGS84_A * (FRAC_PI_4 + lat / 2.0).tan().ln();
+        (x, y)
+    }
+
+    fn unproject(&self, x: f64, y: f64) -> Coordinate {
+        let lon = (x / WGS84_A).to_degrees();
+        let lat = (2.0 * (y / WGS84_A).exp().atan() - FRAC_PI_2).to_degrees();
+        Coordinate::rounded(lat, lon)
+    }
+}
+
+impl Utm {
+    /// Returns `None` if zone is outside of the range 1-60.
+    pub fn new(zone: u8, north: bool) -> Option<Self> {
+        if !(1..=60).contains(&zone) {
+            return None;
+        }
+        Some(Utm { zone, north })
+    }
+
+    /// Find the UTM zone of a coordinate, including the exceptions around Norway and Svalbard.
+    pub fn from_coordinate(coordinate: Coordinate) -> Self {
+        let (lat, lon) = (coordinate.lat(), coordinate.lon());
+        let mut zone = (((lon + 180.0) / 6.0).floor() as i32 + 1).clamp(1, 60) as u8;
+
+        if (56.0..64.0).contains(&lat) && (3.0..12.0).contains(&lon) {
+            zone = 32;
+        } else if (72.0..84.0).contains(&lat) && (0.0..42.0).contains(&lon) {
+            zone = match lon {
+                lon if lon < 9.0 => 31,
+                lon if lon < 21.0 => 33,
+                lon if lon < 33.0 => 35,
+                _ => 37,
+            };
+        }
+
+        Utm {
+            zone,
+            north: lat >= 0.0,
+        }
+    }
+
+    /// Central meridian of the zone in radians.
+    fn central_meridian(&self) -> f64 {
+        (self.zone as f64 * 6.0 - 183.0).to_radians()
+    }
+
+    fn false_northing(&self) -> f64 {
+        if self.north {
+            0.0
+        } else {
+            UTM_FALSE_NORTHING
+        }
+    }
+}
+
+/// Transverse Mercator formulas from Snyder, "Map Projections - A Working Manual", p. 61.
+impl Projection for Utm {
+    fn project(&self, coordinate: Coordinate) -> (f64, f64) {
+        let (e2, ep2) = eccentricity();
+        let lat = coordinate.lat().to_radians();
+        let lon = coordinate.lon().to_radians();
+
+        let n = WGS84_A / (1.0 - e2 * lat.sin().powi(2)).sqrt();
+        let t = lat.tan().powi(2);
+        let c = ep2 * lat.cos().powi(2);
+        let a = lat.cos() * (lon - self.central_meridian());
+        let m = meridian_arc(lat);
+
+        let x = UTM_SCALE
+            * n
+            * (a + (1.0 - t + c) * a.powi(3) / 6.0
+                + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0);
+
+        let y = UTM_SCALE
+            * (m + n
+                * lat.tan()
+                * (a * a / 2.0
+                    + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+                    + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0));
+
+        (x + UTM_FALSE_EASTING, y + self.false_northing())
+    }
+
+    fn unproject(&self, x: f64, y: f64) -> Coordinate {
+        let (e2, ep2) = eccentricity();
+        let e4 = e2 * e2;
+        let e6 = e4 * e2;
+
+        let m = (y - self.false_northing()) / UTM_SCALE;
+        let mu = m / (WGS84_A * (1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0));
+
+        let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+        let lat1 = mu
+            + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+            + (21.0 * e1 * e1 / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+            + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+            + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+
+        let n1 = WGS84_A / (1.0 - e2 * lat1.sin().powi(2)).sqrt();
+        let t1 = lat1.tan().powi(2);
+        let c1 = ep2 * lat1.cos().powi(2);
+        let r1 = WGS84_A * (1.0 - e2) / (1.0 - e2 * lat1.sin().powi(2)).powf(1.5);
+        let d = (x - UTM_FALSE_EASTING) / (n1 * UTM_SCALE);
+
+        let lat = lat1
+            - (n1 * lat1.tan() / r1)
+                * (d * d / 2.0
+                    - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1 * c1 - 9.0 * ep2) * d.powi(4) / 24.0
+                    + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1 * t1
+                        - 252.0 * ep2
+                        - 3.0 * c1 * c1)
+                        * d.powi(6)
+                        / 720.0);
+
+        let lon = self.central_meridian()
+            + (d - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+                + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1 * c1 + 8.0 * ep2 + 24.0 * t1 * t1)
+                    * d.powi(5)
+                    / 120.0)
+                / lat1.cos();
+
+        Coordinate::rounded(lat.to_degrees(), lon.to_degrees())
+    }
+}
+
+impl Position {
+    /// Convert to a coordinate, projected positions are converted with `projection`.
+    pub fn to_coordinate(self, projection: &dyn Projection) -> Coordinate {
+        match self {
+            Position::Coordinate(coordinate) => coordinate,
+            Position::Projected(x, y) => projection.unproject(x, y),
+        }
+    }
+}
+
+impl<C: Into<Coordinate>> From<C> for Position {
+    fn from(coordinate: C) -> Self {
+        Position::Coordinate(coordinate.into())
+    }
+}
+
+/// Squared eccentricity and squared second eccentricity of WGS84.
+fn eccentricity() -> (f64, f64) {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    (e2, e2 / (1.0 - e2))
+}
+
+/// Distance along the meridian from equator to latitude `lat` (radians).
+fn meridian_arc(lat: f64) -> f64 {
+    let (e2, _) = eccentricity();
+    let e4 = e2 * e2;
+    let e6 = e4 * e2;
+
+    WGS84_A
+        * ((1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0) * lat
+            - (3.0 * e2 / 8.0 + 3.0 * e4 / 32.0 + 45.0 * e6 / 1024.0) * (2.0 * lat).sin()
+            + (15.0 * e4 / 256.0 + 45.0 * e6 / 1024.0) * (4.0 * lat).sin()
+            - (35.0 * e6 / 3072.0) * (6.0 * lat).sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geo::{Coordinate, Position, Projection, Utm, WebMercator, Wgs84};
+
+    fn assert_close(actual: (f64, f64), expected: (f64, f64), tolerance: f64) {
+        assert!(
+            (actual.0 - expected.0).abs() < tolerance && (actual.1 - expected.1).abs() < tolerance,
+            "{:?} is not close to {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn web_mercator() {
+        let coordinate = (60.0, 18.0).into();
+        let projected = WebMercator.project(coordinate);
+        assert_close(projected, (2_003_750.834, 8_399_737.890), 0.01);
+        assert_eq!(WebMercator.unproject(projected.0, projected.1), coordinate);
+    }
+
+    #[test]
+    fn web_mercator_clamps_poles() {
+        let (_, y) = WebMercator.project((90.0, 0.0).into());
+        assert_close((0.0, y), (0.0, 20_037_508.343), 0.01);
+    }
+
+    #[test]
+    fn utm_northern_hemisphere() {
+        // Eiffel Tower.
+        let utm = Utm::new(31, true).unwrap();
+        let (x, y) = utm.project((48.858222, 2.2945).into());
+        assert_close((x, y), (448_251.818, 5_411_935.123), 0.01);
+    }
+
+    #[test]
+    fn utm_zones() {
+        assert_eq!(
+            Some(Utm::from_coordinate((60.67, 17.14).into())),
+            Utm::new(33, true)
+        );
+        assert_eq!(
+            Some(Utm::from_coordinate((-33.9, 151.2).into())),
+            Utm::new(56, false)
+        );
+        assert_eq!(
+            Some(Utm::from_coordinate((0.0, -180.0).into())),
+            Utm::new(1, true)
+        );
+        assert_eq!(
+            Some(Utm::from_coordinate((0.0, 180.0).into())),
+            Utm::new(60, true)
+        );
+        assert_eq!(
+            Some(Utm::from_coordinate((60.0, 5.0).into())),
+            Utm::new(32, true)
+        );
+        assert_eq!(
+            Some(Utm::from_coordinate((78.0, 15.0).into())),
+            Utm::new(33, true)
+        );
+    }
+
+    #[test]
+    fn utm_invalid_zone() {
+        assert_eq!(Utm::new(0, true), None);
+        assert_eq!(Utm::new(61, false), None);
+    }
+
+    #[test]
+    fn utm_round_trip() {
+        let coordinates: Vec<Coordinate> = vec![
+            (60.6750500, 17.1362500).into(),
+            (-33.8567844, 151.2152967).into(),
+            (0.0, 3.0).into(),
+            (71.1708, 25.7846).into(),
+        ];
+
+        for coordinate in coordinates {
+            let utm = Utm::from_coordinate(coordinate);
+            let (x, y) = utm.project(coordinate);
+            assert_eq!(utm.unproject(x, y), coordinate);
+        }
+    }
+
+    #[test]
+    fn utm_southern_hemisphere() {
+        // Sydney Opera House.
+        let utm = Utm::new(56, false).unwrap();
+        let (x, y) = utm.project((-33.8567844, 151.2152967).into());
+        assert_close((x, y), (334_900.234, 6_252_290.478), 0.01);
+    }
+
+    #[test]
+    fn wgs84_rounds() {
+        let coordinate = Wgs84.unproject(10.000001, -10.000001);
+        assert_eq!(
+            coordinate,
+            Coordinate {
+                lat: -100_000_010,
+                lon: 100_000_010
+            }
+        );
+    }
+
+    #[test]
+    fn builder_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<crate::OsmBuilder>();
+    }
+
+    #[test]
+    fn position_to_coordinate() {
+        let position: Position = (1.0, 2.0).into();
+        assert_eq!(position.to_coordinate(&Wgs84), (1.0, 2.0).into());
+
+        let position = Position::Projected(2.0, 1.0);
+        assert_eq!(position.to_coordinate(&Wgs84), (1.0, 2.0).into());
+    }
+}
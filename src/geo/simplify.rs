@@ -0,0 +1,492 @@
+//! Line simplification, i.e. reducing the number of points of a line while keeping its shape.
+//!
+//! See: https://en.wikipedia.org/wiki/Ramer%E2%80%93Douglas%E2%80%93Peucker_algorithm
+//! and https://en.wikipedia.org/wiki/Visvalingam%E2%80%93Whyatt_algorithm
+use crate::geo::{Coordinate, EARTH_RADIUS};
+use crate::{Osm, RelationMember};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// Algorithm used when simplifying lines.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum SimplifyAlgorithm {
+    /// Removes points closer than the tolerance to the simplified line.
+    DouglasPeucker,
+
+    /// Removes points forming triangles with an area smaller than the tolerance squared with
+    /// their neighbours. Tends to give smoother results than Douglas-Peucker.
+    VisvalingamWhyatt,
+}
+
+/// Simplify a line with a tolerance in metres. The first and last points are always kept, and
+/// closed rings are kept closed with at least 4 points.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::geo::{simplify, Coordinate, SimplifyAlgorithm};
+/// let line: Vec<Coordinate> = vec![
+///     (0.0, 0.0).into(),
+///     (0.00001, 0.001).into(),
+///     (0.0, 0.002).into(),
+///     (0.001, 0.003).into(),
+/// ];
+///
+/// let simplified = simplify(&line, 10.0, SimplifyAlgorithm::DouglasPeucker);
+/// assert_eq!(simplified, vec![line[0], line[2], line[3]]);
+/// ```
+pub fn simplify(
+    coordinates: &[Coordinate],
+    tolerance: f64,
+    algorithm: SimplifyAlgorithm,
+) -> Vec<Coordinate> {
+    let keep = vec![false; coordinates.len()];
+    simplify_indices(coordinates, &keep, tolerance, algorithm)
+        .into_iter()
+        .map(|i| coordinates[i])
+        .collect()
+}
+
+impl Osm {
+    /// Simplify all ways with a tolerance in metres. See [`simplify`].
+    ///
+    /// Nodes shared with other ways, tagged nodes and nodes that are members of relations are
+    /// always kept, so the topology of the map is preserved. Nodes which are no longer used are
+    /// removed from the map.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::OsmBuilder;
+    /// # use vadeen_osm::geo::SimplifyAlgorithm;
+    /// let mut builder = OsmBuilder::default();
+    /// builder.add_polyline(
+    ///     vec![(0.0, 0.0), (0.00001, 0.001), (0.0, 0.002)],
+    ///     vec![("highway", "track")],
+    /// );
+    ///
+    /// let mut osm = builder.build();
+    /// osm.simplify_ways(20.0, SimplifyAlgorithm::VisvalingamWhyatt);
+    /// assert_eq!(osm.ways[0].refs, vec![1, 3]);
+    /// assert_eq!(osm.nodes.len(), 2);
+    /// ```
+    ///
+    /// [`simplify`]: geo/fn.simplify.html
+    pub fn simplify_ways(&mut self, tolerance: f64, algorithm: SimplifyAlgorithm) {
+        let fixed = self.fixed_nodes();
+        let coordinates: HashMap<i64, Coordinate> =
+            self.nodes.iter().map(|n| (n.id, n.coordinate)).collect();
+
+        for way in &mut self.ways {
+            // Ways with missing nodes can not be simplified.
+            let line: Option<Vec<Coordinate>> = way
+                .refs
+                .iter()
+                .map(|r| coordinates.get(r).cloned())
+                .collect();
+            let line = match line {
+                Some(line) => line,
+                None => continue,
+            };
+
+            let keep: Vec<bool> = way.refs.iter().map(|r| fixed.contains(r)).collect();
+            way.refs = simplify_indices(&line, &keep, tolerance, algorithm)
+                .into_iter()
+                .map(|i| way.refs[i])
+                .collect();
+        }
+
+        let used: HashSet<i64> = self.ways.iter().flat_map(|w| w.refs.clone()).collect();
+        let before = self.nodes.len();
+        self.nodes
+            .retain(|n| used.contains(&n.id) || fixed.contains(&n.id));
+
+        if self.nodes.len() != before {
+            self.rebuild_indices();
+        }
+    }
+
+    /// Nodes which must not be removed. Nodes which are not used by any way are included as well.
+    fn fixed_nodes(&self) -> HashSet<i64> {
+        let mut usage: HashMap<i64, u32> = HashMap::new();
+        for way in &self.ways {
+            for id in way.refs.iter().collect::<HashSet<_>>() {
+                *usage.entry(*id).or_default() += 1;
+            }
+        }
+
+        let mut fixed: HashSet<i64> = self
+            .nodes
+            .iter()
+            .filter(|n| !n.meta.tags.is_empty() || usage.get(&n.id) != Some(&1))
+            .map(|n| n.id)
+            .collect();
+
+        for rel in &self.relations {
+            for member in &rel.members {
+                if let RelationMember::Node(id, _) = member {
+                    fixed.insert(*id);
+                }
+            }
+        }
+        fixed
+    }
+}
+
+/// Simplify a line, returns the indices of the points to keep. Points marked in `keep` are always
+/// kept.
+fn simplify_indices(
+    coordinates: &[Coordinate],
+    keep: &[bool],
+    tolerance: f64,
+    algorithm: SimplifyAlgorithm,
+) -> Vec<usize> {
+    if coordinates.len() < 3 {
+        return (0..coordinates.len()).collect();
+    }
+
+    let points = to_metres(coordinates);
+    let mut keep = keep.to_vec();
+    let last = points.len() - 1;
+    keep[0] = true;
+    keep[last] = true;
+
+    // Rings would collapse, so the point farthest from the start and the point farthest from
+    // the line between them are kept as well. This keeps at least a triangle.
+    let closed = coordinates[0] == coordinates[last];
+    if closed && last >= 3 {
+        let farthest = max_distance(1..last, |i| distance(points[0], points[i]));
+        keep[farthest] = true;
+
+        let others = (1..last).filter(|i| *i != farthest);
+        let second = max_distance(others, |i| {
+            segment_distance(points[i], points[0], points[farthest])
+        });
+        keep[second] = true;
+    }
+
+    match algorithm {
+        SimplifyAlgorithm::DouglasPeucker => {
+            let fixed: Vec<usize> = (0..points.len()).filter(|i| keep[*i]).collect();
+            for pair in fixed.windows(2) {
+                douglas_peucker(&points, pair[0], pair[1], tolerance, &mut keep);
+            }
+        }
+        SimplifyAlgorithm::VisvalingamWhyatt => {
+            visvalingam_whyatt(&points, tolerance * tolerance, closed, &mut keep);
+        }
+    }
+
+    (0..points.len()).filter(|i| keep[*i]).collect()
+}
+
+/// The index with the largest distance.
+fn max_distance<I: Iterator<Item = usize>, F: Fn(usize) -> f64>(indices: I, f: F) -> usize {
+    indices
+        .map(|i| (i, f(i)))
+        .fold((0, -1.0), |a, b| if b.1 > a.1 { b } else { a })
+        .0
+}
+
+/// Mark the points between `start` and `end` needed to stay within the tolerance.
+fn douglas_peucker(
+    points: &[(f64, f64)],
+    start: usize,
+    end: usize,
+    tolerance: f64,
+    keep: &mut [bool],
+) {
+    if end <= start + 1 {
+        return;
+    }
+
+    let (index, max_distance) = (start + 1..end)
+        .map(|i| (i, segment_distance(points[i], points[start], points[end])))
+        .fold((start, 0.0), |a, b| if b.1 > a.1 { b } else { a });
+
+    if max_distance > tolerance {
+        keep[index] = true;
+        douglas_peucker(points, start, index, tolerance, keep);
+        douglas_peucker(points, index, end, tolerance, keep);
+    }
+}
+
+/// Repeatedly remove the point with the smallest triangle area until all areas are above the
+/// threshold. Points marked in `keep` are never removed.
+///
+/// Areas are kept in a min heap. When a point is removed the areas of its neighbours change, the
+/// new areas are pushed and the outdated entries are skipped when they are popped.
+fn visvalingam_whyatt(points: &[(f64, f64)], threshold: f64, closed: bool, keep: &mut [bool]) {
+    let last = points.len() - 1;
+    let min_len = if closed { 4 } else { 2 };
+
+    // Linked list of the remaining points.
+    let mut prev: Vec<usize> = (0..points.len()).map(|i| i.saturating_sub(1)).collect();
+    let mut next: Vec<usize> = (0..points.len()).map(|i| i + 1).collect();
+    let mut removed = vec![false; points.len()];
+    let mut version = vec![0usize; points.len()];
+    let mut remaining = points.len();
+
+    let area = |prev: &[usize], next: &[usize], i: usize| {
+        triangle_area(points[prev[i]], points[i], points[next[i]])
+    };
+
+    let mut heap = BinaryHeap::new();
+    for i in (1..last).filter(|i| !keep[*i]) {
+        heap.push(Reverse(Triangle {
+            area: area(&prev, &next, i),
+            index: i,
+            version: 0,
+        }));
+    }
+
+    while remaining > min_len {
+        let triangle = match heap.pop() {
+            Some(Reverse(triangle)) => triangle,
+            None => break,
+        };
+        let i = triangle.index;
+        if removed[i] || triangle.version != version[i] {
+            continue;
+        }
+        if triangle.area >= threshold {
+            break;
+        }
+
+        removed[i] = true;
+        remaining -= 1;
+        let (p, n) = (prev[i], next[i]);
+        next[p] = n;
+        prev[n] = p;
+
+        for neighbour in [p, n] {
+            if neighbour != 0 && neighbour != last && !keep[neighbour] {
+                version[neighbour] += 1;
+                heap.push(Reverse(Triangle {
+                    area: area(&prev, &next, neighbour),
+                    index: neighbour,
+                    version: version[neighbour],
+                }));
+            }
+        }
+    }
+
+    for i in (0..points.len()).filter(|i| !removed[*i]) {
+        keep[i] = true;
+    }
+}
+
+/// A point and the area of the triangle it forms with its neighbours. Ordered by area, then by
+/// index so the first of equally small triangles is removed first.
+#[derive(Debug)]
+struct Triangle {
+    area: f64,
+    index: usize,
+    version: usize,
+}
+
+impl PartialEq for Triangle {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Triangle {}
+
+impl PartialOrd for Triangle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Triangle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.area
+            .total_cmp(&other.area)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+/// Project coordinates to x/y in metres, relative to the first coordinate. The distortion is
+/// negligible for the short distances between points of a way.
+fn to_metres(coordinates: &[Coordinate]) -> Vec<(f64, f64)> {
+    let origin = coordinates[0];
+    let scale = origin.lat().to_radians().cos();
+    coordinates
+        .iter()
+        .map(|c| {
+            let x = (i64::from(c.lon) - i64::from(origin.lon)) as f64 / 10_000_000.0;
+            let y = (i64::from(c.lat) - i64::from(origin.lat)) as f64 / 10_000_000.0;
+            (
+                x.to_radians() * scale * EARTH_RADIUS,
+                y.to_radians() * EARTH_RADIUS,
+            )
+        })
+        .collect()
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// Distance from a point to the segment between `a` and `b`.
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    if length == 0.0 {
+        return distance(p, a);
+    }
+
+    let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0);
+    distance(p, (a.0 + t * dx, a.1 + t * dy))
+}
+
+fn triangle_area(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    ((b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1)).abs() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::triangle_area;
+    use crate::geo::{simplify, Coordinate, SimplifyAlgorithm};
+    use crate::{Meta, Node, Osm, Relation, RelationMember, Way};
+
+    /// A zigzag line along the equator, the zigzag is about 11 m high.
+    fn zigzag() -> Vec<Coordinate> {
+        (0..10)
+            .map(|i| (if i % 2 == 0 { 0.0 } else { 0.0001 }, i as f64 * 0.001).into())
+            .collect()
+    }
+
+    #[test]
+    fn douglas_peucker() {
+        let line = zigzag();
+        let simplified = simplify(&line, 20.0, SimplifyAlgorithm::DouglasPeucker);
+        assert_eq!(simplified, vec![line[0], line[9]]);
+
+        let simplified = simplify(&line, 5.0, SimplifyAlgorithm::DouglasPeucker);
+        assert_eq!(simplified, line);
+    }
+
+    #[test]
+    fn visvalingam_whyatt() {
+        let line = zigzag();
+        let simplified = simplify(&line, 100.0, SimplifyAlgorithm::VisvalingamWhyatt);
+        assert_eq!(simplified, vec![line[0], line[9]]);
+
+        let simplified = simplify(&line, 5.0, SimplifyAlgorithm::VisvalingamWhyatt);
+        assert_eq!(simplified, line);
+    }
+
+    /// Removes one point at a time by scanning all remaining points, for comparison.
+    fn naive_visvalingam_whyatt(points: &[(f64, f64)], threshold: f64) -> Vec<usize> {
+        let mut remaining: Vec<usize> = (0..points.len()).collect();
+        while remaining.len() > 2 {
+            let (i, area) = (1..remaining.len() - 1)
+                .map(|i| {
+                    let (a, b, c) = (remaining[i - 1], remaining[i], remaining[i + 1]);
+                    (i, triangle_area(points[a], points[b], points[c]))
+                })
+                .fold((0, f64::MAX), |a, b| if a.1 <= b.1 { a } else { b });
+            if area >= threshold {
+                break;
+            }
+            remaining.remove(i);
+        }
+        remaining
+    }
+
+    #[test]
+    fn visvalingam_whyatt_same_as_naive() {
+        let mut random: u64 = 1;
+        let points: Vec<(f64, f64)> = (0..500)
+            .map(|i| {
+                random = random.wrapping_mul(6364136223846793005).wrapping_add(1);
+                (i as f64 * 10.0, (random >> 33) as f64 % 100.0)
+            })
+            .collect();
+
+        for threshold in &[10.0, 500.0, 5000.0] {
+            let mut keep = vec![false; points.len()];
+            super::visvalingam_whyatt(&points, *threshold, false, &mut keep);
+            let kept: Vec<usize> = (0..points.len()).filter(|i| keep[*i]).collect();
+            assert_eq!(kept, naive_visvalingam_whyatt(&points, *threshold));
+        }
+    }
+
+    #[test]
+    fn simplify_long_way() {
+        let line: Vec<Coordinate> =
+            vec![(0.0, -179.0).into(), (0.0, 0.0).into(), (0.0, 179.0).into()];
+
+        for algorithm in &[
+            SimplifyAlgorithm::DouglasPeucker,
+            SimplifyAlgorithm::VisvalingamWhyatt,
+        ] {
+            let simplified = simplify(&line, 1.0, *algorithm);
+            assert_eq!(simplified, vec![line[0], line[2]]);
+        }
+    }
+
+    #[test]
+    fn simplify_ring() {
+        let ring: Vec<Coordinate> = vec![
+            (0.0, 0.0).into(),
+            (0.0, 0.0001).into(),
+            (0.0001, 0.0001).into(),
+            (0.0001, 0.0).into(),
+            (0.0, 0.0).into(),
+        ];
+
+        for algorithm in &[
+            SimplifyAlgorithm::DouglasPeucker,
+            SimplifyAlgorithm::VisvalingamWhyatt,
+        ] {
+            let simplified = simplify(&ring, 1000.0, *algorithm);
+            assert_eq!(simplified.len(), 4);
+            assert_eq!(simplified.first(), simplified.last());
+        }
+    }
+
+    #[test]
+    fn simplify_ways_keeps_topology() {
+        let mut osm = Osm::default();
+        for (i, c) in zigzag().into_iter().enumerate() {
+            let tags = if i == 3 {
+                vec![("highway", "crossing").into()]
+            } else {
+                vec![]
+            };
+            osm.add_node(Node {
+                id: i as i64 + 1,
+                coordinate: c,
+                meta: Meta {
+                    tags,
+                    ..Meta::default()
+                },
+            });
+        }
+
+        osm.add_way(Way {
+            id: 20,
+            refs: (1..=10).collect(),
+            meta: Meta::default(),
+        });
+        osm.add_way(Way {
+            id: 21,
+            refs: vec![5, 6],
+            meta: Meta::default(),
+        });
+        osm.add_relation(Relation {
+            id: 30,
+            members: vec![RelationMember::Node(8, "".to_owned())],
+            meta: Meta::default(),
+        });
+
+        osm.simplify_ways(100.0, SimplifyAlgorithm::DouglasPeucker);
+        assert_eq!(osm.ways[0].refs, vec![1, 4, 5, 6, 8, 10]);
+        assert_eq!(osm.ways[1].refs, vec![5, 6]);
+
+        let ids: Vec<i64> = osm.nodes.iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![1, 4, 5, 6, 8, 10]);
+        assert_eq!(osm.find_node(8).unwrap().id, 8);
+    }
+}
@@ -0,0 +1,261 @@
+//! Slippy map tiles, i.e. the z/x/y tiles used by most web maps.
+//!
+//! See: https://wiki.openstreetmap.org/wiki/Slippy_map_tilenames
+use crate::geo::projection::WEB_MERCATOR_MAX_LAT;
+use crate::geo::{Boundary, Coordinate};
+use std::f64::consts::PI;
+use std::fmt::{Display, Formatter};
+
+/// Max supported zoom level.
+pub const MAX_ZOOM: u8 = 31;
+
+/// A slippy map tile. Tile 0/0/0 covers the whole world, the number of tiles doubles in each
+/// direction for every zoom level. The x axis goes from west to east and the y axis from north to
+/// south.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::geo::{Boundary, Tile};
+/// let tile = Tile::from_coordinate((59.3293, 18.0686).into(), 10).unwrap();
+/// assert_eq!(Some(tile), Tile::new(10, 563, 301));
+/// assert_eq!(tile.to_string(), "10/563/301");
+/// assert_eq!(tile.quadkey(), "1200312213");
+/// assert_eq!(Tile::from_quadkey("1200312213"), Some(tile));
+///
+/// assert!(tile.boundary().contains((59.3293, 18.0686).into()));
+///
+/// let tiles = Tile::covering(&Boundary::new((-1.0, -1.0), (1.0, 1.0)), 1).unwrap();
+/// assert_eq!(tiles.len(), 4);
+///
+/// assert_eq!(Tile::new(1, 2, 0), None);
+/// assert_eq!(Tile::from_coordinate((59.3293, 18.0686).into(), 32), None);
+/// ```
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct Tile {
+    pub zoom: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Tile {
+    /// Returns `None` if zoom is above `MAX_ZOOM` or x or y is outside of the zoom level.
+    pub fn new(zoom: u8, x: u32, y: u32) -> Option<Self> {
+        if zoom > MAX_ZOOM || u64::from(x) >= tile_count(zoom) || u64::from(y) >= tile_count(zoom) {
+            return None;
+        }
+        Some(Tile { zoom, x, y })
+    }
+
+    /// Find the tile containing a coordinate. Coordinates on the edge between two tiles belong to
+    /// the tile to the east and south respectively. Latitudes outside of Web Mercator are clamped
+    /// to the first and last row of tiles. Returns `None` if zoom is above `MAX_ZOOM`.
+    pub fn from_coordinate(coordinate: Coordinate, zoom: u8) -> Option<Self> {
+        if zoom > MAX_ZOOM {
+            return None;
+        }
+
+        let n = tile_count(zoom) as f64;
+        let lat = coordinate
+            .lat()
+            .clamp(-WEB_MERCATOR_MAX_LAT, WEB_MERCATOR_MAX_LAT)
+            .to_radians();
+
+        let x = (coordinate.lon() + 180.0) / 360.0 * n;
+        let y = (1.0 - lat.tan().asinh() / PI) / 2.0 * n;
+        Tile::new(zoom, clamp_index(x, n), clamp_index(y, n))
+    }
+
+    /// All tiles at a zoom level that intersects with a boundary, row by row from north west.
+    /// Returns `None` if zoom is above `MAX_ZOOM`.
+    pub fn covering(boundary: &Boundary, zoom: u8) -> Option<Vec<Self>> {
+        if zoom > MAX_ZOOM {
+            return None;
+        }
+        if boundary.is_empty() {
+            return Some(Vec::new());
+        }
+
+        let north_west = Tile::from_coordinate(
+            Coordinate {
+                lat: boundary.max.lat,
+                lon: boundary.min.lon,
+            },
+            zoom,
+        )?;
+        let south_east = Tile::from_coordinate(
+            Coordinate {
+                lat: boundary.min.lat,
+                lon: boundary.max.lon,
+            },
+            zoom,
+        )?;
+
+        let columns: Vec<u32> = if boundary.crosses_antimeridian() {
+            let last = tile_count(zoom) as u32 - 1;
+            (north_west.x..=last).chain(0..=south_east.x).collect()
+        } else {
+            (north_west.x..=south_east.x).collect()
+        };
+
+        let mut tiles = Vec::new();
+        for y in north_west.y..=south_east.y {
+            for x in &columns {
+                tiles.push(Tile { zoom, x: *x, y });
+            }
+        }
+        Some(tiles)
+    }
+
+    /// Parse a Bing maps quadkey. Returns `None` if the key contains anything but the digits 0-3
+    /// or is too long.
+    ///
+    /// See: https://docs.microsoft.com/en-us/bingmaps/articles/bing-maps-tile-system
+    pub fn from_quadkey(quadkey: &str) -> Option<Self> {
+        if quadkey.len() > MAX_ZOOM as usize {
+            return None;
+        }
+
+        let (mut x, mut y) = (0, 0);
+        for c in quadkey.chars() {
+            let digit = c.to_digit(4)?;
+            x = x << 1 | digit & 1;
+            y = y << 1 | digit >> 1;
+        }
+        Tile::new(quadkey.len() as u8, x, y)
+    }
+
+    /// The Bing maps quadkey of the tile, one digit per zoom level.
+    pub fn quadkey(&self) -> String {
+        (1..=self.zoom)
+            .rev()
+            .map(|i| {
+                let mask = 1 << (i - 1);
+                let digit = (self.x & mask != 0) as u8 + 2 * (self.y & mask != 0) as u8;
+                (b'0' + digit) as char
+            })
+            .collect()
+    }
+
+    /// The area covered by the tile.
+    pub fn boundary(&self) -> Boundary {
+        Boundary::new(
+            tile_corner(self.x, self.y + 1, self.zoom),
+            tile_corner(self.x + 1, self.y, self.zoom),
+        )
+    }
+}
+
+impl Display for Tile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}/{}", self.zoom, self.x, self.y)
+    }
+}
+
+/// Number of tiles in each direction.
+fn tile_count(zoom: u8) -> u64 {
+    1 << zoom
+}
+
+fn clamp_index(value: f64, n: f64) -> u32 {
+    value.floor().clamp(0.0, n - 1.0) as u32
+}
+
+/// The north west corner of a tile.
+fn tile_corner(x: u32, y: u32, zoom: u8) -> Coordinate {
+    let n = tile_count(zoom) as f64;
+    let lon = x as f64 / n * 360.0 - 180.0;
+    let lat = (PI * (1.0 - 2.0 * y as f64 / n)).sinh().atan().to_degrees();
+    Coordinate::rounded(lat, lon)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geo::{Boundary, Tile};
+
+    #[test]
+    fn tile_from_coordinate() {
+        assert_eq!(
+            Tile::from_coordinate((0.0, 0.0).into(), 0),
+            Tile::new(0, 0, 0)
+        );
+        assert_eq!(
+            Tile::from_coordinate((0.0, 0.0).into(), 1),
+            Tile::new(1, 1, 1)
+        );
+        assert_eq!(
+            Tile::from_coordinate((90.0, -180.0).into(), 2),
+            Tile::new(2, 0, 0)
+        );
+        assert_eq!(
+            Tile::from_coordinate((-90.0, 180.0).into(), 2),
+            Tile::new(2, 3, 3)
+        );
+        assert_eq!(
+            Tile::from_coordinate((51.5074, -0.1278).into(), 12),
+            Tile::new(12, 2046, 1362)
+        );
+    }
+
+    #[test]
+    fn invalid_tiles() {
+        assert_eq!(
+            Tile::new(31, (1 << 31) - 1, 0).map(|t| t.x),
+            Some((1 << 31) - 1)
+        );
+        assert_eq!(Tile::new(32, 0, 0), None);
+        assert_eq!(Tile::new(2, 4, 0), None);
+        assert_eq!(Tile::new(2, 0, 4), None);
+        assert_eq!(Tile::from_coordinate((0.0, 0.0).into(), 32), None);
+    }
+
+    #[test]
+    fn tile_boundary() {
+        assert_eq!(
+            Tile::new(1, 0, 0).unwrap().boundary(),
+            Boundary::new((0.0, -180.0), (85.0511288, 0.0))
+        );
+
+        let tile = Tile::new(12, 2046, 1362).unwrap();
+        let boundary = tile.boundary();
+        assert_eq!(Tile::from_coordinate(boundary.center(), 12), Some(tile));
+        assert!(Tile::covering(&boundary, 12).unwrap().contains(&tile));
+    }
+
+    #[test]
+    fn tiles_covering() {
+        let boundary = Boundary::new((51.3, -0.2), (51.6, 0.1));
+        let tiles = Tile::covering(&boundary, 10).unwrap();
+        assert_eq!(
+            tiles,
+            vec![
+                Tile::new(10, 511, 340).unwrap(),
+                Tile::new(10, 512, 340).unwrap(),
+                Tile::new(10, 511, 341).unwrap(),
+                Tile::new(10, 512, 341).unwrap(),
+            ]
+        );
+
+        assert_eq!(Tile::covering(&Boundary::inverted(), 10), Some(Vec::new()));
+        assert_eq!(Tile::covering(&boundary, 32), None);
+
+        let boundary = Boundary::new((-1.0, 170.0), (1.0, -170.0));
+        assert_eq!(
+            Tile::covering(&boundary, 1).unwrap(),
+            vec![
+                Tile::new(1, 1, 0).unwrap(),
+                Tile::new(1, 0, 0).unwrap(),
+                Tile::new(1, 1, 1).unwrap(),
+                Tile::new(1, 0, 1).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn quadkey() {
+        assert_eq!(Tile::new(0, 0, 0).unwrap().quadkey(), "");
+        assert_eq!(Tile::new(3, 3, 5).unwrap().quadkey(), "213");
+        assert_eq!(Tile::from_quadkey("213"), Tile::new(3, 3, 5));
+        assert_eq!(Tile::from_quadkey(""), Tile::new(0, 0, 0));
+        assert_eq!(Tile::from_quadkey("124"), None);
+    }
+}
@@ -0,0 +1,93 @@
+//! Meta data of a map file.
+use crate::Timestamp;
+
+/// Meta data about the map as a whole, e.g. which program generated it and how recent the data
+/// is. Readers fill in what the format contains and writers write what the format supports.
+///
+/// The xml format supports all fields. The o5m format only supports the timestamp.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::{OsmBuilder, OsmHeader, Timestamp};
+/// # use vadeen_osm::osm_io::{create_reader, create_writer, FileFormat};
+/// let mut osm = OsmBuilder::default().build();
+/// osm.header = OsmHeader {
+///     generator: Some("my-tool 1.0".to_owned()),
+///     timestamp: Some(Timestamp(1577836800)),
+///     ..OsmHeader::default()
+/// };
+///
+/// let mut writer = create_writer(Vec::new(), FileFormat::Xml);
+/// writer.write(&osm).unwrap();
+/// let xml = writer.into_inner();
+///
+/// let read = create_reader(xml.as_slice(), FileFormat::Xml).read().unwrap();
+/// assert_eq!(read.header.generator, Some("my-tool 1.0".to_owned()));
+/// assert_eq!(read.header.timestamp, Some(Timestamp(1577836800)));
+/// ```
+#[derive(Debug, Eq, PartialEq, Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct OsmHeader {
+    /// The program that generated the data. Writers use their own name if this is not set, see
+    /// `XmlWriterOptions::generator`.
+    pub generator: Option<String>,
+
+    /// Version of the OSM data format, e.g. "0.6".
+    pub version: Option<String>,
+
+    /// Time of the data, e.g. `osm_base` from Overpass.
+    pub timestamp: Option<Timestamp>,
+
+    /// Sequence number of the replication diff the data is up to date with.
+    pub replication_sequence: Option<u64>,
+
+    /// Where the data comes from, e.g. an url.
+    pub source: Option<String>,
+
+    /// Copyright and license note of the data.
+    pub copyright: Option<String>,
+
+    /// How the copyright is written in xml.
+    pub copyright_form: CopyrightForm,
+
+    /// Whether editors like JOSM allow the data to be uploaded to OSM.
+    pub upload: Option<UploadPolicy>,
+}
+
+/// The forms of the copyright note in xml.
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum CopyrightForm {
+    /// A `<note>` element, used by Overpass.
+    #[default]
+    Note,
+
+    /// A `copyright` attribute on `<osm>`, used by the OSM API.
+    Attribute,
+}
+
+/// The `upload` attribute of JOSM files.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum UploadPolicy {
+    /// The data can be uploaded, `upload="true"`.
+    Normal,
+
+    /// The user is warned before uploading, `upload="false"`.
+    Discouraged,
+
+    /// The data can not be uploaded, `upload="never"`.
+    Blocked,
+}
@@ -0,0 +1,268 @@
+//! Time travel in history data, i.e. maps with several versions of the same elements.
+//!
+//! Versions are ordered by version number and timestamp. Deleted versions have `visible` set to
+//! false in their meta data.
+use crate::{Meta, Node, Osm, Relation, Timestamp, Way};
+use std::collections::HashMap;
+use std::ops::RangeBounds;
+
+/// An element with versioned meta data.
+trait Versioned: Clone {
+    fn id(&self) -> i64;
+
+    fn meta(&self) -> &Meta;
+
+    /// The time the version was created, if known.
+    fn created(&self) -> Option<Timestamp> {
+        self.meta().author.as_ref().map(|a| a.created)
+    }
+
+    /// True if this version is newer than, or the same as, `other`.
+    fn is_newer(&self, other: &Self) -> bool {
+        (self.meta().version, self.created()) >= (other.meta().version, other.created())
+    }
+}
+
+impl Osm {
+    /// The state of the map at `timestamp`. Contains the latest version of each element created at
+    /// or before `timestamp`, elements deleted at that time are left out. Elements without
+    /// timestamp are always included.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::{AuthorInformation, Meta, Node, Osm, Timestamp};
+    /// fn version(version: u32, created: i64, visible: bool) -> Node {
+    ///     let author = AuthorInformation {
+    ///         created: Timestamp(created),
+    ///         change_set: 1,
+    ///         uid: 1,
+    ///         user: "user".to_owned(),
+    ///     };
+    ///     Node {
+    ///         id: 1,
+    ///         coordinate: (1.0, f64::from(version)).into(),
+    ///         meta: Meta {
+    ///             version: Some(version),
+    ///             author: Some(author),
+    ///             visible,
+    ///             ..Meta::default()
+    ///         },
+    ///     }
+    /// }
+    ///
+    /// let mut osm = Osm::default();
+    /// osm.add_node(version(1, 100, true));
+    /// osm.add_node(version(2, 200, true));
+    /// osm.add_node(version(3, 300, false));
+    ///
+    /// assert!(osm.snapshot_at(Timestamp(50)).nodes.is_empty());
+    /// assert_eq!(osm.snapshot_at(Timestamp(250)).nodes[0].meta.version, Some(2));
+    /// assert!(osm.snapshot_at(Timestamp(300)).nodes.is_empty());
+    /// ```
+    pub fn snapshot_at(&self, timestamp: Timestamp) -> Osm {
+        let mut osm = self.empty_copy();
+        for node in latest_versions(&self.nodes, timestamp) {
+            osm.add_node(node);
+        }
+        for way in latest_versions(&self.ways, timestamp) {
+            osm.add_way(way);
+        }
+        for relation in latest_versions(&self.relations, timestamp) {
+            osm.add_relation(relation);
+        }
+        osm.recompute_boundary();
+        osm
+    }
+
+    /// All versions created within `range`, including deleted versions. Elements without
+    /// timestamp are left out.
+    pub fn filter_time<R: RangeBounds<Timestamp>>(&self, range: R) -> Osm {
+        let mut osm = self.empty_copy();
+        for node in versions_in(&self.nodes, &range) {
+            osm.add_node(node);
+        }
+        for way in versions_in(&self.ways, &range) {
+            osm.add_way(way);
+        }
+        for relation in versions_in(&self.relations, &range) {
+            osm.add_relation(relation);
+        }
+        osm.recompute_boundary();
+        osm
+    }
+
+    /// A map with the same header but without elements.
+    fn empty_copy(&self) -> Osm {
+        Osm {
+            header: self.header.clone(),
+            ..Osm::default()
+        }
+    }
+}
+
+/// The latest visible version of each element at `timestamp`, in order of first appearance.
+fn latest_versions<T: Versioned>(elements: &[T], timestamp: Timestamp) -> Vec<T> {
+    let mut latest: Vec<&T> = Vec::new();
+    let mut index = HashMap::new();
+    for element in elements {
+        if element.created().is_some_and(|created| created > timestamp) {
+            continue;
+        }
+
+        match index.get(&element.id()) {
+            Some(&i) => {
+                if element.is_newer(latest[i]) {
+                    latest[i] = element;
+                }
+            }
+            None => {
+                index.insert(element.id(), latest.len());
+                latest.push(element);
+            }
+        }
+    }
+
+    latest
+        .into_iter()
+        .filter(|e| e.meta().visible)
+        .cloned()
+        .collect()
+}
+
+fn versions_in<T: Versioned, R: RangeBounds<Timestamp>>(elements: &[T], range: &R) -> Vec<T> {
+    elements
+        .iter()
+        .filter(|e| e.created().is_some_and(|created| range.contains(&created)))
+        .cloned()
+        .collect()
+}
+
+impl Versioned for Node {
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn meta(&self) -> &Meta {
+        &self.meta
+    }
+}
+
+impl Versioned for Way {
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn meta(&self) -> &Meta {
+        &self.meta
+    }
+}
+
+impl Versioned for Relation {
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn meta(&self) -> &Meta {
+        &self.meta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geo::Boundary;
+    use crate::{AuthorInformation, Meta, Node, Osm, Timestamp, Way};
+
+    fn meta(version: u32, created: i64, visible: bool) -> Meta {
+        Meta {
+            version: Some(version),
+            author: Some(AuthorInformation {
+                created: Timestamp(created),
+                change_set: 1,
+                uid: 1,
+                user: "user".to_owned(),
+            }),
+            visible,
+            ..Meta::default()
+        }
+    }
+
+    fn history() -> Osm {
+        let mut osm = Osm::default();
+        osm.add_node(Node {
+            id: 1,
+            coordinate: (1.0, 1.0).into(),
+            meta: meta(1, 100, true),
+        });
+        osm.add_way(Way {
+            id: 2,
+            refs: vec![1, 1],
+            meta: meta(1, 150, true),
+        });
+        osm.add_node(Node {
+            id: 1,
+            coordinate: (2.0, 2.0).into(),
+            meta: meta(2, 200, true),
+        });
+        osm.add_node(Node {
+            id: 1,
+            meta: meta(3, 300, false),
+            ..Node::default()
+        });
+        osm
+    }
+
+    #[test]
+    fn snapshot_at() {
+        let osm = history();
+
+        let snapshot = osm.snapshot_at(Timestamp(150));
+        assert_eq!(snapshot.nodes[0].coordinate, (1.0, 1.0).into());
+        assert_eq!(snapshot.ways.len(), 1);
+        assert_eq!(
+            snapshot.boundary,
+            Some(Boundary::new((1.0, 1.0), (1.0, 1.0)))
+        );
+
+        let snapshot = osm.snapshot_at(Timestamp(299));
+        assert_eq!(snapshot.nodes.len(), 1);
+        assert_eq!(snapshot.find_node(1).unwrap().coordinate, (2.0, 2.0).into());
+
+        let snapshot = osm.snapshot_at(Timestamp(300));
+        assert!(snapshot.nodes.is_empty());
+        assert_eq!(snapshot.ways.len(), 1);
+        assert_eq!(snapshot.boundary, None);
+    }
+
+    #[test]
+    fn snapshot_without_timestamps() {
+        let mut osm = Osm::default();
+        osm.add_node(Node::default());
+        osm.add_node(Node {
+            meta: Meta {
+                version: Some(2),
+                ..Meta::default()
+            },
+            ..Node::default()
+        });
+
+        let snapshot = osm.snapshot_at(Timestamp(0));
+        assert_eq!(snapshot.nodes.len(), 1);
+        assert_eq!(snapshot.nodes[0].meta.version, Some(2));
+    }
+
+    #[test]
+    fn filter_time() {
+        let osm = history();
+
+        let filtered = osm.filter_time(Timestamp(150)..=Timestamp(300));
+        let versions: Vec<_> = filtered.nodes.iter().map(|n| n.meta.version).collect();
+        assert_eq!(versions, vec![Some(2), Some(3)]);
+        assert_eq!(filtered.ways.len(), 1);
+        assert_eq!(
+            filtered.boundary,
+            Some(Boundary::new((2.0, 2.0), (2.0, 2.0)))
+        );
+
+        assert!(osm.filter_time(..Timestamp(100)).nodes.is_empty());
+    }
+}
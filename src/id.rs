@@ -0,0 +1,174 @@
+//! Id allocation of new elements in the `OsmBuilder`.
+use crate::Osm;
+
+/// Determines how the [`OsmBuilder`] assigns ids to new elements.
+///
+/// Ids are never reused, the counters always start after the ids already present in the map.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::{IdAllocation, OsmBuilder};
+/// let mut builder = OsmBuilder::default();
+/// builder.set_id_allocation(IdAllocation {
+///     start: 100,
+///     per_type: true,
+///     ..IdAllocation::negative()
+/// });
+/// builder.add_polyline(vec![(1.0, 1.0), (1.0, 2.0)], vec![("highway", "track")]);
+///
+/// let osm = builder.build();
+/// assert_eq!(osm.nodes[0].id, -100);
+/// assert_eq!(osm.nodes[1].id, -101);
+/// assert_eq!(osm.ways[0].id, -100);
+/// ```
+///
+/// [`OsmBuilder`]: struct.OsmBuilder.html
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct IdAllocation {
+    /// Use negative ids counting down. This is the JOSM convention for elements that have not
+    /// been uploaded, which avoids conflicts with existing OSM elements.
+    pub negative: bool,
+
+    /// Absolute value of the first id.
+    pub start: i64,
+
+    /// Separate counters for nodes, ways and relations, otherwise all types share one counter.
+    pub per_type: bool,
+}
+
+/// Element types with separate counters.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum IdType {
+    Node = 0,
+    Way = 1,
+    Relation = 2,
+}
+
+/// Keeps track of the next id of each type.
+#[derive(Debug)]
+pub(crate) struct IdAllocator {
+    allocation: IdAllocation,
+    next: [i64; 3],
+}
+
+impl IdAllocation {
+    /// Negative ids counting down from -1.
+    pub fn negative() -> Self {
+        IdAllocation {
+            negative: true,
+            ..IdAllocation::default()
+        }
+    }
+}
+
+impl Default for IdAllocation {
+    /// Positive ids counting up from 1, shared by all types.
+    fn default() -> Self {
+        IdAllocation {
+            negative: false,
+            start: 1,
+            per_type: false,
+        }
+    }
+}
+
+impl IdAllocator {
+    /// Create an allocator with counters starting after the ids used in `osm`.
+    pub(crate) fn new(allocation: IdAllocation, osm: &Osm) -> Self {
+        let used = |ids: &mut dyn Iterator<Item = i64>| {
+            ids.filter(|id| (*id < 0) == allocation.negative)
+                .map(i64::abs)
+                .max()
+                .unwrap_or(0)
+        };
+
+        let mut next = [
+            used(&mut osm.nodes.iter().map(|n| n.id)),
+            used(&mut osm.ways.iter().map(|w| w.id)),
+            used(&mut osm.relations.iter().map(|r| r.id)),
+        ];
+        if !allocation.per_type {
+            next = [*next.iter().max().unwrap(); 3];
+        }
+
+        for n in &mut next {
+            *n = (*n + 1).max(allocation.start);
+        }
+        IdAllocator { allocation, next }
+    }
+
+    pub(crate) fn next(&mut self, id_type: IdType) -> i64 {
+        let id = if self.allocation.per_type {
+            let next = &mut self.next[id_type as usize];
+            *next += 1;
+            *next - 1
+        } else {
+            let id = self.next[0];
+            self.next = [id + 1; 3];
+            id
+        };
+
+        if self.allocation.negative {
+            -id
+        } else {
+            id
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::id::{IdAllocator, IdType};
+    use crate::{IdAllocation, Meta, Node, Osm, Way};
+
+    #[test]
+    fn shared_counter() {
+        let mut ids = IdAllocator::new(IdAllocation::default(), &Osm::default());
+        assert_eq!(ids.next(IdType::Node), 1);
+        assert_eq!(ids.next(IdType::Way), 2);
+        assert_eq!(ids.next(IdType::Relation), 3);
+        assert_eq!(ids.next(IdType::Node), 4);
+    }
+
+    #[test]
+    fn seeded_from_osm() {
+        let mut osm = Osm::default();
+        osm.add_node(Node {
+            id: -5,
+            coordinate: (1.0, 1.0).into(),
+            meta: Meta::default(),
+        });
+        osm.add_node(Node {
+            id: 7,
+            coordinate: (1.0, 2.0).into(),
+            meta: Meta::default(),
+        });
+        osm.add_way(Way {
+            id: 20,
+            refs: vec![-5, 7],
+            meta: Meta::default(),
+        });
+
+        let mut ids = IdAllocator::new(IdAllocation::default(), &osm);
+        assert_eq!(ids.next(IdType::Node), 21);
+
+        let allocation = IdAllocation {
+            per_type: true,
+            ..IdAllocation::default()
+        };
+        let mut ids = IdAllocator::new(allocation, &osm);
+        assert_eq!(ids.next(IdType::Node), 8);
+        assert_eq!(ids.next(IdType::Way), 21);
+        assert_eq!(ids.next(IdType::Relation), 1);
+
+        let mut ids = IdAllocator::new(IdAllocation::negative(), &osm);
+        assert_eq!(ids.next(IdType::Way), -6);
+
+        let allocation = IdAllocation {
+            start: 1000,
+            ..IdAllocation::negative()
+        };
+        let mut ids = IdAllocator::new(allocation, &osm);
+        assert_eq!(ids.next(IdType::Node), -1000);
+    }
+}
@@ -0,0 +1,263 @@
+//! Conversions to and from [`geo-types`], enabled by the `geo-types` feature.
+//!
+//! The x axis of geo-types is longitude and the y axis is latitude.
+//!
+//! [`geo-types`]: https://docs.rs/geo-types
+use crate::geo::{Coordinate, MultiPolygon, Polygon, Position};
+use crate::{ElementId, Osm, OsmBuilder, Tag, Way};
+
+impl From<geo_types::Coord<f64>> for Coordinate {
+    fn from(c: geo_types::Coord<f64>) -> Self {
+        Coordinate::rounded(c.y, c.x)
+    }
+}
+
+impl From<Coordinate> for geo_types::Coord<f64> {
+    fn from(c: Coordinate) -> Self {
+        geo_types::coord! { x: c.lon(), y: c.lat() }
+    }
+}
+
+impl From<geo_types::Point<f64>> for Coordinate {
+    fn from(p: geo_types::Point<f64>) -> Self {
+        p.0.into()
+    }
+}
+
+impl From<Coordinate> for geo_types::Point<f64> {
+    fn from(c: Coordinate) -> Self {
+        geo_types::Point(c.into())
+    }
+}
+
+impl From<Polygon> for geo_types::Polygon<f64> {
+    fn from(polygon: Polygon) -> Self {
+        geo_types::Polygon::new(
+            line_string(polygon.outer),
+            polygon.inner.into_iter().map(line_string).collect(),
+        )
+    }
+}
+
+impl From<MultiPolygon> for geo_types::MultiPolygon<f64> {
+    fn from(multi_polygon: MultiPolygon) -> Self {
+        geo_types::MultiPolygon(multi_polygon.polygons.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<geo_types::Polygon<f64>> for Polygon {
+    fn from(polygon: geo_types::Polygon<f64>) -> Self {
+        let (exterior, interiors) = polygon.into_inner();
+        Polygon::new(
+            coordinates(&exterior),
+            interiors.iter().map(coordinates).collect(),
+        )
+    }
+}
+
+impl From<geo_types::MultiPolygon<f64>> for MultiPolygon {
+    fn from(multi_polygon: geo_types::MultiPolygon<f64>) -> Self {
+        MultiPolygon::new(multi_polygon.0.into_iter().map(Into::into).collect())
+    }
+}
+
+impl Osm {
+    /// Get the geometry of a way as a line string. Returns `None` if any of the nodes are missing
+    /// from the map.
+    ///
+    /// Use `MultiPolygon::from_relation` and `Polygon::from_way` to get areas, they can be
+    /// converted into their geo-types counterparts.
+    pub fn way_line_string(&self, way: &Way) -> Option<geo_types::LineString<f64>> {
+        self.way_coordinates(way).map(line_string)
+    }
+}
+
+impl OsmBuilder {
+    /// Add a geo-types geometry. Points are added as nodes, lines as polylines, and polygons as
+    /// polygons or multipolygons. Collections add each part with the same tags. Returns the ids of
+    /// the added elements, polylines may add several ways.
+    ///
+    /// The x and y values are converted with the projection of the builder.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::OsmBuilder;
+    /// use geo_types::{line_string, point, polygon};
+    ///
+    /// let mut builder = OsmBuilder::default();
+    /// builder.add_geometry(point!(x: 18.07, y: 59.33), vec![("place", "city")]);
+    /// builder.add_geometry(
+    ///     line_string![(x: 18.0, y: 59.0), (x: 18.1, y: 59.1)],
+    ///     vec![("highway", "primary")],
+    /// );
+    /// builder.add_geometry(
+    ///     polygon![(x: 0.0, y: 0.0), (x: 1.0, y: 0.0), (x: 1.0, y: 1.0), (x: 0.0, y: 0.0)],
+    ///     vec![("natural", "water")],
+    /// );
+    ///
+    /// let osm = builder.build();
+    /// assert_eq!(osm.nodes[0].coordinate, (59.33, 18.07).into());
+    /// assert_eq!(osm.ways.len(), 2);
+    ///
+    /// let line = osm.way_line_string(&osm.ways[0]).unwrap();
+    /// assert_eq!(line, line_string![(x: 18.0, y: 59.0), (x: 18.1, y: 59.1)]);
+    /// ```
+    pub fn add_geometry<G, T>(&mut self, geometry: G, tags: Vec<T>) -> Vec<ElementId>
+    where
+        G: Into<geo_types::Geometry<f64>>,
+        T: Into<Tag>,
+    {
+        let tags: Vec<Tag> = tags.into_iter().map(T::into).collect();
+        let mut ids = Vec::new();
+        self.add_geometry_parts(geometry.into(), &tags, &mut ids);
+        ids
+    }
+
+    fn add_geometry_parts(
+        &mut self,
+        geometry: geo_types::Geometry<f64>,
+        tags: &[Tag],
+        ids: &mut Vec<ElementId>,
+    ) {
+        use geo_types::Geometry;
+
+        match geometry {
+            Geometry::Point(point) => {
+                ids.push(self.add_point(position(point.0), tags.to_vec()).into());
+            }
+            Geometry::Line(line) => {
+                let line = vec![position(line.start), position(line.end)];
+                let ways = self.add_polyline(line, tags.to_vec());
+                ids.extend(ways.into_iter().map(ElementId::from));
+            }
+            Geometry::LineString(line) => {
+                if line.0.is_empty() {
+                    return;
+                }
+                let line = line.0.into_iter().map(position).collect();
+                let ways = self.add_polyline(line, tags.to_vec());
+                ids.extend(ways.into_iter().map(ElementId::from));
+            }
+            Geometry::Polygon(polygon) => {
+                if polygon.exterior().0.is_empty() {
+                    return;
+                }
+                let (exterior, interiors) = polygon.into_inner();
+                let parts = std::iter::once(exterior)
+                    .chain(interiors)
+                    .map(|ring| ring.0.into_iter().map(position).collect())
+                    .collect();
+                ids.push(self.add_polygon(parts, tags.to_vec()));
+            }
+            Geometry::MultiPolygon(multi_polygon) => {
+                let polygons: Vec<Polygon> = multi_polygon
+                    .0
+                    .into_iter()
+                    .filter(|p| !p.exterior().0.is_empty())
+                    .map(|p| {
+                        let (exterior, interiors) = p.into_inner();
+                        Polygon::new(
+                            self.to_coordinates(exterior.0.into_iter().map(position).collect()),
+                            interiors
+                                .into_iter()
+                                .map(|r| {
+                                    self.to_coordinates(r.0.into_iter().map(position).collect())
+                                })
+                                .collect(),
+                        )
+                    })
+                    .collect();
+                if !polygons.is_empty() {
+                    ids.push(self.add_multipolygon(polygons, tags.to_vec()).into());
+                }
+            }
+            Geometry::MultiPoint(points) => {
+                for point in points {
+                    self.add_geometry_parts(point.into(), tags, ids);
+                }
+            }
+            Geometry::MultiLineString(lines) => {
+                for line in lines {
+                    self.add_geometry_parts(line.into(), tags, ids);
+                }
+            }
+            Geometry::GeometryCollection(collection) => {
+                for geometry in collection {
+                    self.add_geometry_parts(geometry, tags, ids);
+                }
+            }
+            Geometry::Rect(rect) => self.add_geometry_parts(rect.to_polygon().into(), tags, ids),
+            Geometry::Triangle(triangle) => {
+                self.add_geometry_parts(triangle.to_polygon().into(), tags, ids)
+            }
+        }
+    }
+}
+
+/// Geo-types coordinates are treated as projected so the projection of the builder is used.
+fn position(c: geo_types::Coord<f64>) -> Position {
+    Position::Projected(c.x, c.y)
+}
+
+fn line_string(coordinates: Vec<Coordinate>) -> geo_types::LineString<f64> {
+    coordinates
+        .into_iter()
+        .map(geo_types::Coord::from)
+        .collect()
+}
+
+fn coordinates(line: &geo_types::LineString<f64>) -> Vec<Coordinate> {
+    line.0.iter().map(|&c| c.into()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geo::{Coordinate, MultiPolygon, WebMercator};
+    use crate::{ElementId, OsmBuilder, RelationId};
+    use geo_types::{coord, line_string, polygon, MultiPolygon as GeoMultiPolygon};
+
+    #[test]
+    fn coordinate_conversion() {
+        let c = Coordinate::new(59.3293, 18.0686);
+        let geo: geo_types::Coord<f64> = c.into();
+        assert_eq!(geo, coord! { x: 18.0686, y: 59.3293 });
+        assert_eq!(Coordinate::from(geo), c);
+        assert_eq!(Coordinate::from(geo_types::Point(geo)), c);
+    }
+
+    #[test]
+    fn coordinate_conversion_rounds() {
+        let geo = coord! { x: -179.9518987, y: 65.0344851 };
+        let c = Coordinate::from(geo);
+        assert_eq!((c.lat, c.lon), (650_344_851, -1_799_518_987));
+    }
+
+    #[test]
+    fn multipolygon_round_trip() {
+        let outer = polygon!(
+            exterior: [(x: 0.0, y: 0.0), (x: 3.0, y: 0.0), (x: 3.0, y: 3.0), (x: 0.0, y: 0.0)],
+            interiors: [[(x: 1.0, y: 1.0), (x: 2.0, y: 1.0), (x: 2.0, y: 1.5), (x: 1.0, y: 1.0)]],
+        );
+        let geo = GeoMultiPolygon(vec![outer]);
+
+        let mut builder = OsmBuilder::default();
+        let ids = builder.add_geometry(geo.clone(), vec![("natural", "water")]);
+        assert_eq!(ids, vec![ElementId::Relation(RelationId(9))]);
+
+        let osm = builder.build();
+        let multi_polygon = MultiPolygon::from_relation(&osm, &osm.relations[0]).unwrap();
+        assert_eq!(GeoMultiPolygon::from(multi_polygon), geo);
+    }
+
+    #[test]
+    fn geometry_projected() {
+        let mut builder = OsmBuilder::with_projection(WebMercator);
+        builder.add_geometry(
+            line_string![(x: 0.0, y: 0.0), (x: 20037508.342789244, y: 0.0)],
+            vec![("highway", "primary")],
+        );
+
+        let osm = builder.build();
+        assert_eq!(osm.nodes[1].coordinate, (0.0, 180.0).into());
+    }
+}
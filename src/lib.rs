@@ -15,13 +15,42 @@
 //! [`osm_io`]: osm_io/index.html
 //! [`geo`]: geo/index.html
 mod element;
+mod extract;
 pub mod geo;
+mod header;
+mod history;
+mod id;
+#[cfg(feature = "geo-types")]
+mod interop;
 pub mod osm_io;
+#[cfg(feature = "serde")]
+mod serialize;
+mod snap;
+mod timestamp;
+mod topology;
 
-use crate::geo::{Boundary, Coordinate};
+use crate::geo::{
+    normalize_ring, Boundary, Coordinate, Polygon, Position, Projection, Wgs84, Winding,
+};
+use crate::id::{IdAllocator, IdType};
+use crate::osm_io::error::{Error, ErrorKind, Result};
+use crate::snap::SnapIndex;
+use crate::topology::Edge;
 pub use element::*;
-use std::cmp::max;
+pub use extract::*;
+pub use header::{CopyrightForm, OsmHeader, UploadPolicy};
+pub use id::IdAllocation;
+#[cfg(feature = "serde")]
+pub use serialize::{raw_coordinate, RawCoordinates};
+pub use snap::Snapping;
+use std::cmp::min;
 use std::collections::HashMap;
+pub use timestamp::Timestamp;
+
+/// Max number of nodes in a way accepted by the OSM API.
+///
+/// See: https://wiki.openstreetmap.org/wiki/API_v0.6#Capabilities
+pub const MAX_WAY_NODES: usize = 2000;
 
 /// `OsmBuilder` makes it easy to build OSM maps from non OSM data. Polygons, multi polygons,
 /// poly lines and points are all represented as vectors of coordinates.
@@ -66,10 +95,43 @@ use std::collections::HashMap;
 /// assert_eq!(osm.boundary, Some(Boundary::new((1.0, 1.0), (10.0, 10.0))));
 /// ```
 ///
+/// Projected data, e.g. from CAD or survey sources, can be added with a [`Projection`]. Projected
+/// positions are converted to coordinates by the builder:
+/// ```
+/// # use vadeen_osm::OsmBuilder;
+/// # use vadeen_osm::geo::{Position, Utm};
+/// let mut builder = OsmBuilder::with_projection(Utm::new(33, true).unwrap());
+/// builder.add_point(Position::Projected(597_700.0, 6_727_500.0), vec![("power", "tower")]);
+///
+/// let osm = builder.build();
+/// assert_eq!(osm.nodes[0].coordinate, (60.6712513, 16.7880908).into());
+/// ```
+///
 /// [`Osm`]: struct.Osm.html
 /// [`osm_io`]: osm_io/index.html
+/// [`Projection`]: geo/trait.Projection.html
 pub struct OsmBuilder {
     osm: Osm,
+    projection: Box<dyn Projection>,
+    ring_normalization: Option<RingNormalization>,
+    max_way_nodes: usize,
+    snapping: Option<Snapping>,
+    snap_index: Option<SnapIndex>,
+    shared_edges: bool,
+    edge_ways: HashMap<Edge, i64>,
+    ids: IdAllocator,
+}
+
+/// Normalization of polygon rings added to an [`OsmBuilder`]. Consecutive duplicate coordinates
+/// are removed and the rings are closed. The winding of outer and inner rings is enforced if set.
+///
+/// The default is counterclockwise outer rings and clockwise inner rings.
+///
+/// [`OsmBuilder`]: struct.OsmBuilder.html
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct RingNormalization {
+    pub outer: Option<Winding>,
+    pub inner: Option<Winding>,
 }
 
 /// Abstract representation of an OSM map.
@@ -91,103 +153,476 @@ pub struct OsmBuilder {
 /// [`OsmBuilder`]: struct.OsmBuilder.html
 #[derive(Debug)]
 pub struct Osm {
+    pub header: OsmHeader,
     pub boundary: Option<Boundary>,
     pub nodes: Vec<Node>,
     pub ways: Vec<Way>,
     pub relations: Vec<Relation>,
-    max_id: i64,
     node_id_index: HashMap<Coordinate, i64>,
+    node_index: HashMap<i64, usize>,
+    way_index: HashMap<i64, usize>,
+    relation_index: HashMap<i64, usize>,
 }
 
 impl OsmBuilder {
+    /// Create a builder which converts projected positions with `projection`.
+    pub fn with_projection<P: Projection + 'static>(projection: P) -> Self {
+        let mut builder = OsmBuilder::from_osm(Osm::default());
+        builder.projection = Box::new(projection);
+        builder
+    }
+
+    /// Create a builder which adds elements to an existing map. New ids never collide with the
+    /// ids in the map, and coordinates matching existing nodes reuse those nodes.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::{Meta, Node, Osm, OsmBuilder};
+    /// let mut osm = Osm::default();
+    /// osm.add_node(Node { id: 10, coordinate: (1.0, 1.0).into(), meta: Meta::default() });
+    ///
+    /// let mut builder = OsmBuilder::from_osm(osm);
+    /// builder.add_polyline(vec![(1.0, 1.0), (1.0, 2.0)], vec![("highway", "track")]);
+    ///
+    /// let osm = builder.build();
+    /// assert_eq!(osm.ways[0].refs, vec![10, 11]);
+    /// assert_eq!(osm.ways[0].id, 12);
+    /// ```
+    pub fn from_osm(osm: Osm) -> Self {
+        let ids = IdAllocator::new(IdAllocation::default(), &osm);
+        OsmBuilder {
+            osm,
+            projection: Box::new(Wgs84),
+            ring_normalization: None,
+            max_way_nodes: MAX_WAY_NODES,
+            snapping: None,
+            snap_index: None,
+            shared_edges: false,
+            edge_ways: HashMap::new(),
+            ids,
+        }
+    }
+
+    /// Set how ids are assigned to new elements, see [`IdAllocation`]. Positive ids counting up
+    /// from 1 are used by default.
+    ///
+    /// [`IdAllocation`]: struct.IdAllocation.html
+    pub fn set_id_allocation(&mut self, allocation: IdAllocation) {
+        self.ids = IdAllocator::new(allocation, &self.osm);
+    }
+
+    /// Snap coordinates so nearby coordinates share the same node, see [`Snapping`]. Only
+    /// coordinates added after this is set are affected. Without snapping, nodes are only shared by
+    /// coordinates that are exactly equal.
+    ///
+    /// Consecutive coordinates snapped to the same node are only referenced once in ways.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::{OsmBuilder, Snapping};
+    /// let mut builder = OsmBuilder::default();
+    /// builder.set_snapping(Some(Snapping::Metres(1.0)));
+    /// builder.add_polyline(vec![(1.0, 1.0), (1.0, 2.0)], vec![("highway", "track")]);
+    /// builder.add_polyline(vec![(1.000001, 2.0), (1.0, 3.0)], vec![("highway", "track")]);
+    ///
+    /// let osm = builder.build();
+    /// assert_eq!(osm.nodes.len(), 3);
+    /// assert_eq!(osm.ways[0].refs.last(), osm.ways[1].refs.first());
+    /// ```
+    ///
+    /// [`Snapping`]: enum.Snapping.html
+    pub fn set_snapping(&mut self, snapping: Option<Snapping>) {
+        self.snap_index = match snapping {
+            Some(Snapping::Metres(tolerance)) => {
+                let mut index = SnapIndex::new(tolerance);
+                for node in &self.osm.nodes {
+                    index.insert(node.coordinate, node.id);
+                }
+                Some(index)
+            }
+            _ => None,
+        };
+        self.snapping = snapping;
+    }
+
+    /// Share edges between polygon rings. Parts of rings that are shared with rings added
+    /// earlier reuse the same ways, instead of duplicating the geometry. This is typically used
+    /// together with snapping, so almost shared borders become exactly shared.
+    ///
+    /// All polygons are added as multipolygon relations when this is enabled, since the rings
+    /// may consist of several ways.
+    pub fn set_shared_edges(&mut self, shared_edges: bool) {
+        self.shared_edges = shared_edges;
+    }
+
+    /// Max number of nodes per way, `MAX_WAY_NODES` by default. Longer poly lines are split into
+    /// several ways, chained by sharing their end nodes. Longer polygon rings are split the same
+    /// way and added as multipolygon relations.
+    ///
+    /// Panics if `max` is less than 2.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::OsmBuilder;
+    /// let mut builder = OsmBuilder::default();
+    /// builder.set_max_way_nodes(3);
+    /// builder.add_polyline(vec![(1.0, 1.0), (1.0, 2.0), (1.0, 3.0), (1.0, 4.0)], vec![("highway", "track")]);
+    /// builder.add_polygon(vec![vec![(5.0, 5.0), (5.0, 6.0), (6.0, 6.0), (5.0, 5.0)]], vec![("natural", "water")]);
+    ///
+    /// let osm = builder.build();
+    /// assert_eq!(osm.ways[0].refs, vec![1, 2, 3]);
+    /// assert_eq!(osm.ways[1].refs, vec![3, 4]);
+    /// assert_eq!(osm.ways[0].meta.tags, osm.ways[1].meta.tags);
+    ///
+    /// // The polygon ring is split into two untagged ways, the tags are added to the relation.
+    /// assert_eq!(osm.ways[2].refs, vec![7, 8, 9]);
+    /// assert_eq!(osm.ways[3].refs, vec![9, 7]);
+    /// assert!(osm.ways[2].meta.tags.is_empty());
+    /// assert_eq!(osm.relations[0].members.len(), 2);
+    /// assert_eq!(osm.relations[0].meta.tags.len(), 2);
+    /// ```
+    pub fn set_max_way_nodes(&mut self, max: usize) {
+        assert!(
+            max >= 2,
+            "A way must be allowed at least 2 nodes, got {}",
+            max
+        );
+        self.max_way_nodes = max;
+    }
+
+    /// Normalize polygon rings before they are added, see [`RingNormalization`]. Rings are added
+    /// as given by default.
+    ///
+    /// [`RingNormalization`]: struct.RingNormalization.html
+    pub fn set_ring_normalization(&mut self, normalization: Option<RingNormalization>) {
+        self.ring_normalization = normalization;
+    }
+
     pub fn build(self) -> Osm {
         self.osm
     }
 
-    pub fn add_point<C: Into<Coordinate>, T: Into<Tag>>(&mut self, coordinate: C, tags: Vec<T>) {
+    /// Add a node. If there already is a node at the coordinate, the tags are added to that node
+    /// instead. Returns the id of the node.
+    pub fn add_point<C: Into<Position>, T: Into<Tag>>(
+        &mut self,
+        coordinate: C,
+        tags: Vec<T>,
+    ) -> NodeId {
         let tags = tags.into_iter().map(T::into).collect();
-        self.add_node(coordinate.into(), tags);
+        let coordinate = coordinate.into().to_coordinate(self.projection.as_ref());
+        NodeId(self.add_node(coordinate, tags))
     }
 
     /// First part is the outer polygon, rest of the parts is inner polygons.
     /// `parts` must not be empty or a panic will occur.
-    pub fn add_polygon<C, T>(&mut self, mut parts: Vec<Vec<C>>, tags: Vec<T>)
+    ///
+    /// Use `add_multipolygon` for polygons with more than one outer ring, and `try_add_polygon`
+    /// to validate the rings.
+    ///
+    /// Returns the id of the way, or the id of the multipolygon relation if the polygon has inner
+    /// rings or needs more than one way.
+    pub fn add_polygon<C, T>(&mut self, parts: Vec<Vec<C>>, tags: Vec<T>) -> ElementId
     where
-        C: Into<Coordinate>,
+        C: Into<Position>,
         T: Into<Tag>,
     {
-        if parts.len() == 1 {
-            self.add_polyline(parts.pop().unwrap(), tags);
+        let mut rings = parts.into_iter().map(|part| self.to_coordinates(part));
+        let outer = rings.next().unwrap();
+        let inner: Vec<Vec<Coordinate>> = rings.collect();
+
+        let outer = self.normalize_ring(outer, false);
+        if inner.is_empty() && outer.len() <= self.max_way_nodes && !self.shared_edges {
+            self.add_polyline(outer, tags)[0].into()
         } else {
-            self.add_multipolygon(parts, tags);
+            self.add_multipolygon(vec![Polygon::new(outer, inner)], tags)
+                .into()
+        }
+    }
+
+    /// Same as `add_polygon`, but fails if there are no rings or if any ring has less than 3
+    /// distinct coordinates. Nothing is added if it fails.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::{OsmBuilder, RingNormalization};
+    /// let mut builder = OsmBuilder::default();
+    /// builder.set_ring_normalization(Some(RingNormalization::default()));
+    ///
+    /// // Closed, duplicate removed and reversed to counterclockwise.
+    /// let ring = vec![(0.0, 0.0), (1.0, 1.0), (1.0, 1.0), (0.0, 1.0)];
+    /// builder.try_add_polygon(vec![ring], vec![("natural", "water")]).unwrap();
+    ///
+    /// let ring = vec![(0.0, 0.0), (1.0, 1.0), (0.0, 0.0)];
+    /// let error = builder.try_add_polygon(vec![ring], vec![("natural", "water")]).unwrap_err();
+    /// assert_eq!(error.to_string(), "Ring 1 has less than 3 distinct coordinates.");
+    ///
+    /// let osm = builder.build();
+    /// assert_eq!(osm.ways[0].refs, vec![1, 2, 3, 1]);
+    /// assert_eq!(osm.nodes[1].coordinate, (0.0, 1.0).into());
+    /// ```
+    pub fn try_add_polygon<C, T>(&mut self, parts: Vec<Vec<C>>, tags: Vec<T>) -> Result<ElementId>
+    where
+        C: Into<Position>,
+        T: Into<Tag>,
+    {
+        let parts: Vec<Vec<Coordinate>> = parts
+            .into_iter()
+            .map(|part| self.to_coordinates(part))
+            .collect();
+
+        if parts.is_empty() {
+            let message = "Polygon has no rings.".to_owned();
+            return Err(Error::new(ErrorKind::InvalidGeometry, Some(message)));
+        }
+
+        for (i, part) in parts.iter().enumerate() {
+            if normalize_ring(part, None).is_none() {
+                let message = format!("Ring {} has less than 3 distinct coordinates.", i + 1);
+                return Err(Error::new(ErrorKind::InvalidGeometry, Some(message)));
+            }
         }
+
+        Ok(self.add_polygon(parts, tags))
     }
 
-    pub fn add_polyline<C, T>(&mut self, coordinates: Vec<C>, tags: Vec<T>) -> i64
+    /// Add a poly line as a way. If the line is longer than the max number of nodes per way it is
+    /// split into several ways with the same tags, see `set_max_way_nodes`.
+    ///
+    /// Returns the ids of the ways, in order.
+    pub fn add_polyline<C, T>(&mut self, coordinates: Vec<C>, tags: Vec<T>) -> Vec<WayId>
     where
-        C: Into<Coordinate>,
+        C: Into<Position>,
         T: Into<Tag>,
     {
         let refs = self.add_nodes(coordinates);
-        let id = self.next_id();
+        let tags = tags.into_iter().map(T::into).collect();
+        self.add_ways(refs, tags).into_iter().map(WayId).collect()
+    }
+
+    /// Add a multipolygon relation, each polygon has its own outer ring and zero or more inner
+    /// rings. Returns the id of the relation.
+    ///
+    /// Each ring is added as a way, the tags are added to the relation. Use
+    /// [`MultiPolygon::from_rings`] if the roles of the rings are unknown.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::{OsmBuilder, RelationMember};
+    /// # use vadeen_osm::geo::{MultiPolygon, Polygon};
+    /// let square = |min: f64, max: f64| {
+    ///     vec![(min, min).into(), (min, max).into(), (max, max).into(), (max, min).into(), (min, min).into()]
+    /// };
+    ///
+    /// let mut builder = OsmBuilder::default();
+    /// builder.add_multipolygon(
+    ///     vec![
+    ///         Polygon::new(square(0.0, 3.0), vec![square(1.0, 2.0)]),
+    ///         Polygon::new(square(4.0, 5.0), vec![]),
+    ///     ],
+    ///     vec![("natural", "water")],
+    /// );
+    ///
+    /// // Roles from containment.
+    /// let rings = vec![square(6.0, 9.0), square(7.0, 8.0)];
+    /// let polygons = MultiPolygon::from_rings(rings).unwrap().polygons;
+    /// builder.add_multipolygon(polygons, vec![("natural", "water")]);
+    ///
+    /// let osm = builder.build();
+    /// assert_eq!(osm.ways.len(), 5);
+    /// assert_eq!(osm.relations.len(), 2);
+    /// let roles: Vec<&str> = osm.relations[0]
+    ///     .members
+    ///     .iter()
+    ///     .map(|m| match m {
+    ///         RelationMember::Way(_, role) => role.as_str(),
+    ///         _ => unreachable!(),
+    ///     })
+    ///     .collect();
+    /// assert_eq!(roles, vec!["outer", "inner", "outer"]);
+    /// ```
+    ///
+    /// [`MultiPolygon::from_rings`]: geo/struct.MultiPolygon.html#method.from_rings
+    pub fn add_multipolygon<T: Into<Tag>>(
+        &mut self,
+        polygons: Vec<Polygon>,
+        tags: Vec<T>,
+    ) -> RelationId {
+        let mut members = Vec::new();
+        for polygon in polygons {
+            for id in self.add_ring(polygon.outer, false) {
+                members.push(RelationMember::Way(id, "outer".to_owned()));
+            }
+
+            for ring in polygon.inner {
+                for id in self.add_ring(ring, true) {
+                    members.push(RelationMember::Way(id, "inner".to_owned()));
+                }
+            }
+        }
+
+        let mut tags: Vec<Tag> = tags.into_iter().map(T::into).collect();
+        tags.push(("type", "multipolygon").into());
+        self.add_relation(members, tags)
+    }
+
+    /// Add a relation with any members. Members can be given as `(id, role)` where the id is
+    /// returned by another add method. Returns the id of the relation.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::{OsmBuilder, RelationMember, Tag};
+    /// let mut builder = OsmBuilder::default();
+    /// let stop = builder.add_point((1.0, 1.0), vec![("highway", "bus_stop")]);
+    /// let road = builder.add_polyline(vec![(1.0, 1.0), (1.0, 2.0)], vec![("highway", "primary")]);
+    ///
+    /// let mut members: Vec<RelationMember> = vec![(stop, "stop").into()];
+    /// members.extend(road.iter().map(|&way| RelationMember::from((way, ""))));
+    /// let route = builder.add_relation(members, vec![("type", "route"), ("route", "bus")]);
+    ///
+    /// builder.add_tags(route, vec![("ref", "4")]);
+    /// builder.meta_mut(stop).unwrap().version = Some(2);
+    ///
+    /// let osm = builder.build();
+    /// assert_eq!(osm.relations[0].members, vec![RelationMember::Node(1, "stop".to_owned()), RelationMember::Way(3, "".to_owned())]);
+    /// assert_eq!(osm.relations[0].meta.tags[2], Tag::from(("ref", "4")));
+    /// assert_eq!(osm.nodes[0].meta.version, Some(2));
+    /// ```
+    pub fn add_relation<M, T>(&mut self, members: Vec<M>, tags: Vec<T>) -> RelationId
+    where
+        M: Into<RelationMember>,
+        T: Into<Tag>,
+    {
+        let id = self.next_relation_id();
         let meta = Meta {
-            tags: tags.into_iter().map(|t| t.into()).collect(),
+            tags: tags.into_iter().map(T::into).collect(),
             ..Default::default()
         };
-        self.osm.add_way(Way { id, refs, meta });
-        id
+        let members = members.into_iter().map(M::into).collect();
+        self.osm.add_relation(Relation { id, members, meta });
+        RelationId(id)
     }
 
-    fn add_multipolygon<C, T>(&mut self, parts: Vec<Vec<C>>, tags: Vec<T>)
+    /// Add tags to an element that has already been added. Returns false if the element does not
+    /// exist.
+    pub fn add_tags<I, T>(&mut self, id: I, tags: Vec<T>) -> bool
     where
-        C: Into<Coordinate>,
+        I: Into<ElementId>,
         T: Into<Tag>,
     {
-        let mut polygon_ids = Vec::new();
-        for part in parts {
-            let refs = self.add_nodes(part);
-            let id = self.next_id();
-            let meta = Meta::default();
-            self.osm.add_way(Way { id, refs, meta });
-            polygon_ids.push(id);
+        match self.osm.meta_mut(id) {
+            Some(meta) => {
+                meta.tags.extend(tags.into_iter().map(T::into));
+                true
+            }
+            None => false,
         }
+    }
 
-        let mut tags: Vec<Tag> = tags.into_iter().map(T::into).collect();
-        tags.push(("type", "multipolygon").into());
+    /// Get the metadata of an element that has already been added, e.g. to set the version or
+    /// author information.
+    pub fn meta_mut<I: Into<ElementId>>(&mut self, id: I) -> Option<&mut Meta> {
+        self.osm.meta_mut(id)
+    }
 
-        let (outer, inner) = polygon_ids.split_first().unwrap();
-        self.add_polygon_relations(*outer, inner, tags);
+    /// Add untagged ways for a multipolygon ring.
+    fn add_ring(&mut self, ring: Vec<Coordinate>, inner: bool) -> Vec<i64> {
+        let ring = self.normalize_ring(ring, inner);
+        let refs = self.add_nodes(ring);
+        if self.shared_edges {
+            self.add_shared_ring(refs)
+        } else {
+            self.add_ways(refs, vec![])
+        }
     }
 
-    fn add_polygon_relations(&mut self, outer: i64, inner: &[i64], tags: Vec<Tag>) {
-        let mut members = Vec::new();
-        for rel_ref in inner {
-            members.push(RelationMember::Way(*rel_ref, "inner".to_owned()));
+    /// Add one or more ways, split so no way exceeds the max number of nodes. Consecutive ways
+    /// share their end nodes.
+    fn add_ways(&mut self, refs: Vec<i64>, tags: Vec<Tag>) -> Vec<i64> {
+        let step = self.max_way_nodes - 1;
+        let mut parts = Vec::new();
+        let mut start = 0;
+        loop {
+            let end = min(start + self.max_way_nodes, refs.len());
+            parts.push(refs[start..end].to_vec());
+            if end == refs.len() {
+                break;
+            }
+            start += step;
         }
 
-        members.push(RelationMember::Way(outer, "outer".to_owned()));
-        let id = self.next_id();
+        let mut ids = Vec::new();
+        for refs in parts {
+            let id = self.next_way_id();
+            let meta = Meta {
+                tags: tags.clone(),
+                ..Default::default()
+            };
+            self.osm.add_way(Way { id, refs, meta });
+            ids.push(id);
+        }
+        ids
+    }
 
-        let meta = Meta {
-            tags: tags.into_iter().map(|t| t).collect(),
-            ..Default::default()
+    /// Normalize a ring if enabled. Rings with too few coordinates are left as they are.
+    fn normalize_ring(&self, ring: Vec<Coordinate>, inner: bool) -> Vec<Coordinate> {
+        let normalization = match self.ring_normalization {
+            Some(normalization) => normalization,
+            None => return ring,
         };
-        self.osm.add_relation(Relation { id, members, meta });
+
+        let winding = if inner {
+            normalization.inner
+        } else {
+            normalization.outer
+        };
+        normalize_ring(&ring, winding).unwrap_or(ring)
     }
 
-    fn add_nodes<C: Into<Coordinate>>(&mut self, coordinates: Vec<C>) -> Vec<i64> {
-        coordinates
+    fn to_coordinates<C: Into<Position>>(&self, positions: Vec<C>) -> Vec<Coordinate> {
+        positions
             .into_iter()
-            .map(|c| self.add_node(c.into(), vec![]))
+            .map(|c| c.into().to_coordinate(self.projection.as_ref()))
             .collect()
     }
 
+    fn add_nodes<C: Into<Position>>(&mut self, coordinates: Vec<C>) -> Vec<i64> {
+        let mut refs: Vec<i64> = coordinates
+            .into_iter()
+            .map(|c| {
+                let coordinate = c.into().to_coordinate(self.projection.as_ref());
+                self.add_node(coordinate, vec![])
+            })
+            .collect();
+
+        if self.snapping.is_some() {
+            refs.dedup();
+        }
+        refs
+    }
+
     fn add_node(&mut self, coordinate: Coordinate, tags: Vec<Tag>) -> i64 {
-        if let Some(id) = self.osm.find_node_id(coordinate) {
+        let coordinate = match self.snapping {
+            Some(snapping) => snapping.snap(coordinate),
+            None => coordinate,
+        };
+
+        let existing = self
+            .osm
+            .find_node_id(coordinate)
+            .or_else(|| self.snap_index.as_ref()?.find(coordinate));
+        if let Some(id) = existing {
+            if !tags.is_empty() {
+                self.add_tags(NodeId(id), tags);
+            }
             return id;
         }
 
-        let id = self.osm.max_id + 1;
+        let id = self.ids.next(IdType::Node);
+        if let Some(index) = &mut self.snap_index {
+            index.insert(coordinate, id);
+        }
+
         let meta = Meta {
             tags,
             ..Default::default()
@@ -200,38 +635,89 @@ impl OsmBuilder {
         id
     }
 
-    fn next_id(&mut self) -> i64 {
-        self.osm.max_id += 1;
-        self.osm.max_id
+    fn next_way_id(&mut self) -> i64 {
+        self.ids.next(IdType::Way)
+    }
+
+    fn next_relation_id(&mut self) -> i64 {
+        self.ids.next(IdType::Relation)
     }
 }
 
-impl Default for OsmBuilder {
+impl Default for RingNormalization {
     fn default() -> Self {
-        OsmBuilder {
-            osm: Osm::default(),
+        RingNormalization {
+            outer: Some(Winding::CounterClockwise),
+            inner: Some(Winding::Clockwise),
         }
     }
 }
 
+impl Default for OsmBuilder {
+    fn default() -> Self {
+        OsmBuilder::with_projection(Wgs84)
+    }
+}
+
 impl Osm {
+    /// Set the boundary to the smallest boundary containing all visible nodes, or `None` if there
+    /// are no visible nodes. Useful after modifying or removing nodes directly.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::{Osm, OsmBuilder};
+    /// # use vadeen_osm::geo::Boundary;
+    /// let mut builder = OsmBuilder::default();
+    /// builder.add_point((1.0, 1.0), vec![("power", "tower")]);
+    /// builder.add_point((2.0, 2.0), vec![("power", "tower")]);
+    ///
+    /// let mut osm = builder.build();
+    /// osm.nodes.pop();
+    /// osm.recompute_boundary();
+    /// assert_eq!(osm.boundary, Some(Boundary::new((1.0, 1.0), (1.0, 1.0))));
+    ///
+    /// let mut osm = Osm::default();
+    /// osm.recompute_boundary();
+    /// assert_eq!(osm.boundary, None);
+    /// ```
+    pub fn recompute_boundary(&mut self) {
+        // Deleted nodes in history data have no coordinate.
+        let coordinates = self
+            .nodes
+            .iter()
+            .filter(|n| n.meta.visible)
+            .map(|n| n.coordinate);
+        let boundary = Boundary::from_coordinates(coordinates);
+        self.boundary = if boundary.is_empty() {
+            None
+        } else {
+            Some(boundary)
+        };
+    }
+
     /// Add a node to the map, the boundary is expanded to include the node.
     pub fn add_node(&mut self, node: Node) {
-        if let Some(boundary) = &mut self.boundary {
-            boundary.expand(node.coordinate);
+        // Deleted nodes in history data have no coordinate.
+        if node.meta.visible {
+            if let Some(boundary) = &mut self.boundary {
+                boundary.expand(node.coordinate);
+            }
+            self.node_id_index.insert(node.coordinate, node.id);
         }
 
-        self.max_id = max(self.max_id, node.id);
-        self.node_id_index.insert(node.coordinate.clone(), node.id);
+        self.node_index.insert(node.id, self.nodes.len());
         self.nodes.push(node);
     }
 
     /// Add a way to the map.
     pub fn add_way(&mut self, way: Way) {
+        self.way_index.insert(way.id, self.ways.len());
         self.ways.push(way);
     }
 
     pub fn add_relation(&mut self, relation: Relation) {
+        self.relation_index
+            .insert(relation.id, self.relations.len());
         self.relations.push(relation);
     }
 
@@ -239,31 +725,160 @@ impl Osm {
     pub fn find_node_id(&mut self, coordinate: Coordinate) -> Option<i64> {
         self.node_id_index.get(&coordinate).cloned()
     }
+
+    /// Find node in an osm map by id.
+    ///
+    /// Lookups use an index. If `nodes` has been modified directly so the index no longer matches,
+    /// this falls back to a linear search. Use `rebuild_indices` to make lookups fast again.
+    pub fn find_node(&self, id: i64) -> Option<&Node> {
+        match self.node_index.get(&id) {
+            Some(&idx) if self.nodes.get(idx).is_some_and(|n| n.id == id) => Some(&self.nodes[idx]),
+            _ => self.nodes.iter().find(|n| n.id == id),
+        }
+    }
+
+    /// Find way in an osm map by id. See `find_node`.
+    pub fn find_way(&self, id: i64) -> Option<&Way> {
+        match self.way_index.get(&id) {
+            Some(&idx) if self.ways.get(idx).is_some_and(|w| w.id == id) => Some(&self.ways[idx]),
+            _ => self.ways.iter().find(|w| w.id == id),
+        }
+    }
+
+    /// Find relation in an osm map by id. See `find_node`.
+    pub fn find_relation(&self, id: i64) -> Option<&Relation> {
+        match self.relation_index.get(&id) {
+            Some(&idx) if self.relations.get(idx).is_some_and(|r| r.id == id) => {
+                Some(&self.relations[idx])
+            }
+            _ => self.relations.iter().find(|r| r.id == id),
+        }
+    }
+
+    /// Find node in an osm map by id and get a mutable reference. See `find_node`.
+    pub fn find_node_mut(&mut self, id: i64) -> Option<&mut Node> {
+        match self.node_index.get(&id) {
+            Some(&idx) if self.nodes.get(idx).is_some_and(|n| n.id == id) => {
+                Some(&mut self.nodes[idx])
+            }
+            _ => self.nodes.iter_mut().find(|n| n.id == id),
+        }
+    }
+
+    /// Find way in an osm map by id and get a mutable reference. See `find_node`.
+    pub fn find_way_mut(&mut self, id: i64) -> Option<&mut Way> {
+        match self.way_index.get(&id) {
+            Some(&idx) if self.ways.get(idx).is_some_and(|w| w.id == id) => {
+                Some(&mut self.ways[idx])
+            }
+            _ => self.ways.iter_mut().find(|w| w.id == id),
+        }
+    }
+
+    /// Find relation in an osm map by id and get a mutable reference. See `find_node`.
+    pub fn find_relation_mut(&mut self, id: i64) -> Option<&mut Relation> {
+        match self.relation_index.get(&id) {
+            Some(&idx) if self.relations.get(idx).is_some_and(|r| r.id == id) => {
+                Some(&mut self.relations[idx])
+            }
+            _ => self.relations.iter_mut().find(|r| r.id == id),
+        }
+    }
+
+    /// Get the metadata of any element type by id.
+    pub fn meta_mut<I: Into<ElementId>>(&mut self, id: I) -> Option<&mut Meta> {
+        match id.into() {
+            ElementId::Node(NodeId(id)) => self.find_node_mut(id).map(|n| &mut n.meta),
+            ElementId::Way(WayId(id)) => self.find_way_mut(id).map(|w| &mut w.meta),
+            ElementId::Relation(RelationId(id)) => self.find_relation_mut(id).map(|r| &mut r.meta),
+        }
+    }
+
+    /// Get the coordinates of all nodes referenced by a way, in order.
+    /// Returns `None` if any of the nodes are missing from the map.
+    pub fn way_coordinates(&self, way: &Way) -> Option<Vec<Coordinate>> {
+        self.resolve_refs(&way.refs)
+    }
+
+    /// Rebuild the lookup indices used by e.g. `find_node`. After `nodes`, `ways` or `relations`
+    /// have been modified directly, lookups fall back to linear searches until this is called.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::OsmBuilder;
+    /// let mut builder = OsmBuilder::default();
+    /// builder.add_point((1.0, 1.0), vec![("power", "tower")]);
+    /// builder.add_point((2.0, 2.0), vec![("power", "tower")]);
+    ///
+    /// let mut osm = builder.build();
+    /// osm.nodes.remove(0);
+    /// assert!(osm.find_node(1).is_none());
+    /// assert!(osm.find_node(2).is_some());
+    ///
+    /// osm.rebuild_indices();
+    /// assert!(osm.find_node(2).is_some());
+    /// ```
+    pub fn rebuild_indices(&mut self) {
+        self.node_id_index = self
+            .nodes
+            .iter()
+            .filter(|n| n.meta.visible)
+            .map(|n| (n.coordinate, n.id))
+            .collect();
+        self.node_index = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.id, i))
+            .collect();
+        self.way_index = self
+            .ways
+            .iter()
+            .enumerate()
+            .map(|(i, w)| (w.id, i))
+            .collect();
+        self.relation_index = self
+            .relations
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r.id, i))
+            .collect();
+    }
+
+    /// Map node ids to coordinates. Returns `None` if any of the nodes are missing.
+    pub(crate) fn resolve_refs(&self, refs: &[i64]) -> Option<Vec<Coordinate>> {
+        refs.iter()
+            .map(|id| self.find_node(*id).map(|n| n.coordinate))
+            .collect()
+    }
 }
 
 impl Default for Osm {
     fn default() -> Self {
         Osm {
+            header: OsmHeader::default(),
             boundary: Some(Boundary::inverted()),
             nodes: Vec::new(),
             ways: Vec::new(),
             relations: Vec::new(),
-            max_id: 0,
             node_id_index: HashMap::new(),
+            node_index: HashMap::new(),
+            way_index: HashMap::new(),
+            relation_index: HashMap::new(),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::geo::Boundary;
-    use crate::{Meta, Node, Osm};
+    use crate::geo::{ring_winding, Boundary, Position, WebMercator, Winding};
+    use crate::{
+        ElementId, Meta, Node, NodeId, Osm, OsmBuilder, RelationId, RingNormalization, Tag, WayId,
+    };
 
     #[test]
     fn osm_add_node() {
         let mut osm = Osm::default();
-        assert_eq!(osm.max_id, 0);
-
         osm.add_node(Node {
             id: 10,
             coordinate: (65.0, 55.0).into(),
@@ -275,7 +890,138 @@ mod tests {
             max: (65.0, 55.0).into(),
             freeze: false,
         };
-        assert_eq!(osm.max_id, 10);
         assert_eq!(osm.boundary, Some(expected_boundary));
     }
+
+    #[test]
+    fn recompute_boundary_skips_deleted_nodes() {
+        let mut osm = Osm::default();
+        osm.add_node(Node {
+            id: 1,
+            coordinate: (65.0, 55.0).into(),
+            meta: Meta::default(),
+        });
+        osm.add_node(Node {
+            id: 1,
+            meta: Meta {
+                visible: false,
+                ..Meta::default()
+            },
+            ..Node::default()
+        });
+
+        osm.recompute_boundary();
+        assert_eq!(
+            osm.boundary,
+            Some(Boundary::new((65.0, 55.0), (65.0, 55.0)))
+        );
+    }
+
+    #[test]
+    fn find_after_direct_modification() {
+        let mut osm = Osm::default();
+        for id in 1..=3 {
+            osm.add_node(Node {
+                id,
+                coordinate: (1.0, id as f64).into(),
+                meta: Meta::default(),
+            });
+        }
+
+        osm.nodes.swap(0, 2);
+        osm.nodes.push(Node {
+            id: 4,
+            ..Node::default()
+        });
+
+        for id in 1..=4 {
+            assert_eq!(osm.find_node(id).unwrap().id, id);
+            assert_eq!(osm.find_node_mut(id).unwrap().id, id);
+        }
+        assert!(osm.find_node(5).is_none());
+    }
+
+    #[test]
+    fn builder_projected_positions() {
+        let mut builder = OsmBuilder::with_projection(WebMercator);
+        builder.add_polyline(
+            vec![
+                Position::Projected(0.0, 0.0),
+                Position::Projected(111_319.4907933, 0.0),
+                (1.0, 1.0).into(),
+            ],
+            vec![("highway", "track")],
+        );
+
+        let osm = builder.build();
+        assert_eq!(osm.nodes[0].coordinate, (0.0, 0.0).into());
+        assert_eq!(osm.nodes[1].coordinate, (0.0, 1.0).into());
+        assert_eq!(osm.nodes[2].coordinate, (1.0, 1.0).into());
+    }
+
+    #[test]
+    fn builder_ring_normalization() {
+        let mut builder = OsmBuilder::default();
+        builder.set_ring_normalization(Some(RingNormalization {
+            outer: Some(Winding::Clockwise),
+            inner: Some(Winding::CounterClockwise),
+        }));
+        builder.add_polygon(
+            vec![
+                vec![(0.0, 0.0), (0.0, 4.0), (4.0, 4.0), (4.0, 0.0)],
+                vec![(1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (2.0, 2.0), (1.0, 1.0)],
+            ],
+            vec![("natural", "water")],
+        );
+
+        let osm = builder.build();
+        let outer = osm.way_coordinates(&osm.ways[0]).unwrap();
+        let inner = osm.way_coordinates(&osm.ways[1]).unwrap();
+        assert_eq!(outer.len(), 5);
+        assert_eq!(outer.first(), outer.last());
+        assert_eq!(ring_winding(&outer), Some(Winding::Clockwise));
+        assert_eq!(inner.len(), 4);
+        assert_eq!(ring_winding(&inner), Some(Winding::CounterClockwise));
+    }
+
+    #[test]
+    fn builder_splits_long_ways() {
+        let line: Vec<(f64, f64)> = (0..4500).map(|i| (0.0, i as f64 * 0.0001)).collect();
+        let mut builder = OsmBuilder::default();
+        builder.add_polyline(line, vec![("highway", "track")]);
+
+        let osm = builder.build();
+        let lengths: Vec<usize> = osm.ways.iter().map(|w| w.refs.len()).collect();
+        assert_eq!(lengths, vec![2000, 2000, 502]);
+        assert_eq!(osm.ways[0].refs.last(), osm.ways[1].refs.first());
+        assert_eq!(osm.ways[1].refs.last(), osm.ways[2].refs.first());
+        assert!(osm.ways.iter().all(|w| w.meta.tags.len() == 1));
+    }
+
+    #[test]
+    fn builder_handles() {
+        let mut builder = OsmBuilder::default();
+        builder.set_max_way_nodes(2);
+        let square = vec![(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (0.0, 0.0)];
+
+        assert_eq!(builder.add_point((0.0, 0.0), vec![("a", "1")]), NodeId(1));
+        assert_eq!(
+            builder.add_polyline(vec![(0.0, 0.0), (0.0, 1.0), (1.0, 1.0)], vec![("b", "2")]),
+            vec![WayId(4), WayId(5)]
+        );
+        assert_eq!(
+            builder.add_polygon(vec![square], vec![("c", "3")]),
+            ElementId::Relation(RelationId(9))
+        );
+
+        // Existing node is reused and gets the new tags.
+        assert_eq!(builder.add_point((0.0, 1.0), vec![("d", "4")]), NodeId(2));
+        assert!(builder.add_tags(WayId(4), vec![("e", "5")]));
+        assert!(!builder.add_tags(WayId(100), vec![("e", "5")]));
+
+        let osm = builder.build();
+        assert_eq!(osm.nodes.len(), 3);
+        assert_eq!(osm.nodes[1].meta.tags, vec![Tag::from(("d", "4"))]);
+        assert_eq!(osm.ways[0].meta.tags.len(), 2);
+    }
 }
@@ -57,14 +57,15 @@
 //! [`write`]: fn.write.html
 //! [`FileFormat`]: enum.FileFormat.html
 //! [`error`]: error/index.html
-extern crate chrono;
-
 pub mod error;
+mod meta_filter;
 mod o5m;
+mod options;
 mod xml;
 
 use self::error::*;
 use self::o5m::O5mWriter;
+pub use self::options::*;
 use self::xml::XmlWriter;
 use crate::osm_io::o5m::O5mReader;
 use crate::osm_io::xml::XmlReader;
@@ -103,7 +104,14 @@ pub trait OsmWrite<W: Write> {
 
 /// Reader for the osm formats.
 pub trait OsmRead {
-    fn read(&mut self) -> std::result::Result<Osm, Error>;
+    /// Read the map. Warnings from lenient mode are discarded.
+    fn read(&mut self) -> std::result::Result<Osm, Error> {
+        self.read_with_warnings().map(|(osm, _)| osm)
+    }
+
+    /// Read the map and the warnings. Warnings are only produced in lenient mode, see
+    /// `ParseMode`. Each warning is an error with its location.
+    fn read_with_warnings(&mut self) -> std::result::Result<(Osm, Vec<Error>), Error>;
 }
 
 /// Convenience function for easily reading osm files.
@@ -129,6 +137,33 @@ pub fn read<P: AsRef<Path>>(path: P) -> Result<Osm> {
     reader.read()
 }
 
+/// Same as `read` but with reader options, also returns the warnings.
+///
+/// # Example
+/// ```rust,no_run
+/// # use vadeen_osm::osm_io::error::Result;
+/// # use vadeen_osm::osm_io::{read_with_options, ParseMode, ReaderOptions};
+/// # fn main() -> Result<()> {
+/// let options = ReaderOptions {
+///     mode: ParseMode::Lenient,
+/// };
+/// let (osm, warnings) = read_with_options("map.osm", options)?;
+/// for warning in warnings {
+///     eprintln!("{}", warning);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_with_options<P: AsRef<Path>>(
+    path: P,
+    options: ReaderOptions,
+) -> Result<(Osm, Vec<Error>)> {
+    let format = path.as_ref().try_into()?;
+    let file = File::open(path)?;
+    let mut reader = create_reader_with_options(BufReader::new(file), format, options);
+    reader.read_with_warnings()
+}
+
 /// Convenience function for easily writing osm files.
 /// Format is determined from file ending.
 ///
@@ -152,7 +187,37 @@ pub fn write<P: AsRef<Path>>(path: P, osm: &Osm) -> Result<()> {
     let format = path.as_ref().try_into()?;
     let file = File::create(path)?;
     let mut writer = create_writer(file, format);
-    writer.write(&osm)
+    writer.write(osm)
+}
+
+/// Same as `write` but with writer options, see `WriterOptions`.
+///
+/// # Example
+/// ```rust,no_run
+/// # use vadeen_osm::OsmBuilder;
+/// # use vadeen_osm::osm_io::error::Result;
+/// # use vadeen_osm::osm_io::{write_with_options, MetaLevel, WriterOptions};
+/// # fn main() -> Result<()> {
+/// let osm = OsmBuilder::default().build();
+///
+/// // Write without any author information.
+/// let options = WriterOptions {
+///     meta: MetaLevel::Version,
+///     ..WriterOptions::default()
+/// };
+/// write_with_options("map.osm", &osm, options)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_with_options<P: AsRef<Path>>(
+    path: P,
+    osm: &Osm,
+    options: WriterOptions,
+) -> Result<()> {
+    let format = path.as_ref().try_into()?;
+    let file = File::create(path)?;
+    let mut writer = create_writer_with_options(file, format, options);
+    writer.write(osm)
 }
 
 /// Creates an `OsmReader` appropriate to the provided `FileFormat`.
@@ -179,13 +244,22 @@ pub fn write<P: AsRef<Path>>(path: P, osm: &Osm) -> Result<()> {
 /// # Ok(())
 /// # }
 /// ```
-pub fn create_reader<'a, R: BufRead + 'a>(
+pub fn create_reader<'a, R: BufRead + 'a>(reader: R, format: FileFormat) -> Box<dyn OsmRead + 'a> {
+    match format {
+        FileFormat::Xml => Box::new(XmlReader::new(reader)),
+        FileFormat::O5m => Box::new(O5mReader::new(reader)),
+    }
+}
+
+/// Same as `create_reader` but with reader options, see `ReaderOptions`.
+pub fn create_reader_with_options<'a, R: BufRead + 'a>(
     reader: R,
     format: FileFormat,
+    options: ReaderOptions,
 ) -> Box<dyn OsmRead + 'a> {
     match format {
-        FileFormat::Xml => Box::new(XmlReader::new(reader)),
-        FileFormat::O5m => Box::new(O5mReader::new(reader)),
+        FileFormat::Xml => Box::new(XmlReader::with_options(reader, options)),
+        FileFormat::O5m => Box::new(O5mReader::with_options(reader, options)),
     }
 }
 
@@ -223,6 +297,18 @@ pub fn create_writer<'a, W: Write + 'a>(
     }
 }
 
+/// Same as `create_writer` but with writer options, see `WriterOptions`.
+pub fn create_writer_with_options<'a, W: Write + 'a>(
+    writer: W,
+    format: FileFormat,
+    options: WriterOptions,
+) -> Box<dyn OsmWrite<W> + 'a> {
+    match format {
+        FileFormat::O5m => Box::new(O5mWriter::with_options(writer, options)),
+        FileFormat::Xml => Box::new(XmlWriter::with_options(writer, options)),
+    }
+}
+
 impl FileFormat {
     pub fn from(s: &str) -> Option<Self> {
         match s {
@@ -237,7 +323,7 @@ impl TryFrom<&str> for FileFormat {
     type Error = Error;
 
     fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
-        if let Some(format) = FileFormat::from(&value) {
+        if let Some(format) = FileFormat::from(value) {
             Ok(format)
         } else {
             Err(Error::new(
@@ -1,5 +1,6 @@
 use crate::osm_io::error::ErrorKind::IO;
-use crate::osm_io::error::Repr::Simple;
+use crate::osm_io::error::Repr::{Custom, Simple};
+use crate::ElementId;
 use std::fmt::{Display, Formatter};
 use std::io;
 
@@ -10,15 +11,18 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub struct Error {
     repr: Repr,
     message: Option<String>,
+    location: Box<Location>,
 }
 
 /// It will make it possible to change internals without breaking change.
 #[derive(Debug)]
 enum Repr {
     Simple(ErrorKind),
+    Custom(ErrorKind, Box<dyn std::error::Error + Send + Sync>),
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ErrorKind {
     /// Invalid file format. E.g. unsupported or unrecognized osm format.
     InvalidFileFormat,
@@ -28,6 +32,58 @@ pub enum ErrorKind {
 
     /// IO error. E.g. file not found, permission denied.
     IO(io::Error),
+
+    /// Invalid geometry. E.g. a polygon ring with too few points.
+    InvalidGeometry,
+
+    /// A required attribute is missing, contains the name of the attribute.
+    MissingAttribute(String),
+
+    /// An attribute contains a value that could not be parsed.
+    InvalidAttributeValue { attribute: String, value: String },
+
+    /// A relation member has a type that is not node, way or relation.
+    UnknownMemberType(String),
+
+    /// A string reference points outside of the string reference table.
+    BadStringReference { reference: u64, table_size: usize },
+
+    /// A string pair, e.g. a tag, has no zero byte dividing the two strings.
+    InvalidStringPair,
+
+    /// A relation member reference is not a member type followed by a role, contains the
+    /// reference.
+    InvalidMemberReference(String),
+
+    /// A section of an element, e.g. the references of a way, is larger than the bytes left of
+    /// the data set.
+    SectionExceedsDataset { size: u64, remaining: u64 },
+
+    /// A variable integer does not fit in 64 bits.
+    VarIntOverflow,
+
+    /// The data ended in the middle of a data set.
+    TruncatedDataset,
+
+    /// A timestamp could not be parsed or written, contains the timestamp.
+    InvalidTimestamp(String),
+}
+
+/// Where in the data an error occurred. Which fields are known depends on the format, e.g. xml
+/// errors have a line and column while o5m errors have a byte offset.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Number of bytes read before the error occurred.
+    pub byte_offset: Option<u64>,
+
+    /// Line of the error, starting at 1.
+    pub line: Option<u32>,
+
+    /// Column of the error, starting at 1.
+    pub column: Option<u32>,
+
+    /// The element that was being read, if its id is known.
+    pub element: Option<ElementId>,
 }
 
 impl Error {
@@ -35,6 +91,19 @@ impl Error {
         Error {
             repr: Simple(kind),
             message,
+            location: Box::default(),
+        }
+    }
+
+    /// Create an error caused by another error, which is returned by `source()`.
+    pub fn with_source<E>(kind: ErrorKind, message: Option<String>, source: E) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Error {
+            repr: Custom(kind, source.into()),
+            message,
+            location: Box::default(),
         }
     }
 
@@ -49,7 +118,34 @@ impl Error {
     /// Returns reference to error kind.
     pub fn kind(&self) -> &ErrorKind {
         match &self.repr {
-            Simple(e) => &e,
+            Simple(kind) | Custom(kind, _) => kind,
+        }
+    }
+
+    /// Where the error occurred, if known.
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    pub(crate) fn location_mut(&mut self) -> &mut Location {
+        &mut self.location
+    }
+
+    /// True if the error is an unexpected end of file from the underlying reader.
+    pub(crate) fn is_eof(&self) -> bool {
+        matches!(self.kind(), IO(e) if e.kind() == io::ErrorKind::UnexpectedEof)
+    }
+
+    /// False if the data can not be read any further after the error, e.g. an io error.
+    pub(crate) fn is_recoverable(&self) -> bool {
+        !matches!(self.kind(), IO(_) | ErrorKind::TruncatedDataset)
+    }
+
+    /// Turn an unexpected end of file into a truncated data set error, caused by the io error.
+    pub(crate) fn into_truncated(self) -> Self {
+        match self.repr {
+            Simple(IO(e)) => Error::with_source(ErrorKind::TruncatedDataset, self.message, e),
+            _ => self,
         }
     }
 }
@@ -68,6 +164,40 @@ impl Display for ErrorKind {
         match self {
             ErrorKind::InvalidFileFormat => write!(f, "File format not recognized.")?,
             ErrorKind::ParseError => write!(f, "Unknown parse error occurred.")?,
+            ErrorKind::InvalidGeometry => write!(f, "Invalid geometry.")?,
+            ErrorKind::MissingAttribute(attribute) => {
+                write!(f, "Required attribute '{}' missing.", attribute)?
+            }
+            ErrorKind::InvalidAttributeValue { attribute, value } => write!(
+                f,
+                "The '{}' attribute contains invalid data '{}'.",
+                attribute, value
+            )?,
+            ErrorKind::UnknownMemberType(t) => write!(f, "Invalid relation member type '{}'.", t)?,
+            ErrorKind::BadStringReference {
+                reference,
+                table_size,
+            } => write!(
+                f,
+                "String reference '{}' not found in table with size '{}'.",
+                reference, table_size
+            )?,
+            ErrorKind::InvalidStringPair => {
+                write!(f, "String pair is not divided by a zero byte.")?
+            }
+            ErrorKind::InvalidMemberReference(reference) => {
+                write!(f, "Invalid relation member reference '{}'.", reference)?
+            }
+            ErrorKind::SectionExceedsDataset { size, remaining } => write!(
+                f,
+                "Section size {} exceeds the remaining data set size {}.",
+                size, remaining
+            )?,
+            ErrorKind::VarIntOverflow => write!(f, "Varint overflow, read 9 bytes.")?,
+            ErrorKind::TruncatedDataset => write!(f, "Unexpected end of file.")?,
+            ErrorKind::InvalidTimestamp(timestamp) => {
+                write!(f, "Invalid timestamp '{}'.", timestamp)?
+            }
             IO(io_error) => match io_error.kind() {
                 io::ErrorKind::UnexpectedEof => write!(f, "Unexpected end of file.")?,
                 _ => write!(f, "IO error: {}", io_error)?,
@@ -81,14 +211,16 @@ impl Repr {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Simple(kind) => kind.source(),
+            Custom(_, source) => Some(source.as_ref()),
         }
     }
 }
 
 impl Display for Repr {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-        let Simple(kind) = self;
-        kind.fmt(f)
+        match self {
+            Simple(kind) | Custom(kind, _) => kind.fmt(f),
+        }
     }
 }
 
@@ -98,8 +230,15 @@ impl std::error::Error for Error {
     }
 }
 
+/// Errors are prefixed with the line if known, otherwise with the byte offset if known.
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        if let Some(line) = self.location.line {
+            write!(f, "Line {}: ", line)?;
+        } else if let Some(offset) = self.location.byte_offset {
+            write!(f, "Ending at byte {}: ", offset)?;
+        }
+
         match &self.message {
             Some(message) => write!(f, "{}", message),
             None => self.repr.fmt(f),
@@ -116,9 +255,51 @@ impl PartialEq for Error {
 
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
-        Error {
-            repr: Simple(IO(e)),
-            message: None,
-        }
+        Error::new(IO(e), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osm_io::error::{Error, ErrorKind};
+    use std::error::Error as StdError;
+    use std::io;
+
+    #[test]
+    fn error_source() {
+        let error: Error = io::Error::new(io::ErrorKind::NotFound, "missing").into();
+        assert_eq!(error.source().unwrap().to_string(), "missing");
+
+        let cause = "1.5".parse::<i64>().unwrap_err();
+        let kind = ErrorKind::InvalidAttributeValue {
+            attribute: "id".to_owned(),
+            value: "1.5".to_owned(),
+        };
+        let error = Error::with_source(kind, None, cause.clone());
+        assert_eq!(
+            error.to_string(),
+            "The 'id' attribute contains invalid data '1.5'."
+        );
+        assert_eq!(error.source().unwrap().to_string(), cause.to_string());
+
+        assert!(Error::new(ErrorKind::VarIntOverflow, None)
+            .source()
+            .is_none());
+    }
+
+    #[test]
+    fn error_location() {
+        let mut error = Error::new(ErrorKind::MissingAttribute("id".to_owned()), None);
+        error.location_mut().byte_offset = Some(12);
+        assert_eq!(
+            error.to_string(),
+            "Ending at byte 12: Required attribute 'id' missing."
+        );
+
+        error.location_mut().line = Some(3);
+        assert_eq!(
+            error.to_string(),
+            "Line 3: Required attribute 'id' missing."
+        );
     }
 }
@@ -0,0 +1,246 @@
+//! Applies `WriterOptions` to the meta data of elements before they are written.
+use crate::osm_io::{MetaLevel, WriterOptions};
+use crate::{AuthorInformation, Meta};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// The meta data to write for an element.
+#[derive(Debug, PartialEq)]
+pub(crate) struct OutputMeta<'a> {
+    pub version: Option<u32>,
+    pub author: Option<Cow<'a, AuthorInformation>>,
+}
+
+/// Filters and anonymizes meta data according to the writer options. Shared by all writers so
+/// they output the same meta data.
+#[derive(Debug)]
+pub(crate) struct MetaFilter {
+    options: WriterOptions,
+    uids: HashMap<u64, u64>,
+    change_sets: HashMap<u64, u64>,
+}
+
+impl MetaFilter {
+    pub fn new(options: WriterOptions) -> Self {
+        MetaFilter {
+            options,
+            uids: HashMap::new(),
+            change_sets: HashMap::new(),
+        }
+    }
+
+    /// The meta data to write for `meta`, or `None` if no meta data should be written at all.
+    pub fn apply<'a>(&mut self, meta: &'a Meta) -> Option<OutputMeta<'a>> {
+        let author = match self.options.meta {
+            MetaLevel::None => return None,
+            MetaLevel::Version => None,
+            MetaLevel::Full => meta.author.as_ref().map(|a| self.author(a)),
+        };
+
+        Some(OutputMeta {
+            version: meta.version,
+            author,
+        })
+    }
+
+    fn author<'a>(&mut self, author: &'a AuthorInformation) -> Cow<'a, AuthorInformation> {
+        if !self.options.anonymize && self.options.timestamp_precision.is_none() {
+            return Cow::Borrowed(author);
+        }
+
+        let mut author = author.clone();
+        if self.options.anonymize {
+            match self.options.pseudonym_salt {
+                Some(salt) => {
+                    author.uid = keyed_pseudonym(salt, author.uid);
+                    author.change_set = keyed_pseudonym(salt, author.change_set);
+                }
+                None => {
+                    author.uid = pseudonym(&mut self.uids, author.uid);
+                    author.change_set = pseudonym(&mut self.change_sets, author.change_set);
+                }
+            }
+            author.user = format!("user_{}", author.uid);
+        }
+        if let Some(precision) = self.options.timestamp_precision {
+            author.created = author.created.truncate(precision);
+        }
+        Cow::Owned(author)
+    }
+}
+
+/// The pseudonym of `id`, ids are numbered from 1 in order of first appearance.
+fn pseudonym(pseudonyms: &mut HashMap<u64, u64>, id: u64) -> u64 {
+    let next = pseudonyms.len() as u64 + 1;
+    *pseudonyms.entry(id).or_insert(next)
+}
+
+/// The pseudonym of `id` derived from a keyed hash, between 1 and 2^52. Small enough to be delta
+/// coded in o5m and represented exactly as a float in e.g. JSON.
+fn keyed_pseudonym(salt: u128, id: u64) -> u64 {
+    (siphash24(salt, id) >> 12) + 1
+}
+
+/// SipHash-2-4 of the little endian bytes of `value`, with `key` as the little endian key.
+/// Implemented here since the hashers in std may change between releases.
+fn siphash24(key: u128, value: u64) -> u64 {
+    let k0 = key as u64;
+    let k1 = (key >> 64) as u64;
+    let mut v = [
+        k0 ^ 0x736f_6d65_7073_6575,
+        k1 ^ 0x646f_7261_6e64_6f6d,
+        k0 ^ 0x6c79_6765_6e65_7261,
+        k1 ^ 0x7465_6462_7974_6573,
+    ];
+
+    // The value is one block, the last block only holds the message length.
+    for block in [value, 8 << 56] {
+        v[3] ^= block;
+        sip_round(&mut v);
+        sip_round(&mut v);
+        v[0] ^= block;
+    }
+
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        sip_round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+fn sip_round(v: &mut [u64; 4]) {
+    v[0] = v[0].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(13) ^ v[0];
+    v[0] = v[0].rotate_left(32);
+    v[2] = v[2].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(16) ^ v[2];
+    v[0] = v[0].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(21) ^ v[0];
+    v[2] = v[2].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(17) ^ v[2];
+    v[2] = v[2].rotate_left(32);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::osm_io::meta_filter::{siphash24, MetaFilter, OutputMeta};
+    use crate::osm_io::{MetaLevel, WriterOptions};
+    use crate::{AuthorInformation, Meta, Timestamp};
+    use chrono::Duration;
+    use std::borrow::Cow;
+
+    fn meta(uid: u64, change_set: u64) -> Meta {
+        Meta {
+            version: Some(2),
+            author: Some(AuthorInformation {
+                created: Timestamp(1577934245),
+                change_set,
+                uid,
+                user: format!("name{}", uid),
+            }),
+            ..Meta::default()
+        }
+    }
+
+    #[test]
+    fn meta_levels() {
+        let meta = meta(10, 20);
+        let mut filter = MetaFilter::new(WriterOptions::default());
+        let output = filter.apply(&meta).unwrap();
+        assert_eq!(output.author, meta.author.as_ref().map(Cow::Borrowed));
+
+        let mut filter = MetaFilter::new(WriterOptions {
+            meta: MetaLevel::Version,
+            ..WriterOptions::default()
+        });
+        let expected = OutputMeta {
+            version: Some(2),
+            author: None,
+        };
+        assert_eq!(filter.apply(&meta), Some(expected));
+
+        let mut filter = MetaFilter::new(WriterOptions {
+            meta: MetaLevel::None,
+            ..WriterOptions::default()
+        });
+        assert_eq!(filter.apply(&meta), None);
+    }
+
+    #[test]
+    fn anonymize() {
+        let mut filter = MetaFilter::new(WriterOptions {
+            anonymize: true,
+            ..WriterOptions::default()
+        });
+
+        let authors: Vec<_> = [meta(10, 20), meta(30, 40), meta(10, 50)]
+            .iter()
+            .map(|m| filter.apply(m).unwrap().author.unwrap().into_owned())
+            .map(|a| (a.uid, a.user, a.change_set))
+            .collect();
+
+        assert_eq!(
+            authors,
+            vec![
+                (1, "user_1".to_owned(), 1),
+                (2, "user_2".to_owned(), 2),
+                (1, "user_1".to_owned(), 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn anonymize_with_salt() {
+        let options = WriterOptions {
+            anonymize: true,
+            pseudonym_salt: Some(1234),
+            ..WriterOptions::default()
+        };
+        let pseudonyms = |metas: &[Meta]| -> Vec<_> {
+            let mut filter = MetaFilter::new(options.clone());
+            metas
+                .iter()
+                .map(|m| filter.apply(m).unwrap().author.unwrap().into_owned())
+                .map(|a| (a.uid, a.user, a.change_set))
+                .collect()
+        };
+
+        // Same pseudonyms in different writers, regardless of order.
+        let first = pseudonyms(&[meta(10, 20), meta(30, 40)]);
+        let second = pseudonyms(&[meta(30, 40), meta(10, 20)]);
+        assert_eq!(first[0], second[1]);
+        assert_eq!(first[1], second[0]);
+        assert_ne!(first[0].0, first[1].0);
+        assert_eq!(first[0].1, format!("user_{}", first[0].0));
+        assert!(first.iter().all(|(uid, _, _)| (1..=1 << 52).contains(uid)));
+
+        let mut filter = MetaFilter::new(WriterOptions {
+            pseudonym_salt: Some(4321),
+            ..options.clone()
+        });
+        let meta = meta(10, 20);
+        let author = filter.apply(&meta).unwrap().author.unwrap();
+        assert_ne!(author.uid, first[0].0);
+    }
+
+    #[test]
+    fn siphash_reference_vector() {
+        // Key 00..0f and message 00..07 from the SipHash paper.
+        let key = u128::from_le_bytes(std::array::from_fn(|i| i as u8));
+        let value = u64::from_le_bytes(std::array::from_fn(|i| i as u8));
+        assert_eq!(siphash24(key, value), 0x93f5_f579_9a93_2462);
+    }
+
+    #[test]
+    fn round_timestamps() {
+        let mut filter = MetaFilter::new(WriterOptions {
+            timestamp_precision: Some(Duration::hours(1)),
+            ..WriterOptions::default()
+        });
+
+        let meta = meta(10, 20);
+        let author = filter.apply(&meta).unwrap().author.unwrap();
+        assert_eq!(author.created.to_string(), "2020-01-02T03:00:00Z");
+        assert_eq!(author.user, "name10");
+    }
+}
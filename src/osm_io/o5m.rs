@@ -8,8 +8,9 @@ mod writer;
 use crate::osm_io::error::{Error, ErrorKind, Result};
 use crate::osm_io::o5m::varint::VarInt;
 pub use reader::*;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
+use std::sync::Arc;
 pub use writer::*;
 
 const MAX_STRING_TABLE_SIZE: usize = 15_000;
@@ -23,12 +24,24 @@ const O5M_NODE: u8 = 0x10;
 const O5M_WAY: u8 = 0x11;
 const O5M_RELATION: u8 = 0x12;
 const O5M_BOUNDING_BOX: u8 = 0xDB;
+const O5M_TIMESTAMP: u8 = 0xDC;
 
 /// String reference table is used for decoding and encoding strings as references.
 /// See: https://wiki.openstreetmap.org/wiki/O5m#Strings
+///
+/// Every string gets an insertion counter when pushed. The reference of a string is the number of
+/// strings pushed after it plus one, so strings are looked up by a hash map instead of searching
+/// the table.
 #[derive(Debug)]
 struct StringReferenceTable {
-    table: VecDeque<Vec<u8>>,
+    /// The strings, newest first.
+    table: VecDeque<Arc<[u8]>>,
+
+    /// Insertion counter of the newest occurrence of each string in the table.
+    counters: HashMap<Arc<[u8]>, u64>,
+
+    /// Number of strings pushed since the table was cleared.
+    pushed: u64,
 }
 
 /// Represents a delta value, i.e. a value that is relative to it's last value.
@@ -66,27 +79,28 @@ struct DeltaState {
 impl StringReferenceTable {
     pub fn new() -> Self {
         StringReferenceTable {
-            table: VecDeque::with_capacity(15000),
+            table: VecDeque::with_capacity(MAX_STRING_TABLE_SIZE),
+            counters: HashMap::with_capacity(MAX_STRING_TABLE_SIZE),
+            pushed: 0,
         }
     }
 
     pub fn clear(&mut self) {
         self.table.clear();
+        self.counters.clear();
+        self.pushed = 0;
     }
 
     /// Get string from table. idx starts at 1.
-    pub fn get(&mut self, idx: u64) -> Result<&Vec<u8>> {
+    pub fn get(&mut self, idx: u64) -> Result<&[u8]> {
         if let Some(value) = self.table.get((idx - 1) as usize) {
             Ok(value)
         } else {
-            Err(Error::new(
-                ErrorKind::ParseError,
-                Some(format!(
-                    "String reference '{}' not found in table with size '{}'.",
-                    idx,
-                    self.table.len()
-                )),
-            ))
+            let kind = ErrorKind::BadStringReference {
+                reference: idx,
+                table_size: self.table.len(),
+            };
+            Err(Error::new(kind, None))
         }
     }
 
@@ -97,8 +111,8 @@ impl StringReferenceTable {
             return bytes;
         }
 
-        if let Some(pos) = self.table.iter().position(|b| b == &bytes) {
-            VarInt::create_bytes((pos + 1) as u64)
+        if let Some(counter) = self.counters.get(&bytes[..]) {
+            VarInt::create_bytes(self.pushed - counter)
         } else {
             self.push(&bytes);
             bytes
@@ -111,12 +125,21 @@ impl StringReferenceTable {
             return;
         }
 
-        // Pop the oldest one off if we are at the limit.
+        // Pop the oldest one off if we are at the limit. Its counter is only removed if there is no
+        // newer occurrence of the same string.
         if self.table.len() == MAX_STRING_TABLE_SIZE {
-            self.table.pop_back();
+            if let Some(oldest) = self.table.pop_back() {
+                let oldest_counter = self.pushed - MAX_STRING_TABLE_SIZE as u64;
+                if self.counters.get(&oldest) == Some(&oldest_counter) {
+                    self.counters.remove(&oldest);
+                }
+            }
         }
 
-        self.table.push_front(bytes.to_vec());
+        let bytes: Arc<[u8]> = Arc::from(bytes);
+        self.counters.insert(Arc::clone(&bytes), self.pushed);
+        self.table.push_front(bytes);
+        self.pushed += 1;
     }
 }
 
@@ -181,7 +204,9 @@ impl DeltaState {
 
 #[cfg(test)]
 mod test {
-    use crate::osm_io::o5m::StringReferenceTable;
+    use crate::osm_io::o5m::varint::VarInt;
+    use crate::osm_io::o5m::{StringReferenceTable, MAX_STRING_TABLE_SIZE};
+    use std::collections::VecDeque;
 
     #[test]
     fn string_references() {
@@ -198,4 +223,32 @@ mod test {
 
         assert_eq!(table.reference(vec![0x01, 0x01]), vec![0x01, 0x01]); // New
     }
+
+    /// The table must give the same references as a linear search through the strings.
+    #[test]
+    fn string_references_with_evictions() {
+        let mut table = StringReferenceTable::new();
+        let mut expected_table: VecDeque<Vec<u8>> = VecDeque::new();
+
+        // Pseudo random strings from a set twice the size of the table, so there are both
+        // references and evictions.
+        let mut random: u64 = 1;
+        for _ in 0..(MAX_STRING_TABLE_SIZE * 2) {
+            random = random.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let value = (random >> 33) % (MAX_STRING_TABLE_SIZE as u64 * 2);
+            let bytes = format!("string{}", value).into_bytes();
+
+            let expected = match expected_table.iter().position(|b| b == &bytes) {
+                Some(pos) => VarInt::create_bytes((pos + 1) as u64),
+                None => {
+                    if expected_table.len() == MAX_STRING_TABLE_SIZE {
+                        expected_table.pop_back();
+                    }
+                    expected_table.push_front(bytes.clone());
+                    bytes.clone()
+                }
+            };
+            assert_eq!(table.reference(bytes), expected);
+        }
+    }
 }
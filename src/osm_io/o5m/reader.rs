@@ -5,13 +5,19 @@ use crate::geo::{Boundary, Coordinate};
 use crate::osm_io::error::Result;
 use crate::osm_io::error::{Error, ErrorKind};
 use crate::osm_io::o5m::Delta::*;
-use crate::osm_io::OsmRead;
-use crate::{AuthorInformation, Meta, Node, Osm, Relation, RelationMember, Tag, Way};
+use crate::osm_io::{OsmRead, ParseMode, ReaderOptions};
+use crate::{
+    AuthorInformation, ElementId, Meta, Node, NodeId, Osm, Relation, RelationId, RelationMember,
+    Tag, Timestamp, Way, WayId,
+};
 use std::io::{BufRead, Read, Take};
 
 /// A reader for the o5m format.
 pub struct O5mReader<R: BufRead> {
     decoder: O5mDecoder<R>,
+
+    /// The element currently being read, used to locate errors.
+    element: Option<ElementId>,
 }
 
 /// Low level decoding from binary to data types.
@@ -22,12 +28,22 @@ struct O5mDecoder<R: BufRead> {
     delta: DeltaState,
     limit: u64,
     position: u64,
+
+    /// Position where the current data set ends, if its length has been read.
+    dataset_end: Option<u64>,
+    mode: ParseMode,
+    warnings: Vec<Error>,
 }
 
 impl<R: BufRead> O5mReader<R> {
     pub fn new(inner: R) -> Self {
+        Self::with_options(inner, ReaderOptions::default())
+    }
+
+    pub fn with_options(inner: R, options: ReaderOptions) -> Self {
         O5mReader {
-            decoder: O5mDecoder::new(inner),
+            decoder: O5mDecoder::new(inner, options.mode),
+            element: None,
         }
     }
 
@@ -36,8 +52,31 @@ impl<R: BufRead> O5mReader<R> {
         self.decoder.position()
     }
 
-    /// Parse next data set, returns false when there is no more data.
+    /// Parse next data set, returns false when there is no more data. In lenient mode an invalid
+    /// data set is skipped.
     fn parse_next(&mut self, osm: &mut Osm) -> Result<bool> {
+        self.element = None;
+        self.decoder.dataset_end = None;
+        let warnings = self.decoder.warnings.len();
+
+        let result = match self.parse_dataset(osm) {
+            Err(error) if self.decoder.dataset_end.is_some() => {
+                self.decoder.check::<bool>(Err(error)).and_then(|_| {
+                    self.decoder.skip_to_dataset_end()?;
+                    Ok(true)
+                })
+            }
+            result => result,
+        };
+
+        for warning in &mut self.decoder.warnings[warnings..] {
+            warning.location_mut().element = self.element;
+        }
+        result
+    }
+
+    /// Parse a data set, returns false when there is no more data.
+    fn parse_dataset(&mut self, osm: &mut Osm) -> Result<bool> {
         match self.read_set_type()? {
             O5M_NODE => {
                 let node = self.read_node()?;
@@ -46,6 +85,7 @@ impl<R: BufRead> O5mReader<R> {
             O5M_WAY => osm.add_way(self.read_way()?),
             O5M_RELATION => osm.add_relation(self.read_relation()?),
             O5M_BOUNDING_BOX => osm.boundary = Some(self.read_boundary()?),
+            O5M_TIMESTAMP => osm.header.timestamp = Some(self.read_file_timestamp()?),
             O5M_RESET => self.decoder.reset(),
             O5M_EOF => return Ok(false),
             set_type => self.skip_dataset(set_type)?,
@@ -56,7 +96,7 @@ impl<R: BufRead> O5mReader<R> {
     /// See: https://wiki.openstreetmap.org/wiki/O5m#File
     fn read_set_type(&mut self) -> Result<u8> {
         self.decoder.set_limit(1);
-        Ok(self.decoder.read_u8()?)
+        self.decoder.read_u8()
     }
 
     /// Skip a whole data set. Used when data set is unknown.
@@ -88,14 +128,34 @@ impl<R: BufRead> O5mReader<R> {
         })
     }
 
+    /// See: https://wiki.openstreetmap.org/wiki/O5m#File_Timestamp
+    fn read_file_timestamp(&mut self) -> Result<Timestamp> {
+        self.decoder.read_limit()?;
+        let timestamp = self.decoder.read_varint()?;
+        self.decoder.skip_all()?;
+        Ok(Timestamp(timestamp))
+    }
+
     /// See: https://wiki.openstreetmap.org/wiki/O5m#Node
     fn read_node(&mut self) -> Result<Node> {
         self.decoder.read_limit()?;
-        let mut node = Node::default();
-        node.id = self.decoder.read_delta(Id)?;
-        node.meta = self.read_meta()?;
+        let id = self.decoder.read_delta(Id)?;
+        self.element = Some(NodeId(id).into());
+
+        let meta = self.read_meta()?;
+        if !meta.visible {
+            return Ok(Node {
+                id,
+                meta,
+                ..Node::default()
+            });
+        }
 
-        node.coordinate = self.decoder.read_delta_coordinate()?;
+        let mut node = Node {
+            id,
+            meta,
+            coordinate: self.decoder.read_delta_coordinate()?,
+        };
         node.meta.tags = self.decoder.read_tags()?;
 
         Ok(node)
@@ -105,9 +165,17 @@ impl<R: BufRead> O5mReader<R> {
     fn read_way(&mut self) -> Result<Way> {
         self.decoder.read_limit()?;
 
-        let mut way = Way::default();
-        way.id = self.decoder.read_delta(Id)?;
-        way.meta = self.read_meta()?;
+        let id = self.decoder.read_delta(Id)?;
+        self.element = Some(WayId(id).into());
+
+        let mut way = Way {
+            id,
+            meta: self.read_meta()?,
+            ..Way::default()
+        };
+        if !way.meta.visible {
+            return Ok(way);
+        }
 
         let ref_size = self.decoder.read_uvarint()?;
         way.refs = self.decoder.read_way_references(ref_size)?;
@@ -120,9 +188,17 @@ impl<R: BufRead> O5mReader<R> {
     fn read_relation(&mut self) -> Result<Relation> {
         self.decoder.read_limit()?;
 
-        let mut relation = Relation::default();
-        relation.id = self.decoder.read_delta(Id)?;
-        relation.meta = self.read_meta()?;
+        let id = self.decoder.read_delta(Id)?;
+        self.element = Some(RelationId(id).into());
+
+        let mut relation = Relation {
+            id,
+            meta: self.read_meta()?,
+            ..Relation::default()
+        };
+        if !relation.meta.visible {
+            return Ok(relation);
+        }
 
         let ref_size = self.decoder.read_uvarint()?;
         relation.members = self.decoder.read_relation_members(ref_size)?;
@@ -131,7 +207,8 @@ impl<R: BufRead> O5mReader<R> {
         Ok(relation)
     }
 
-    /// Meta is common data part of every element.
+    /// Meta is common data part of every element. Deleted elements have no data after the meta
+    /// data.
     fn read_meta(&mut self) -> Result<Meta> {
         let mut meta = Meta::default();
         let version = self.decoder.read_uvarint()? as u32;
@@ -139,21 +216,27 @@ impl<R: BufRead> O5mReader<R> {
 
         // If version is 0 there is no timestamp or author.
         if meta.version.is_some() {
-            meta.author = self.decoder.read_author_info()?;
+            let author = self.decoder.read_author_info();
+            meta.author = self.decoder.check(author)?.flatten();
         }
 
+        meta.visible = !self.decoder.is_at_limit();
+
         Ok(meta)
     }
 }
 
 impl<R: BufRead> O5mDecoder<R> {
-    fn new(inner: R) -> Self {
+    fn new(inner: R, mode: ParseMode) -> Self {
         O5mDecoder {
             inner: inner.take(0),
             string_table: StringReferenceTable::new(),
             delta: DeltaState::new(),
             limit: 0,
             position: 0,
+            dataset_end: None,
+            mode,
+            warnings: Vec::new(),
         }
     }
 
@@ -183,9 +266,30 @@ impl<R: BufRead> O5mDecoder<R> {
         self.set_limit(9);
         let len = self.read_uvarint()?;
         self.set_limit(len);
+        self.dataset_end = Some(self.position() + len);
         Ok(())
     }
 
+    /// Skip the rest of the current data set.
+    fn skip_to_dataset_end(&mut self) -> Result<()> {
+        if let Some(end) = self.dataset_end {
+            let position = self.position();
+            self.set_limit(end.saturating_sub(position));
+            self.skip_all()?;
+        }
+        Ok(())
+    }
+
+    /// True if all data within the current limit has been read.
+    fn is_at_limit(&self) -> bool {
+        self.inner.limit() == 0
+    }
+
+    /// True if the underlying reader ended before the current limit was reached.
+    fn is_truncated(&self) -> bool {
+        self.inner.limit() > 0
+    }
+
     /// Skip until limit or end of file is reached.
     fn skip_all(&mut self) -> Result<()> {
         let _ = self.read_until_eof(|r| {
@@ -231,7 +335,7 @@ impl<R: BufRead> O5mDecoder<R> {
         let change_set = self.read_delta(ChangeSet)? as u64;
         let (uid, user) = self.read_user()?;
         Ok(Some(AuthorInformation {
-            created,
+            created: Timestamp(created),
             change_set,
             uid,
             user,
@@ -244,57 +348,67 @@ impl<R: BufRead> O5mDecoder<R> {
         let reference = self.read_uvarint()?;
         if reference != 0 {
             let bytes = self.string_table.get(reference)?;
-            Ok(Self::bytes_to_user(bytes))
+            Self::bytes_to_user(bytes)
         } else {
             let bytes = self.read_string_bytes(2)?;
-            Ok(Self::bytes_to_user(&bytes))
+            Self::bytes_to_user(&bytes)
         }
     }
 
     /// Turns bytes into uid and username.
-    fn bytes_to_user(bytes: &[u8]) -> (u64, String) {
-        let (uid_bytes, user_bytes) = Self::split_string_bytes(&bytes);
+    fn bytes_to_user(bytes: &[u8]) -> Result<(u64, String)> {
+        let (uid_bytes, user_bytes) = Self::split_string_bytes(bytes)?;
         let uid: u64 = VarInt::new(Vec::from(uid_bytes)).into();
-        let user = String::from_utf8_lossy(&user_bytes).into_owned();
-        (uid, user)
+        let user = String::from_utf8_lossy(user_bytes).into_owned();
+        Ok((uid, user))
     }
 
     /// Read tags. There is no size or delimiter for tags, so they are read until there is no more
     /// data to read in the current limit.
     fn read_tags(&mut self) -> Result<Vec<Tag>> {
-        let pairs = self.read_until_eof(|r| Ok(r.read_string_pair()?))?;
+        let pairs = self.read_until_eof(|r| r.read_string_pair())?;
         let tags = pairs.into_iter().map(|s| s.into()).collect();
         Ok(tags)
     }
 
     /// Reads way references until `size` is consumed.
     fn read_way_references(&mut self, size: u64) -> Result<Vec<i64>> {
-        let limit = self.inner.limit();
-        self.set_limit(size);
-        let refs = self.read_until_eof(|r| Ok(r.read_delta(WayRef)?))?;
+        let limit = self.nested_limit(size)?;
+        let refs = self.read_until_eof(|r| r.read_delta(WayRef))?;
         self.set_limit(limit - size);
         Ok(refs)
     }
 
     /// Reads relation members until `size` is consumed.
     fn read_relation_members(&mut self, size: u64) -> Result<Vec<RelationMember>> {
-        let limit = self.inner.limit();
-        self.set_limit(size);
-        let members = self.read_until_eof(|r| Ok(r.read_relation_member()?))?;
+        let limit = self.nested_limit(size)?;
+        let members = self.read_until_eof(|r| r.read_relation_member())?;
         self.set_limit(limit - size);
         Ok(members)
     }
 
+    /// Limit reading to the next `size` bytes, returns the previous limit. The size must be within
+    /// the current limit.
+    fn nested_limit(&mut self, size: u64) -> Result<u64> {
+        let limit = self.inner.limit();
+        if size > limit {
+            let kind = ErrorKind::SectionExceedsDataset {
+                size,
+                remaining: limit,
+            };
+            return Err(Error::new(kind, None));
+        }
+        self.set_limit(size);
+        Ok(limit)
+    }
+
     /// Read a single relation member.
     fn read_relation_member(&mut self) -> Result<RelationMember> {
         let id = self.read_varint()?;
         let s = self.read_string()?;
 
         if !s.is_char_boundary(1) || s.len() < 2 {
-            return Err(Error::new(
-                ErrorKind::ParseError,
-                Some("Corrupt relation member reference data.".to_owned()),
-            ));
+            return Err(Error::new(ErrorKind::InvalidMemberReference(s), None));
         }
 
         let (mem_type, mem_role) = s.split_at(1);
@@ -312,10 +426,7 @@ impl<R: BufRead> O5mDecoder<R> {
                 self.delta.decode(RelRelRef, id),
                 mem_role.to_owned(),
             )),
-            s => Err(Error::new(
-                ErrorKind::ParseError,
-                Some(format!("Invalid relation member type '{}'.", s)),
-            )),
+            s => Err(Error::new(ErrorKind::UnknownMemberType(s.to_owned()), None)),
         }
     }
 
@@ -325,10 +436,10 @@ impl<R: BufRead> O5mDecoder<R> {
         let reference: u64 = self.inner.read_varint()?.into();
         if reference != 0 {
             let bytes = self.string_table.get(reference)?;
-            Ok(Self::bytes_to_string_pair(bytes))
+            Self::bytes_to_string_pair(bytes)
         } else {
             let bytes = self.read_string_bytes(2)?;
-            Ok(Self::bytes_to_string_pair(&bytes))
+            Self::bytes_to_string_pair(&bytes)
         }
     }
 
@@ -340,25 +451,28 @@ impl<R: BufRead> O5mDecoder<R> {
             String::from_utf8_lossy(&value).into_owned()
         } else {
             let value = self.string_table.get(reference)?;
-            String::from_utf8_lossy(&value).into_owned()
+            String::from_utf8_lossy(value).into_owned()
         };
 
         Ok(value)
     }
 
     /// Turns bytes into two strings by splitting on first zero bytes and utf8 encode them.
-    fn bytes_to_string_pair(bytes: &[u8]) -> (String, String) {
-        let (key_bytes, value_bytes) = Self::split_string_bytes(bytes);
+    fn bytes_to_string_pair(bytes: &[u8]) -> Result<(String, String)> {
+        let (key_bytes, value_bytes) = Self::split_string_bytes(bytes)?;
         let key = String::from_utf8_lossy(key_bytes).into_owned();
         let value = String::from_utf8_lossy(value_bytes).into_owned();
-        (key, value)
+        Ok((key, value))
     }
 
-    /// Splits bytes at the first zero byte.
-    /// Panics if 0-byte is not found.
-    fn split_string_bytes(bytes: &[u8]) -> (&[u8], &[u8]) {
-        let div = bytes.iter().position(|b| b == &0u8).unwrap();
-        (&bytes[0..div], &bytes[(div + 1)..])
+    /// Splits bytes at the first zero byte. Fails if there is no zero byte, e.g. if a string
+    /// reference points at a single string.
+    fn split_string_bytes(bytes: &[u8]) -> Result<(&[u8], &[u8])> {
+        let div = bytes
+            .iter()
+            .position(|b| b == &0u8)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidStringPair, None))?;
+        Ok((&bytes[0..div], &bytes[(div + 1)..]))
     }
 
     /// Reads string bytes from stream. A string can consist of 1 or more parts. Each part is
@@ -388,57 +502,72 @@ impl<R: BufRead> O5mDecoder<R> {
         Ok(self.delta.decode(delta, val))
     }
 
-    /// Calls callback until end of file is reached.
+    /// Calls callback until the limit is reached.
     /// This function assumes that the callback is consuming data on the provided reader (self),
     /// otherwise this will loop in infinity.
     fn read_until_eof<T>(&mut self, f: fn(&mut Self) -> Result<T>) -> Result<Vec<T>> {
         let mut vec = Vec::new();
         loop {
             match f(self) {
-                Ok(r) => vec.push(r),
-                Err(e) => {
-                    if let ErrorKind::IO(err) = e.kind() {
-                        if err.kind() == std::io::ErrorKind::UnexpectedEof {
-                            break;
-                        }
-                    }
-                    return Err(e);
-                }
+                Err(e) if e.is_eof() && !self.is_truncated() => break,
+                result => vec.extend(self.check(result)?),
             }
         }
         Ok(vec)
     }
+
+    /// Returns the error in strict mode. In lenient mode a recoverable error is recorded as a
+    /// warning and `None` is returned.
+    fn check<T>(&mut self, result: Result<T>) -> Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(mut error) if self.mode == ParseMode::Lenient && error.is_recoverable() => {
+                error.location_mut().byte_offset = Some(self.position());
+                self.warnings.push(error);
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
 }
 
 impl<R: BufRead> OsmRead for O5mReader<R> {
-    fn read(&mut self) -> std::result::Result<Osm, Error> {
+    fn read_with_warnings(&mut self) -> std::result::Result<(Osm, Vec<Error>), Error> {
         let mut osm = Osm::default();
 
         loop {
             match self.parse_next(&mut osm) {
                 Ok(true) => {}
                 Ok(false) => break,
-                Err(mut error) => {
-                    if let Some(message) = error.message() {
-                        let message = format!("Ending at byte {}: {}", self.position(), message);
-                        error.set_message(message);
-                    }
-
+                Err(error) => {
+                    let mut error = if error.is_eof() && self.decoder.is_truncated() {
+                        error.into_truncated()
+                    } else {
+                        error
+                    };
+
+                    let location = error.location_mut();
+                    location.byte_offset = Some(self.position());
+                    location.element = self.element;
                     return Err(error);
                 }
             }
         }
 
-        Ok(osm)
+        Ok((osm, std::mem::take(&mut self.decoder.warnings)))
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::geo::Coordinate;
+    use crate::osm_io::error::ErrorKind;
     use crate::osm_io::o5m::O5mReader;
-    use crate::osm_io::OsmRead;
-    use crate::{AuthorInformation, Meta, Node, Relation, RelationMember, Way};
+    use crate::osm_io::{OsmRead, ParseMode, ReaderOptions};
+    use crate::{
+        AuthorInformation, ElementId, Meta, Node, Relation, RelationId, RelationMember, Timestamp,
+        Way, WayId,
+    };
     use std::io::BufReader;
 
     #[test]
@@ -469,12 +598,13 @@ mod test {
                     tags: vec![],
                     version: Some(5),
                     author: Some(AuthorInformation {
-                        created: 1285874610,
+                        created: Timestamp(1285874610),
                         change_set: 5922698,
                         uid: 45445,
                         user: "UScha".to_string()
                     }),
-                    ..Meta::default()
+                    visible: true,
+                    action: None,
                 }
             }
         );
@@ -565,9 +695,13 @@ mod test {
 
         let mut reader = O5mReader::new(BufReader::new(data.as_slice()));
         let error = reader.read().unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::InvalidMemberReference(reference) if reference == "\u{FFFD}"
+        ));
         assert_eq!(
             error.to_string(),
-            "Ending at byte 13: Corrupt relation member reference data."
+            "Ending at byte 13: Invalid relation member reference '\u{FFFD}'."
         );
     }
 
@@ -640,6 +774,155 @@ mod test {
 
         let mut reader = O5mReader::new(BufReader::new(data.as_slice()));
         let error = reader.read().unwrap_err();
-        assert_eq!(error.to_string(), "Unexpected end of file.");
+        assert!(matches!(error.kind(), ErrorKind::TruncatedDataset));
+        assert_eq!(
+            error.to_string(),
+            "Ending at byte 1: Unexpected end of file."
+        );
+    }
+
+    #[test]
+    fn error_location() {
+        let data: Vec<u8> = vec![
+            0x12, // relation
+            0x28, // length of following data of this node: 40 bytes
+            0x90, 0x2e, // id: 0+2952=2952
+            0x00, // no version and no author information
+            0x11, // length of references section: 17 bytes
+            0xf4, 0x98, 0x83, 0x0b, // id: 0+11560506=11560506
+            0x03, // Invalid string reference
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let mut reader = O5mReader::new(BufReader::new(data.as_slice()));
+        let error = reader.read().unwrap_err();
+        match error.kind() {
+            ErrorKind::BadStringReference {
+                reference,
+                table_size,
+            } => assert_eq!((*reference, *table_size), (3, 0)),
+            e => panic!("Unexpected kind {:?}", e),
+        }
+        let location = error.location();
+        assert_eq!(location.byte_offset, Some(11));
+        assert_eq!(location.line, None);
+        assert_eq!(
+            location.element,
+            Some(ElementId::Relation(RelationId(2952)))
+        );
+    }
+
+    #[test]
+    fn truncated_tags() {
+        let data: Vec<u8> = vec![
+            0x11, // way
+            0x0a, // length of following data: 10 bytes
+            0x02, // id: 1
+            0x00, // no version and no author information
+            0x00, // no references
+            0x00, 0x61, // Tag cut off.
+        ];
+
+        let mut reader = O5mReader::new(BufReader::new(data.as_slice()));
+        let error = reader.read().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::TruncatedDataset));
+        assert_eq!(error.location().element, Some(ElementId::Way(WayId(1))));
+    }
+
+    #[test]
+    fn string_pair_reference_to_single_string() {
+        let data: Vec<u8> = vec![
+            0x12, // relation
+            0x0e, // length of following data: 14 bytes
+            0x90, 0x2e, // id: 0+2952=2952
+            0x00, // no version and no author information
+            0x09, // length of references section: 9 bytes
+            0x02, 0x00, 0x31, 0x6f, 0x75, 0x74, 0x65, 0x72, 0x00, // way: 0+1=1, role: "outer"
+            0x01, // tag referencing the member type and role, which is a single string
+            0xfe, // end of file
+        ];
+
+        let mut reader = O5mReader::new(BufReader::new(data.as_slice()));
+        let error = reader.read().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidStringPair));
+
+        let options = ReaderOptions {
+            mode: ParseMode::Lenient,
+        };
+        let mut reader = O5mReader::with_options(BufReader::new(data.as_slice()), options);
+        let (osm, warnings) = reader.read_with_warnings().unwrap();
+        assert_eq!(
+            osm.relations[0].members,
+            vec![RelationMember::Way(1, "outer".to_owned())]
+        );
+        assert!(osm.relations[0].meta.tags.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(warnings[0].kind(), ErrorKind::InvalidStringPair));
+    }
+
+    #[test]
+    fn read_lenient() {
+        let data: Vec<u8> = vec![
+            0x12, // relation
+            0x14, // length of following data: 20 bytes
+            0x90, 0x2e, // id: 0+2952=2952
+            0x00, // no version and no author information
+            0x0b, // length of references section: 11 bytes
+            0x02, 0x03, // Member with invalid string reference
+            0x02, 0x00, 0x31, 0x6f, 0x75, 0x74, 0x65, 0x72, 0x00, // way: 0+1=1, role: "outer"
+            0x00, 0x61, 0x00, 0x62, 0x00, // tag: a=b
+            0xff, // reset
+            0x11, // way
+            0x04, // length of following data: 4 bytes
+            0x04, // id: 0+2=2
+            0x00, // no version and no author information
+            0x7f, // References exceeds the data set
+            0x00, 0x10, // node
+            0x04, // length of following data: 4 bytes
+            0x06, // id: 2+3=5
+            0x00, // no version and no author information
+            0x00, 0x00, // lon, lat
+            0xfe, // end of file
+        ];
+
+        let options = ReaderOptions {
+            mode: ParseMode::Lenient,
+        };
+        let mut reader = O5mReader::with_options(BufReader::new(data.as_slice()), options);
+        let (osm, warnings) = reader.read_with_warnings().unwrap();
+
+        assert_eq!(
+            osm.relations[0].members,
+            vec![RelationMember::Way(1, "outer".to_owned())]
+        );
+        assert_eq!(osm.relations[0].meta.tags, vec![("a", "b").into()]);
+        assert!(osm.ways.is_empty());
+        assert_eq!(osm.nodes[0].id, 5);
+
+        assert_eq!(warnings.len(), 2);
+        assert!(matches!(
+            warnings[0].kind(),
+            ErrorKind::BadStringReference { .. }
+        ));
+        assert_eq!(warnings[0].location().byte_offset, Some(8));
+        assert_eq!(
+            warnings[0].location().element,
+            Some(ElementId::Relation(RelationId(2952)))
+        );
+        assert!(matches!(
+            warnings[1].kind(),
+            ErrorKind::SectionExceedsDataset {
+                size: 127,
+                remaining: 1
+            }
+        ));
+        assert_eq!(
+            warnings[1].to_string(),
+            "Ending at byte 28: Section size 127 exceeds the remaining data set size 1."
+        );
+        assert_eq!(
+            warnings[1].location().element,
+            Some(ElementId::Way(WayId(2)))
+        );
     }
 }
@@ -53,10 +53,7 @@ pub trait ReadVarInt: Read {
         for i in 0..10 {
             // If we get to byte 9 we have more bits than 64.
             if i == 9 {
-                return Err(Error::new(
-                    ErrorKind::ParseError,
-                    Some("Varint overflow, read 9 bytes.".to_owned()),
-                ));
+                return Err(Error::new(ErrorKind::VarIntOverflow, None));
             }
 
             let mut buf = [0u8; 1];
@@ -171,7 +168,7 @@ impl From<i64> for VarInt {
             bytes.push(least_significant | 0x80);
 
             // Only first byte is special, rest is same as uvarint.
-            let mut rest = Self::from((value >> 6) as u64);
+            let mut rest = Self::from(value >> 6);
             bytes.append(&mut rest.bytes);
         } else {
             bytes.push(least_significant);
@@ -244,49 +241,49 @@ mod test_to_bytes {
 
     #[test]
     fn one_byte_uvarint() {
-        let varint = VarInt::from(5 as u64);
+        let varint = VarInt::from(5_u64);
         assert_eq!(varint.bytes, vec![0x05]);
     }
 
     #[test]
     fn max_one_byte_uvarint() {
-        let varint = VarInt::from(127 as u64);
+        let varint = VarInt::from(127_u64);
         assert_eq!(varint.bytes, vec![0x7F]);
     }
 
     #[test]
     fn two_byte_uvarint() {
-        let varint = VarInt::from(323 as u64);
+        let varint = VarInt::from(323_u64);
         assert_eq!(varint.bytes, vec![0xC3, 0x02]);
     }
 
     #[test]
     fn three_byte_uvarint() {
-        let varint = VarInt::from(16384 as u64);
+        let varint = VarInt::from(16384_u64);
         assert_eq!(varint.bytes, vec![0x80, 0x80, 0x01]);
     }
 
     #[test]
     fn one_byte_positive_varint() {
-        let varint = VarInt::from(4 as i64);
+        let varint = VarInt::from(4_i64);
         assert_eq!(varint.bytes, vec![0x08]);
     }
 
     #[test]
     fn one_byte_negative_varint() {
-        let varint = VarInt::from(-3 as i64);
+        let varint = VarInt::from(-3_i64);
         assert_eq!(varint.bytes, vec![0x05]);
     }
 
     #[test]
     fn two_byte_positive_varint() {
-        let varint = VarInt::from(64 as i64);
+        let varint = VarInt::from(64_i64);
         assert_eq!(varint.bytes, vec![0x80, 0x01]);
     }
 
     #[test]
     fn two_byte_negative_varint() {
-        let varint = VarInt::from(-65 as i64);
+        let varint = VarInt::from(-65_i64);
         assert_eq!(varint.bytes, vec![0x81, 0x01]);
     }
 }
@@ -4,12 +4,13 @@ use std::io::Write;
 use super::*;
 use crate::geo::{Boundary, Coordinate};
 use crate::osm_io::error::Error;
+use crate::osm_io::meta_filter::MetaFilter;
 use crate::osm_io::o5m::varint::WriteVarInt;
 use crate::osm_io::o5m::Delta::{
     ChangeSet, Id, Lat, Lon, RelNodeRef, RelRelRef, RelWayRef, Time, WayRef,
 };
-use crate::osm_io::OsmWrite;
-use crate::{Meta, Node, Osm, Relation, RelationMember, Tag, Way};
+use crate::osm_io::{OsmWrite, WriterOptions};
+use crate::{Meta, Node, Osm, Relation, RelationMember, Tag, Timestamp, Way};
 
 /// A writer for the o5m binary format.
 #[derive(Debug)]
@@ -24,13 +25,18 @@ pub struct O5mWriter<W> {
 struct O5mEncoder {
     string_table: StringReferenceTable,
     delta: DeltaState,
+    meta_filter: MetaFilter,
 }
 
 impl<W: Write> O5mWriter<W> {
     pub fn new(writer: W) -> O5mWriter<W> {
+        Self::with_options(writer, WriterOptions::default())
+    }
+
+    pub fn with_options(writer: W, options: WriterOptions) -> O5mWriter<W> {
         O5mWriter {
             inner: writer,
-            encoder: O5mEncoder::new(),
+            encoder: O5mEncoder::new(options),
         }
     }
 
@@ -55,6 +61,17 @@ impl<W: Write> O5mWriter<W> {
         Ok(())
     }
 
+    /// See: https://wiki.openstreetmap.org/wiki/O5m#File_Timestamp
+    fn write_file_timestamp(&mut self, timestamp: Timestamp) -> Result<()> {
+        let mut bytes = Vec::new();
+        bytes.write_varint(timestamp.seconds())?;
+
+        self.inner.write_all(&[O5M_TIMESTAMP])?;
+        self.inner.write_varint(bytes.len() as u64)?;
+        self.inner.write_all(&bytes)?;
+        Ok(())
+    }
+
     /// See: https://wiki.openstreetmap.org/wiki/O5m#Node
     fn write_node(&mut self, node: &Node) -> Result<()> {
         let mut bytes = Vec::new();
@@ -95,23 +112,27 @@ impl<W: Write> OsmWrite<W> for O5mWriter<W> {
         self.inner.write_all(&[O5M_HEADER])?;
         self.inner.write_all(O5M_HEADER_DATA)?;
 
-        if let Some(boundary) = &osm.boundary {
-            self.write_bounding_box(&boundary)?;
+        if let Some(timestamp) = osm.header.timestamp {
+            self.write_file_timestamp(timestamp)?;
+        }
+
+        if let Some(boundary) = osm.boundary.as_ref().filter(|b| !b.is_empty()) {
+            self.write_bounding_box(boundary)?;
         }
 
         self.reset()?;
         for node in &osm.nodes {
-            self.write_node(&node)?;
+            self.write_node(node)?;
         }
 
         self.reset()?;
         for way in &osm.ways {
-            self.write_way(&way)?;
+            self.write_way(way)?;
         }
 
         self.reset()?;
         for rel in &osm.relations {
-            self.write_relation(&rel)?;
+            self.write_relation(rel)?;
         }
 
         self.inner.write_all(&[O5M_EOF])?;
@@ -124,10 +145,11 @@ impl<W: Write> OsmWrite<W> for O5mWriter<W> {
 }
 
 impl O5mEncoder {
-    pub fn new() -> Self {
+    pub fn new(options: WriterOptions) -> Self {
         O5mEncoder {
             string_table: StringReferenceTable::new(),
             delta: DeltaState::new(),
+            meta_filter: MetaFilter::new(options),
         }
     }
 
@@ -141,15 +163,20 @@ impl O5mEncoder {
     /// See: https://wiki.openstreetmap.org/wiki/O5m#Node
     pub fn write_node<W: Write>(&mut self, writer: &mut W, node: &Node) -> Result<()> {
         let delta_id = self.delta.encode(Id, node.id);
-        let delta_coordinate = self.delta_coordinate(node.coordinate);
-
         writer.write_varint(delta_id)?;
         self.write_meta(writer, &node.meta)?;
+
+        // Deleted elements only have id and meta data.
+        if !node.meta.visible {
+            return Ok(());
+        }
+
+        let delta_coordinate = self.delta_coordinate(node.coordinate);
         writer.write_varint(delta_coordinate.lon)?;
         writer.write_varint(delta_coordinate.lat)?;
 
         for tag in &node.meta.tags {
-            self.write_tag(writer, &tag)?;
+            self.write_tag(writer, tag)?;
         }
 
         Ok(())
@@ -159,16 +186,19 @@ impl O5mEncoder {
     /// See: https://wiki.openstreetmap.org/wiki/O5m#Way
     pub fn write_way<W: Write>(&mut self, writer: &mut W, way: &Way) -> Result<()> {
         let delta_id = self.delta.encode(Id, way.id);
-        let mut ref_bytes = Vec::new();
-        self.write_way_refs(&mut ref_bytes, &way.refs)?;
-
         writer.write_varint(delta_id)?;
         self.write_meta(writer, &way.meta)?;
+        if !way.meta.visible {
+            return Ok(());
+        }
+
+        let mut ref_bytes = Vec::new();
+        self.write_way_refs(&mut ref_bytes, &way.refs)?;
         writer.write_varint(ref_bytes.len() as u64)?;
         writer.write_all(&ref_bytes)?;
 
         for tag in &way.meta.tags {
-            self.write_tag(writer, &tag)?;
+            self.write_tag(writer, tag)?;
         }
 
         Ok(())
@@ -186,16 +216,19 @@ impl O5mEncoder {
     /// Converts a relation into a byte vector that can be written to file.
     /// See: https://wiki.openstreetmap.org/wiki/O5m#Relation
     pub fn write_relation<W: Write>(&mut self, writer: &mut W, rel: &Relation) -> Result<()> {
-        let mut mem_bytes = Vec::new();
-        self.write_rel_members(&mut mem_bytes, &rel.members)?;
-
         writer.write_varint(self.delta.encode(Id, rel.id))?;
         self.write_meta(writer, &rel.meta)?;
+        if !rel.meta.visible {
+            return Ok(());
+        }
+
+        let mut mem_bytes = Vec::new();
+        self.write_rel_members(&mut mem_bytes, &rel.members)?;
         writer.write_varint(mem_bytes.len() as u64)?;
         writer.write_all(&mem_bytes)?;
 
         for tag in &rel.meta.tags {
-            self.write_tag(writer, &tag)?;
+            self.write_tag(writer, tag)?;
         }
 
         Ok(())
@@ -214,8 +247,8 @@ impl O5mEncoder {
 
             let mut mem_bytes = Vec::new();
             mem_bytes.write_all(&[0x00])?;
-            mem_bytes.write_all(&mem_type.as_bytes())?;
-            mem_bytes.write_all(&mem_role.as_bytes())?;
+            mem_bytes.write_all(mem_type.as_bytes())?;
+            mem_bytes.write_all(mem_role.as_bytes())?;
             mem_bytes.write_all(&[0x00])?;
 
             writer.write_varint(delta)?;
@@ -226,11 +259,12 @@ impl O5mEncoder {
 
     /// Writes meta to `writer`. It's positioned directly after the id of the element.
     pub fn write_meta<W: Write>(&mut self, writer: &mut W, meta: &Meta) -> Result<()> {
-        if let Some(version) = meta.version {
+        let output = self.meta_filter.apply(meta);
+        if let Some(version) = output.as_ref().and_then(|o| o.version) {
             writer.write_varint(version)?;
 
-            if let Some(author) = meta.author.as_ref() {
-                let delta_time = self.delta.encode(Time, author.created);
+            if let Some(author) = output.as_ref().and_then(|o| o.author.as_ref()) {
+                let delta_time = self.delta.encode(Time, author.created.seconds());
                 let delta_change_set = self.delta.encode(ChangeSet, author.change_set as i64);
 
                 writer.write_varint(delta_time)?;
@@ -320,11 +354,110 @@ fn member_type(member: &RelationMember) -> &str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::osm_io::o5m::O5mReader;
+    use crate::osm_io::OsmRead;
     use crate::{AuthorInformation, Meta, Relation, RelationMember, Way};
 
+    #[test]
+    fn file_timestamp_round_trip() {
+        let mut osm = Osm::default();
+        osm.header.timestamp = Some(Timestamp(1577836800));
+
+        let mut writer = O5mWriter::new(Vec::new());
+        writer.write(&osm).unwrap();
+        let data = writer.inner;
+        assert_eq!(data[7], O5M_TIMESTAMP);
+
+        let read = O5mReader::new(data.as_slice()).read().unwrap();
+        assert_eq!(read.header.timestamp, Some(Timestamp(1577836800)));
+    }
+
+    #[test]
+    fn anonymized_round_trip() {
+        let meta = Meta {
+            version: Some(3),
+            author: Some(AuthorInformation {
+                created: Timestamp(1577934245),
+                change_set: 1234,
+                uid: 42,
+                user: "alice".to_owned(),
+            }),
+            ..Meta::default()
+        };
+        let mut osm = Osm::default();
+        osm.add_node(Node {
+            id: 1,
+            meta,
+            ..Node::default()
+        });
+
+        let options = WriterOptions {
+            anonymize: true,
+            ..WriterOptions::default()
+        };
+        let mut writer = O5mWriter::with_options(Vec::new(), options);
+        writer.write(&osm).unwrap();
+
+        let read = O5mReader::new(writer.inner.as_slice()).read().unwrap();
+        let expected = AuthorInformation {
+            created: Timestamp(1577934245),
+            change_set: 1,
+            uid: 1,
+            user: "user_1".to_owned(),
+        };
+        assert_eq!(read.nodes[0].meta.author, Some(expected));
+        assert_eq!(read.nodes[0].meta.version, Some(3));
+    }
+
+    #[test]
+    fn deleted_round_trip() {
+        let deleted = Meta {
+            version: Some(2),
+            visible: false,
+            ..Meta::default()
+        };
+        let mut osm = Osm::default();
+        osm.add_node(Node {
+            id: 1,
+            coordinate: (1.0, 1.0).into(),
+            meta: Meta {
+                version: Some(1),
+                ..Meta::default()
+            },
+        });
+        osm.add_node(Node {
+            id: 1,
+            meta: deleted.clone(),
+            ..Node::default()
+        });
+        osm.add_node(Node {
+            id: 2,
+            coordinate: (2.0, 2.0).into(),
+            meta: Meta::default(),
+        });
+        osm.add_way(Way {
+            id: 3,
+            refs: vec![],
+            meta: deleted.clone(),
+        });
+        osm.add_relation(Relation {
+            id: 4,
+            members: vec![],
+            meta: deleted,
+        });
+
+        let mut writer = O5mWriter::new(Vec::new());
+        writer.write(&osm).unwrap();
+        let read = O5mReader::new(writer.inner.as_slice()).read().unwrap();
+
+        assert_eq!(read.nodes, osm.nodes);
+        assert_eq!(read.ways, osm.ways);
+        assert_eq!(read.relations, osm.relations);
+    }
+
     #[test]
     fn string_pair_bytes() {
-        let mut encoder = O5mEncoder::new();
+        let mut encoder = O5mEncoder::new(WriterOptions::default());
         let bytes = encoder.string_pair_to_bytes("oneway", "yes");
         let expected: Vec<u8> = vec![
             0x00, 0x6f, 0x6e, 0x65, 0x77, 0x61, 0x79, 0x00, 0x79, 0x65, 0x73, 0x00,
@@ -334,7 +467,7 @@ mod tests {
 
     #[test]
     fn string_references() {
-        let mut references = O5mEncoder::new();
+        let mut references = O5mEncoder::new(WriterOptions::default());
         assert_eq!(
             references.string_pair_to_bytes("oneway", "yes"),
             vec![0x00, 0x6f, 0x6e, 0x65, 0x77, 0x61, 0x79, 0x00, 0x79, 0x65, 0x73, 0x00]
@@ -383,12 +516,13 @@ mod tests {
                 tags: vec![("oneway", "yes").into()],
                 version: Some(1),
                 author: Some(AuthorInformation {
-                    created: 1285874610,
+                    created: Timestamp(1285874610),
                     change_set: 5922698,
                     uid: 45445,
                     user: "UScha".to_string(),
                 }),
-                ..Default::default()
+                visible: true,
+                action: None,
             },
         };
 
@@ -468,7 +602,7 @@ mod tests {
 
     #[test]
     fn coordinate_delta() {
-        let mut encoder = O5mEncoder::new();
+        let mut encoder = O5mEncoder::new(WriterOptions::default());
         assert_eq!(
             encoder.delta_coordinate(Coordinate { lat: 1, lon: 10 }),
             Coordinate { lat: 1, lon: 10 }
@@ -0,0 +1,211 @@
+//! Options for the readers and writers.
+use chrono::Duration;
+
+/// How a reader handles invalid elements.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum ParseMode {
+    /// The first invalid element aborts the read with an error. This is the default.
+    #[default]
+    Strict,
+
+    /// Invalid elements are recorded as warnings and the read continues. An element is kept
+    /// without the invalid parts if possible (e.g. a bad tag, member or meta data), otherwise it is
+    /// dropped (e.g. a missing id or coordinate).
+    ///
+    /// Errors that makes it impossible to continue, like malformed xml or a truncated o5m file,
+    /// still aborts the read.
+    Lenient,
+}
+
+/// Options for `create_reader_with_options`.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::osm_io::{create_reader_with_options, FileFormat, ParseMode, ReaderOptions};
+/// let xml = r#"<osm>
+///     <node id="1" lat="1.0" lon="1.0"/>
+///     <node id="2" lat="1.0"/>
+/// </osm>"#;
+///
+/// let options = ReaderOptions {
+///     mode: ParseMode::Lenient,
+/// };
+/// let mut reader = create_reader_with_options(xml.as_bytes(), FileFormat::Xml, options);
+/// let (osm, warnings) = reader.read_with_warnings().unwrap();
+///
+/// assert_eq!(osm.nodes.len(), 1);
+/// assert_eq!(
+///     warnings[0].to_string(),
+///     "Line 3: Required attribute 'lon' missing."
+/// );
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct ReaderOptions {
+    pub mode: ParseMode,
+}
+
+/// Which meta data writers output for each element.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum MetaLevel {
+    /// No version, timestamp or author information.
+    None,
+
+    /// Only the version.
+    Version,
+
+    /// Version, timestamp and author information. This is the default.
+    #[default]
+    Full,
+}
+
+/// Options for `create_writer_with_options`.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::osm_io::{create_writer_with_options, FileFormat, WriterOptions};
+/// # use vadeen_osm::{AuthorInformation, Meta, Node, Osm, Timestamp};
+/// # use chrono::Duration;
+/// let mut osm = Osm::default();
+/// osm.add_node(Node {
+///     id: 1,
+///     coordinate: (1.0, 1.0).into(),
+///     meta: Meta {
+///         version: Some(1),
+///         author: Some(AuthorInformation {
+///             created: "2020-01-02T03:04:05Z".parse().unwrap(),
+///             change_set: 1234,
+///             uid: 42,
+///             user: "alice".to_owned(),
+///         }),
+///         ..Meta::default()
+///     },
+/// });
+///
+/// let options = WriterOptions {
+///     anonymize: true,
+///     timestamp_precision: Some(Duration::days(1)),
+///     ..WriterOptions::default()
+/// };
+/// let mut writer = create_writer_with_options(Vec::new(), FileFormat::Xml, options);
+/// writer.write(&osm).unwrap();
+/// let xml = String::from_utf8(writer.into_inner()).unwrap();
+///
+/// assert!(xml.contains(r#"uid="1" user="user_1" changeset="1" timestamp="2020-01-02T00:00:00Z""#));
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct WriterOptions {
+    pub meta: MetaLevel,
+
+    /// Replace uids, users and change sets with pseudonyms. Each uid and change set gets a number
+    /// in order of first appearance, and users are named after their pseudonymous uid, e.g.
+    /// `user_1`. Pseudonyms are stable for the lifetime of the writer, so the same user is
+    /// recognizable across elements but not identifiable. Use `pseudonym_salt` for pseudonyms
+    /// that are stable across writers.
+    pub anonymize: bool,
+
+    /// Derive pseudonyms from a keyed hash of the ids instead of numbering them, so all writers
+    /// with the same salt give an id the same pseudonym. Keep the salt secret, anyone who knows
+    /// it can recover the ids by hashing candidates. Only used if `anonymize` is true. Default is
+    /// `None`.
+    pub pseudonym_salt: Option<u128>,
+
+    /// Round element timestamps down to a multiple of this duration, e.g. `Duration::days(1)`.
+    pub timestamp_precision: Option<Duration>,
+
+    /// Formatting of xml output, ignored by other formats.
+    pub xml: XmlWriterOptions,
+}
+
+/// Indentation of pretty printed xml.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum Indent {
+    /// One tab per level. This is the default.
+    #[default]
+    Tabs,
+
+    /// The given number of spaces per level.
+    Spaces(usize),
+}
+
+/// Order of the attributes of nodes, ways and relations in xml output.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum AttributeOrder {
+    /// `id lat lon version uid user changeset timestamp visible`. This is the default.
+    #[default]
+    Vadeen,
+
+    /// `id version timestamp uid user changeset visible lat lon`, like osmium.
+    Osmium,
+
+    /// `id timestamp uid user visible version changeset lat lon`, like JOSM.
+    Josm,
+}
+
+/// Formatting options for xml output, see `WriterOptions`.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::osm_io::{create_writer_with_options, AttributeOrder, FileFormat};
+/// # use vadeen_osm::osm_io::{WriterOptions, XmlWriterOptions};
+/// # use vadeen_osm::{Meta, Node, Osm};
+/// let mut osm = Osm::default();
+/// osm.add_node(Node {
+///     id: 1,
+///     coordinate: (59.3293, 18.0686).into(),
+///     meta: Meta {
+///         version: Some(2),
+///         ..Meta::default()
+///     },
+/// });
+///
+/// let options = WriterOptions {
+///     xml: XmlWriterOptions {
+///         pretty: false,
+///         fixed_precision: true,
+///         attribute_order: AttributeOrder::Osmium,
+///         ..XmlWriterOptions::default()
+///     },
+///     ..WriterOptions::default()
+/// };
+/// let mut writer = create_writer_with_options(Vec::new(), FileFormat::Xml, options);
+/// writer.write(&osm).unwrap();
+/// let xml = String::from_utf8(writer.into_inner()).unwrap();
+///
+/// assert!(xml.contains(r#"><node id="1" version="2" lat="59.3293000" lon="18.0686000"/></osm>"#));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct XmlWriterOptions {
+    /// Write each element on its own line and indent nested elements. If false, no whitespace is
+    /// written between elements. Default is true.
+    pub pretty: bool,
+
+    /// Indentation used when `pretty` is true.
+    pub indent: Indent,
+
+    /// Write coordinates with exactly 7 decimals, the precision of OSM coordinates. Default is
+    /// false, i.e. the shortest representation of the coordinate.
+    pub fixed_precision: bool,
+
+    /// Write `version="1"` for elements without version. Default is true, since some tools
+    /// require the version attribute. New elements, i.e. with negative ids, never get a version.
+    pub write_missing_version: bool,
+
+    pub attribute_order: AttributeOrder,
+
+    /// Written in the `generator` attribute of `<osm>` instead of the generator in `OsmHeader`.
+    /// If neither is set, "Vadeen OSM" is written. Default is `None`.
+    pub generator: Option<String>,
+}
+
+impl Default for XmlWriterOptions {
+    fn default() -> Self {
+        XmlWriterOptions {
+            pretty: true,
+            indent: Indent::default(),
+            fixed_precision: false,
+            write_missing_version: true,
+            attribute_order: AttributeOrder::default(),
+            generator: None,
+        }
+    }
+}
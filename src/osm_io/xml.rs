@@ -13,13 +13,15 @@ use crate::osm_io::error::ErrorKind::ParseError;
 
 impl From<quick_xml::Error> for Error {
     fn from(e: quick_xml::Error) -> Self {
-        Error::new(ParseError, Some(e.to_string()))
+        Error::with_source(ParseError, Some(e.to_string()), e)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::osm_io::{create_reader, FileFormat};
+    use crate::osm_io::{create_reader, create_writer, create_writer_with_options, FileFormat};
+    use crate::osm_io::{AttributeOrder, WriterOptions, XmlWriterOptions};
+    use crate::{Action, CopyrightForm, UploadPolicy};
 
     #[test]
     fn quick_xml_error() {
@@ -35,4 +37,67 @@ mod test {
             error.to_string()
         );
     }
+
+    #[test]
+    fn api_header_round_trip() {
+        let xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                   <osm version=\"0.6\" generator=\"OpenStreetMap server\" \
+                   copyright=\"OpenStreetMap and contributors\">\n\
+                   \t<bounds minlat=\"1\" minlon=\"2\" maxlat=\"3\" maxlon=\"4\"/>\n\
+                   \t<node id=\"1\" lat=\"1.5\" lon=\"2.5\" version=\"1\"/>\n\
+                   </osm>";
+
+        let osm = create_reader(xml.as_bytes(), FileFormat::Xml)
+            .read()
+            .unwrap();
+        assert_eq!(osm.header.copyright_form, CopyrightForm::Attribute);
+
+        let mut writer = create_writer(Vec::new(), FileFormat::Xml);
+        writer.write(&osm).unwrap();
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), xml);
+    }
+
+    #[test]
+    fn josm_round_trip() {
+        let xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                   <osm version=\"0.6\" generator=\"JOSM\" upload=\"false\">\n\
+                   \t<node id=\"-1\" action=\"modify\" lat=\"1.5\" lon=\"2.5\"/>\n\
+                   \t<node id=\"7\" action=\"delete\" timestamp=\"2010-09-30T19:23:30Z\" uid=\"1\" \
+                   user=\"osm\" version=\"3\" changeset=\"9\" lat=\"1.5\" lon=\"2.5\"/>\n\
+                   </osm>";
+
+        let mut osm = create_reader(xml.as_bytes(), FileFormat::Xml)
+            .read()
+            .unwrap();
+        assert_eq!(osm.header.upload, Some(UploadPolicy::Discouraged));
+        assert_eq!(osm.nodes[0].id, -1);
+        assert_eq!(osm.nodes[0].meta.action, Some(Action::Modify));
+        assert_eq!(osm.nodes[1].meta.action, Some(Action::Delete));
+        assert!(osm.nodes[1].meta.visible);
+
+        // JOSM files have no bounds, the reader calculates them.
+        osm.boundary = None;
+        let options = WriterOptions {
+            xml: XmlWriterOptions {
+                attribute_order: AttributeOrder::Josm,
+                ..XmlWriterOptions::default()
+            },
+            ..WriterOptions::default()
+        };
+        let mut writer = create_writer_with_options(Vec::new(), FileFormat::Xml, options);
+        writer.write(&osm).unwrap();
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), xml);
+    }
+
+    #[test]
+    fn invalid_action() {
+        let xml = r#"<osm><node id="1" lat="1" lon="1" action="create"/></osm>"#;
+        let error = create_reader(xml.as_bytes(), FileFormat::Xml)
+            .read()
+            .unwrap_err();
+        assert_eq!(
+            "Line 1: The 'action' attribute contains invalid data 'create'.",
+            error.to_string()
+        );
+    }
 }
@@ -1,19 +1,72 @@
-use super::super::chrono::{DateTime, Utc};
 use super::quick_xml::Reader;
 use crate::geo::{Boundary, Coordinate};
-use crate::osm_io::error::ErrorKind::ParseError;
-use crate::osm_io::error::{Error, Result};
-use crate::osm_io::OsmRead;
-use crate::{AuthorInformation, Meta, Node, Osm, Relation, RelationMember, Tag, Way};
+use crate::osm_io::error::{Error, ErrorKind, Location, Result};
+use crate::osm_io::{OsmRead, ParseMode, ReaderOptions};
+use crate::{
+    Action, AuthorInformation, CopyrightForm, ElementId, Meta, Node, NodeId, Osm, OsmHeader,
+    Relation, RelationId, RelationMember, Tag, Timestamp, UploadPolicy, Way, WayId,
+};
 use quick_xml::events::{BytesStart, Event};
+use std::cell::Cell;
 use std::collections::HashMap;
-use std::io::BufRead;
+use std::io::{BufRead, Read};
+use std::rc::Rc;
 use std::str::FromStr;
 
 /// A reader for the xml format.
 pub struct XmlReader<R: BufRead> {
-    reader: Reader<R>,
+    reader: Reader<PositionReader<R>>,
+    positions: Rc<Cell<Positions>>,
+    options: ReaderOptions,
+
+    /// Location of the element being read.
+    element: Location,
+    warnings: Vec<Error>,
+
+    /// Names of the open elements.
+    open: Vec<Vec<u8>>,
+    state: State,
+}
+
+/// What the reader is currently inside of.
+enum State {
+    /// At the top level of the document, or directly inside <osm>.
+    Document,
+
+    /// Inside an OSM element (<node>, <way>, ...), collecting its content.
+    Element {
+        start: BytesStart<'static>,
+        content: Vec<BytesStart<'static>>,
+    },
+
+    /// Inside an element that is skipped, e.g. the content of a non empty <tag> or an unknown
+    /// element. The state returns to `previous` when the element at `depth` ends.
+    Skip { depth: usize, previous: Box<State> },
+
+    /// Inside <note>, collecting the copyright note.
+    Note { text: String },
+}
+
+/// Wraps the inner reader to keep track of the position of the consumed data, since the xml
+/// reader only knows the byte offset.
+struct PositionReader<R> {
+    inner: R,
+    positions: Rc<Cell<Positions>>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct TextPosition {
+    offset: u64,
     line: u32,
+    column: u32,
+}
+
+/// The current position, and the position of the last consumed '<', i.e. the start of the last
+/// read tag.
+#[derive(Debug, Copy, Clone)]
+struct Positions {
+    current: TextPosition,
+    tag_start: TextPosition,
 }
 
 /// Abstract representation of the attributes of an XML element.
@@ -27,14 +80,12 @@ impl Attributes {
     /// Create from quick_xml attributes data.
     fn from(attributes: super::quick_xml::events::attributes::Attributes) -> Self {
         let mut map = HashMap::new();
-        for attr in attributes {
-            if let Ok(attr) = attr {
-                if let Ok(value) = attr.unescaped_value() {
-                    map.insert(
-                        String::from_utf8_lossy(attr.key).into_owned(),
-                        String::from_utf8_lossy(value.as_ref()).into_owned(),
-                    );
-                }
+        for attr in attributes.flatten() {
+            if let Ok(value) = attr.unescaped_value() {
+                map.insert(
+                    String::from_utf8_lossy(attr.key).into_owned(),
+                    String::from_utf8_lossy(value.as_ref()).into_owned(),
+                );
             }
         }
         Attributes { map }
@@ -46,33 +97,44 @@ impl Attributes {
 
     /// Same as normal get, but returns error instead of option.
     fn get_required(&self, val: &str) -> Result<&String> {
-        Ok(self.get(val).ok_or_else(|| {
-            Error::new(
-                ParseError,
-                Some(format!("Required attribute '{}' missing.", val)),
-            )
-        })?)
+        self.get(val)
+            .ok_or_else(|| Error::new(ErrorKind::MissingAttribute(val.to_owned()), None))
     }
 
     /// Get element (with get_required) and parse data into F.
-    fn get_parse<F: FromStr>(&self, field: &str) -> Result<F> {
+    fn get_parse<F>(&self, field: &str) -> Result<F>
+    where
+        F: FromStr,
+        F::Err: std::error::Error + Send + Sync + 'static,
+    {
         let s = self.get_required(field)?;
         self.parse(field, s)
     }
 
-    /// Get element (with get_required) and parse data into F.
-    fn parse<F: FromStr>(&self, field: &str, s: &str) -> Result<F> {
-        str::parse(s).map_err(|_| {
-            Error::new(
-                ParseError,
-                Some(format!(
-                    "The '{}' attribute contains invalid data '{}'.",
-                    field, s
-                )),
-            )
+    /// Parse attribute value into F.
+    fn parse<F>(&self, field: &str, s: &str) -> Result<F>
+    where
+        F: FromStr,
+        F::Err: std::error::Error + Send + Sync + 'static,
+    {
+        str::parse(s).map_err(|e| {
+            let kind = ErrorKind::InvalidAttributeValue {
+                attribute: field.to_owned(),
+                value: s.to_owned(),
+            };
+            Error::with_source(kind, None, e)
         })
     }
 
+    /// Error for an attribute with a value that is not one of the allowed values.
+    fn invalid_value(&self, field: &str, value: &str) -> Error {
+        let kind = ErrorKind::InvalidAttributeValue {
+            attribute: field.to_owned(),
+            value: value.to_owned(),
+        };
+        Error::new(kind, None)
+    }
+
     /// Check if all attribute `keys` are present.
     fn contains_all(&self, keys: Vec<&str>) -> bool {
         for key in keys {
@@ -91,6 +153,15 @@ impl Attributes {
         ))
     }
 
+    /// Try to create the coordinate of a node. Deleted nodes in history data have no coordinate.
+    fn create_node_coordinate(&self) -> Result<Coordinate> {
+        let deleted = self.get("visible").map(String::as_str) == Some("false");
+        if deleted && !self.contains_all(vec!["lat", "lon"]) {
+            return Ok(Coordinate { lat: 0, lon: 0 });
+        }
+        self.create_coordinate()
+    }
+
     /// Try to create a `Boundary` from attribute values.
     fn create_boundary(&self) -> Result<Boundary> {
         Ok(Boundary {
@@ -112,7 +183,7 @@ impl Attributes {
     fn create_meta(&self) -> Result<Meta> {
         let author = if self.contains_all(vec!["timestamp", "uid", "user", "changeset"]) {
             Some(AuthorInformation {
-                created: self.get_timestamp()?,
+                created: self.get_timestamp("timestamp")?,
                 uid: self.get_parse("uid")?,
                 user: self.get_required("user")?.to_owned(),
                 change_set: self.get_parse("changeset")?,
@@ -127,25 +198,72 @@ impl Attributes {
             None
         };
 
+        let visible = match self.get("visible") {
+            Some(visible) => self.parse("visible", visible)?,
+            None => true,
+        };
+
+        let action = match self.get("action").map(String::as_str) {
+            Some("modify") => Some(Action::Modify),
+            Some("delete") => Some(Action::Delete),
+            Some(action) => return Err(self.invalid_value("action", action)),
+            None => None,
+        };
+
         Ok(Meta {
             version,
             author,
+            visible,
+            action,
             ..Meta::default()
         })
     }
 
-    fn get_timestamp(&self) -> Result<i64> {
-        let time_str = self.get_required("timestamp")?;
-        if let Ok(time) = time_str.parse::<DateTime<Utc>>() {
-            Ok(time.timestamp())
-        } else {
-            Err(Error::new(
-                ParseError,
-                Some(format!("Invalid timestamp '{}'", time_str)),
-            ))
+    fn get_timestamp(&self, field: &str) -> Result<Timestamp> {
+        let time_str = self.get_required(field)?;
+        match time_str.parse() {
+            Ok(time) => Ok(time),
+            Err(e) => Err(Error::with_source(
+                ErrorKind::InvalidTimestamp(time_str.to_owned()),
+                None,
+                e,
+            )),
         }
     }
 
+    /// Try to create an `OsmHeader` from the attributes of <osm>.
+    fn create_header(&self) -> Result<OsmHeader> {
+        let replication_sequence = match self.get("replication_sequence") {
+            Some(sequence) => Some(self.parse("replication_sequence", sequence)?),
+            None => None,
+        };
+
+        let upload = match self.get("upload").map(String::as_str) {
+            Some("true") => Some(UploadPolicy::Normal),
+            Some("false") => Some(UploadPolicy::Discouraged),
+            Some("never") => Some(UploadPolicy::Blocked),
+            Some(upload) => return Err(self.invalid_value("upload", upload)),
+            None => None,
+        };
+
+        let copyright = self.get("copyright").cloned();
+        let copyright_form = match copyright {
+            Some(_) => CopyrightForm::Attribute,
+            None => CopyrightForm::Note,
+        };
+
+        Ok(OsmHeader {
+            generator: self.get("generator").cloned(),
+            version: self.get("version").cloned(),
+            replication_sequence,
+            source: self.get("source").cloned(),
+            copyright,
+            copyright_form,
+            upload,
+            ..OsmHeader::default()
+        })
+    }
+
     /// Try to create a `RelationMember` from attribute values.
     fn create_relation_member(&self) -> Result<RelationMember> {
         let default_role = "".to_owned();
@@ -158,7 +276,7 @@ impl Attributes {
             "way" => Ok(RelationMember::Way(mem_ref, mem_role.to_owned())),
             "rel" | "relation" => Ok(RelationMember::Relation(mem_ref, mem_role.to_owned())),
             t => Err(Error::new(
-                ParseError,
+                ErrorKind::UnknownMemberType(t.to_owned()),
                 Some(format!(
                     "The 'type' attribute contains invalid data '{}'.",
                     t
@@ -170,105 +288,309 @@ impl Attributes {
 
 impl<R: BufRead> XmlReader<R> {
     pub fn new(inner: R) -> XmlReader<R> {
+        Self::with_options(inner, ReaderOptions::default())
+    }
+
+    pub fn with_options(inner: R, options: ReaderOptions) -> XmlReader<R> {
+        let start = TextPosition {
+            offset: 0,
+            line: 1,
+            column: 1,
+        };
+        let positions = Rc::new(Cell::new(Positions {
+            current: start,
+            tag_start: start,
+        }));
+        let inner = PositionReader {
+            inner,
+            positions: positions.clone(),
+        };
         XmlReader {
             reader: Reader::from_reader(inner),
-            line: 1,
+            positions,
+            options,
+            element: Location::default(),
+            warnings: Vec::new(),
+            open: Vec::new(),
+            state: State::Document,
         }
     }
 
-    /// Parse next xml element. Returns false if end of file was reached.
+    fn positions(&self) -> Positions {
+        self.positions.get()
+    }
+
+    /// Parse next xml event. Returns false if end of file was reached.
     fn parse_event(&mut self, osm: &mut Osm) -> Result<bool> {
         let mut buf = Vec::new();
         match self.reader.read_event(&mut buf)? {
-            Event::Start(ref event) => self.parse_element(osm, event)?,
-            Event::Empty(ref event) => self.parse_empty_element(osm, event)?,
-            Event::Eof => return Ok(false),
+            Event::Start(ref event) => {
+                self.start_element(osm, event)?;
+                self.open.push(event.name().to_vec());
+            }
+            Event::Text(ref event) => {
+                if let State::Note { ref mut text } = self.state {
+                    text.push_str(&String::from_utf8_lossy(&event.unescaped()?));
+                }
+            }
+            Event::Empty(ref event) => self.empty_element(osm, event)?,
+            Event::End(_) => {
+                self.open.pop();
+                self.end_element(osm)?;
+            }
+            Event::Eof => {
+                self.check_closed()?;
+                return Ok(false);
+            }
             _ => { /* Ignore all other events. */ }
         }
-
-        self.line += buf.iter().filter(|b| **b == b'\n').count() as u32;
         Ok(true)
     }
 
-    /// Read until and end element, or end of file is reached.
-    /// Only empty elements are returned, the rest is ignored. This limitation since OSM only use
-    /// empty element in a nested context within the <osm> tag.
-    ///
-    /// TODO Corruption if nested elements are encountered:
-    /// This should return error if non empty element is encountered. The end of the nested element
-    /// will terminate this read and possibly corrupt the flow.
-    fn read_element_content(&mut self, mut buf: &mut Vec<u8>) -> Result<Vec<BytesStart>> {
-        let mut events = Vec::new();
-        loop {
-            match self.reader.read_event(&mut buf)? {
-                Event::Empty(ref e) => events.push(e.to_owned()),
-                Event::End(_) => break,
-                Event::Eof => break,
-                _ => { /* Only empty elements are expected in element contents. */ }
+    /// Enter a non empty element.
+    fn start_element(&mut self, osm: &mut Osm, event: &BytesStart) -> Result<()> {
+        match self.state {
+            // The <osm> element wraps the OSM elements, it only contains the header.
+            State::Document if event.name() == b"osm" => {
+                self.element = element_location(self.positions().tag_start, event);
+                let header = Attributes::from(event.attributes()).create_header();
+                if let Some(header) = self.check(header)? {
+                    osm.header = header;
+                }
+                return Ok(());
+            }
+            State::Document if event.name() == b"note" => {
+                self.state = State::Note {
+                    text: String::new(),
+                };
+                return Ok(());
+            }
+            State::Document if is_osm_element(event) => {
+                self.element = element_location(self.positions().tag_start, event);
+                self.state = State::Element {
+                    start: event.to_owned(),
+                    content: Vec::new(),
+                };
+                return Ok(());
             }
+            // Content in non empty form, e.g. <tag k="a" v="b"></tag>. Its own content is skipped.
+            State::Element {
+                ref mut content, ..
+            } => content.push(event.to_owned()),
+            _ => {}
         }
-        Ok(events)
+
+        let previous = std::mem::replace(&mut self.state, State::Document);
+        self.state = State::Skip {
+            depth: self.open.len(),
+            previous: Box::new(previous),
+        };
+        Ok(())
     }
 
-    /// Parse empty top level element. (<node.../>, <bounds.../>)
-    fn parse_empty_element(&mut self, osm: &mut Osm, event: &BytesStart) -> Result<()> {
-        match event.name() {
-            b"node" => osm.add_node(parse_node(&event)?),
-            b"bounds" => osm.boundary = Some(parse_boundary(&event)?),
-            _ => {}
+    /// Handle an empty element, either a top level element (<node.../>, <bounds.../>) or content.
+    fn empty_element(&mut self, osm: &mut Osm, event: &BytesStart) -> Result<()> {
+        match self.state {
+            State::Document => {
+                self.element = element_location(self.positions().tag_start, event);
+                self.create_element(osm, event, &[])?;
+            }
+            State::Element {
+                ref mut content, ..
+            } => content.push(event.to_owned()),
+            State::Skip { .. } | State::Note { .. } => {}
         }
         Ok(())
     }
 
-    /// Parse non empty elements. (<node...>, <way...>, ...)
-    fn parse_element(&mut self, osm: &mut Osm, event: &BytesStart) -> Result<()> {
-        // We only work on one indentation level. To do this we must ignore <osm> since it
-        // introduces another one.
-        if event.name() == b"osm" {
-            return Ok(());
+    /// Leave the current element, the OSM element is created when it ends.
+    fn end_element(&mut self, osm: &mut Osm) -> Result<()> {
+        match std::mem::replace(&mut self.state, State::Document) {
+            State::Element { start, content } => self.create_element(osm, &start, &content)?,
+            State::Skip { depth, previous } if depth == self.open.len() => self.state = *previous,
+            State::Note { text } => {
+                osm.header.copyright = Some(text);
+                osm.header.copyright_form = CopyrightForm::Note;
+            }
+            state => self.state = state,
         }
+        Ok(())
+    }
 
-        let mut buf = Vec::new();
-        let event_content = self.read_element_content(&mut buf)?;
+    /// Returns error if the document ended inside an element.
+    fn check_closed(&mut self) -> Result<()> {
+        let name = match self.open.last() {
+            Some(name) => String::from_utf8_lossy(name).into_owned(),
+            None => return Ok(()),
+        };
+
+        let mut error = Error::new(
+            ErrorKind::TruncatedDataset,
+            Some(format!("Unexpected end of file, expected </{}>.", name)),
+        );
+        if let State::Element { .. } = self.state {
+            *error.location_mut() = self.element.clone();
+        }
+        Err(error)
+    }
+
+    /// Create an element from the start tag and its content. In lenient mode invalid elements
+    /// are dropped and invalid content is skipped.
+    fn create_element(
+        &mut self,
+        osm: &mut Osm,
+        event: &BytesStart,
+        event_content: &[BytesStart],
+    ) -> Result<()> {
+        let attributes = Attributes::from(event.attributes());
         match event.name() {
+            // Overpass meta data, <meta osm_base="..."/>.
+            b"meta" if attributes.get("osm_base").is_some() => {
+                if let Some(timestamp) = self.check(attributes.get_timestamp("osm_base"))? {
+                    osm.header.timestamp = Some(timestamp);
+                }
+            }
+            b"bounds" => {
+                if let Some(boundary) = self.check(attributes.create_boundary())? {
+                    osm.boundary = Some(boundary);
+                }
+            }
             b"node" => {
-                let mut node = parse_node(&event)?;
-                node.meta.tags = create_tags(&event_content)?;
-                osm.add_node(node);
+                let node = attributes.get_parse("id").and_then(|id| {
+                    Ok(Node {
+                        id,
+                        coordinate: attributes.create_node_coordinate()?,
+                        meta: Meta::default(),
+                    })
+                });
+                if let Some(mut node) = self.check(node)? {
+                    node.meta = self.create_meta(&attributes, event_content)?;
+                    osm.add_node(node);
+                }
             }
             b"way" => {
-                let mut way = parse_way(&event)?;
-                way.refs = create_way_refs(&event_content)?;
-                way.meta.tags = create_tags(&event_content)?;
-                osm.add_way(way);
+                if let Some(id) = self.check(attributes.get_parse("id"))? {
+                    let mut meta = self.create_meta(&attributes, &[])?;
+                    let refs = self.create_way_refs(event_content)?;
+                    meta.tags = self.create_tags(event_content)?;
+                    osm.add_way(Way { id, refs, meta });
+                }
             }
             b"relation" => {
-                let mut relation = parse_relation(&event)?;
-                relation.members = create_relation_members(&event_content)?;
-                relation.meta.tags = create_tags(&event_content)?;
-                osm.add_relation(relation);
+                if let Some(id) = self.check(attributes.get_parse("id"))? {
+                    let mut meta = self.create_meta(&attributes, &[])?;
+                    let members = self.create_relation_members(event_content)?;
+                    meta.tags = self.create_tags(event_content)?;
+                    osm.add_relation(Relation { id, members, meta });
+                }
             }
             _ => { /* Ignore unknown elements. */ }
         }
-
-        self.line += buf.iter().filter(|b| **b == b'\n').count() as u32;
         Ok(())
     }
+
+    /// Create meta data with the tags in `events`. Invalid meta data is left out in lenient mode.
+    fn create_meta(&mut self, attributes: &Attributes, events: &[BytesStart]) -> Result<Meta> {
+        let mut meta = self.check(attributes.create_meta())?.unwrap_or_default();
+        meta.tags = self.create_tags(events)?;
+        Ok(meta)
+    }
+
+    fn create_tags(&mut self, events: &[BytesStart]) -> Result<Vec<Tag>> {
+        let mut tags = Vec::new();
+        for e in events.iter().filter(|e| e.name() == b"tag") {
+            tags.extend(self.check(Attributes::from(e.attributes()).create_tag())?);
+        }
+        Ok(tags)
+    }
+
+    fn create_way_refs(&mut self, events: &[BytesStart]) -> Result<Vec<i64>> {
+        let mut refs = Vec::new();
+        for e in events.iter().filter(|e| e.name() == b"nd") {
+            refs.extend(self.check(Attributes::from(e.attributes()).get_parse::<i64>("ref"))?);
+        }
+        Ok(refs)
+    }
+
+    fn create_relation_members(&mut self, events: &[BytesStart]) -> Result<Vec<RelationMember>> {
+        let mut members = Vec::new();
+        for e in events.iter().filter(|e| e.name() == b"member") {
+            let member = Attributes::from(e.attributes()).create_relation_member();
+            members.extend(self.check(member)?);
+        }
+        Ok(members)
+    }
+
+    /// Locate an error at the current element. The error is returned in strict mode, in lenient
+    /// mode it is recorded as a warning and `None` is returned.
+    fn check<T>(&mut self, result: Result<T>) -> Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(mut error) => {
+                *error.location_mut() = self.element.clone();
+                match self.options.mode {
+                    ParseMode::Strict => Err(error),
+                    ParseMode::Lenient => {
+                        self.warnings.push(error);
+                        Ok(None)
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Read for PositionReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead> BufRead for PositionReader<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // Consume is always called after fill_buf, so the data is already buffered.
+        if let Ok(data) = self.inner.fill_buf() {
+            let mut positions = self.positions.get();
+            for &b in &data[..amt.min(data.len())] {
+                let position = &mut positions.current;
+                if b == b'<' {
+                    positions.tag_start = *position;
+                }
+
+                position.offset += 1;
+                if b == b'\n' {
+                    position.line += 1;
+                    position.column = 1;
+                } else {
+                    position.column += 1;
+                }
+            }
+            self.positions.set(positions);
+        }
+        self.inner.consume(amt);
+    }
 }
 
 impl<R: BufRead> OsmRead for XmlReader<R> {
-    fn read(&mut self) -> std::result::Result<Osm, Error> {
+    fn read_with_warnings(&mut self) -> std::result::Result<(Osm, Vec<Error>), Error> {
         let mut osm = Osm::default();
         loop {
             match self.parse_event(&mut osm) {
                 Ok(true) => {}
                 Ok(false) => break,
                 Err(mut error) => {
-                    if let Some(message) = error.message() {
-                        let message = format!("Line {}: {}", self.line, message);
-                        error.set_message(message);
+                    if error.location().line.is_none() {
+                        let current = self.positions().current;
+                        *error.location_mut() = current.into();
                     }
-
                     return Err(error);
                 }
             }
@@ -278,64 +600,40 @@ impl<R: BufRead> OsmRead for XmlReader<R> {
             boundary.freeze = false;
         }
 
-        Ok(osm)
+        Ok((osm, std::mem::take(&mut self.warnings)))
     }
 }
 
-fn parse_boundary(event: &BytesStart) -> Result<Boundary> {
-    let attributes = Attributes::from(event.attributes());
-    Ok(attributes.create_boundary()?)
-}
-
-fn parse_node(event: &BytesStart) -> Result<Node> {
-    let attributes = Attributes::from(event.attributes());
-    Ok(Node {
-        id: attributes.get_parse("id")?,
-        coordinate: attributes.create_coordinate()?,
-        meta: attributes.create_meta()?,
-    })
-}
-
-fn parse_way(event: &BytesStart) -> Result<Way> {
-    let attributes = Attributes::from(event.attributes());
-    Ok(Way {
-        id: attributes.get_parse("id")?,
-        refs: vec![],
-        meta: attributes.create_meta()?,
-    })
-}
-
-fn parse_relation(event: &BytesStart) -> Result<Relation> {
-    let attributes = Attributes::from(event.attributes());
-    Ok(Relation {
-        id: attributes.get_parse("id")?,
-        members: vec![],
-        meta: attributes.create_meta()?,
-    })
-}
-
-fn create_tags(events: &[BytesStart]) -> Result<Vec<Tag>> {
-    let mut tags = Vec::new();
-    for e in events.iter().filter(|e| e.name() == b"tag") {
-        tags.push(Attributes::from(e.attributes()).create_tag()?);
+impl From<TextPosition> for Location {
+    fn from(position: TextPosition) -> Self {
+        Location {
+            byte_offset: Some(position.offset),
+            line: Some(position.line),
+            column: Some(position.column),
+            element: None,
+        }
     }
-    Ok(tags)
 }
 
-fn create_way_refs(events: &[BytesStart]) -> Result<Vec<i64>> {
-    let mut refs = Vec::new();
-    for e in events.iter().filter(|e| e.name() == b"nd") {
-        refs.push(Attributes::from(e.attributes()).get_parse("ref")?);
-    }
-    Ok(refs)
+/// True for elements that are read into the map.
+fn is_osm_element(event: &BytesStart) -> bool {
+    matches!(event.name(), b"node" | b"way" | b"relation" | b"bounds")
 }
 
-fn create_relation_members(events: &[BytesStart]) -> Result<Vec<RelationMember>> {
-    let mut members = Vec::new();
-    for e in events.iter().filter(|e| e.name() == b"member") {
-        members.push(Attributes::from(e.attributes()).create_relation_member()?);
+/// The location of an element, with the element id if it is valid.
+fn element_location(start: TextPosition, event: &BytesStart) -> Location {
+    let id = Attributes::from(event.attributes())
+        .get("id")
+        .and_then(|id| id.parse().ok());
+    Location {
+        element: id.and_then(|id| match event.name() {
+            b"node" => Some(ElementId::Node(NodeId(id))),
+            b"way" => Some(ElementId::Way(WayId(id))),
+            b"relation" => Some(ElementId::Relation(RelationId(id))),
+            _ => None,
+        }),
+        ..start.into()
     }
-    Ok(members)
 }
 
 #[cfg(test)]
@@ -343,8 +641,12 @@ mod tests {
     use crate::geo::{Boundary, Coordinate};
     use crate::osm_io::error::ErrorKind;
     use crate::osm_io::xml::XmlReader;
-    use crate::osm_io::OsmRead;
-    use crate::{AuthorInformation, Meta, Node, Relation, RelationMember, Way};
+    use crate::osm_io::{OsmRead, ParseMode, ReaderOptions};
+    use crate::{
+        AuthorInformation, CopyrightForm, ElementId, Meta, Node, NodeId, OsmHeader, Relation,
+        RelationId, RelationMember, Timestamp, Way, WayId,
+    };
+    use std::error::Error;
 
     #[test]
     fn read_boundary() {
@@ -382,7 +684,7 @@ mod tests {
                 meta: Meta {
                     version: Some(1),
                     author: Some(AuthorInformation {
-                        created: 1169984426,
+                        created: Timestamp(1169984426),
                         uid: 1238,
                         user: "80n".to_owned(),
                         change_set: 203496,
@@ -473,12 +775,13 @@ mod tests {
                     version: Some(1),
                     tags: vec![("highway", "residential").into(), ("oneway", "yes").into()],
                     author: Some(AuthorInformation {
-                        created: 1169984426,
+                        created: Timestamp(1169984426),
                         uid: 1238,
                         user: "80n".to_owned(),
                         change_set: 203496,
                     }),
-                    ..Meta::default()
+                    visible: true,
+                    action: None,
                 }
             }
         );
@@ -544,12 +847,13 @@ mod tests {
                     version: Some(28),
                     tags: vec![("route", "bus").into(), ("ref", "123").into()],
                     author: Some(AuthorInformation {
-                        created: 1235158826,
+                        created: Timestamp(1235158826),
                         uid: 1238,
                         user: "80n".to_owned(),
                         change_set: 203496,
                     }),
-                    ..Meta::default()
+                    visible: true,
+                    action: None,
                 }
             }
         );
@@ -599,16 +903,222 @@ mod tests {
         validate_invalid_attributes(data);
     }
 
+    #[test]
+    fn error_location() {
+        let xml = r#"<osm>
+                       <node id="1" lat="1.0" lon="1.0"/>
+                       <way id="2" version="1">
+                         <nd ref="1"/>
+                         <tag v="value"/>
+                       </way>
+                     </osm>"#;
+        let error = XmlReader::new(xml.as_bytes()).read().unwrap_err();
+        let location = error.location();
+        assert_eq!(location.line, Some(3));
+        assert_eq!(location.column, Some(24));
+        assert_eq!(location.element, Some(ElementId::Way(WayId(2))));
+        assert_eq!(
+            &xml[location.byte_offset.unwrap() as usize..][..8],
+            "<way id="
+        );
+
+        let xml = r#"<node id="1" lat="1.0" lon="1.0" timestamp="yesterday" uid="1" user="a"
+                           changeset="1"/>"#;
+        let error = XmlReader::new(xml.as_bytes()).read().unwrap_err();
+        match error.kind() {
+            ErrorKind::InvalidTimestamp(timestamp) => assert_eq!(timestamp, "yesterday"),
+            e => panic!("Unexpected kind {:?}", e),
+        }
+        assert_eq!(error.to_string(), "Line 1: Invalid timestamp 'yesterday'.");
+        assert!(error.source().is_some());
+        assert_eq!(error.location().element, Some(ElementId::Node(NodeId(1))));
+    }
+
+    #[test]
+    fn read_history() {
+        let xml = r#"<osm>
+                       <node id="1" lat="1.0" lon="1.0" version="1" visible="true"/>
+                       <node id="1" lat="2.0" lon="2.0" version="2"/>
+                       <node id="1" version="3" visible="false"/>
+                       <way id="2" version="2" visible="false"/>
+                     </osm>"#;
+        let osm = XmlReader::new(xml.as_bytes()).read().unwrap();
+
+        let versions: Vec<_> = osm
+            .nodes
+            .iter()
+            .map(|n| (n.meta.version, n.meta.visible))
+            .collect();
+        assert_eq!(
+            versions,
+            vec![(Some(1), true), (Some(2), true), (Some(3), false)]
+        );
+        assert_eq!(osm.find_node(1).unwrap().meta.version, Some(3));
+        assert!(!osm.ways[0].meta.visible);
+        assert_eq!(osm.boundary.unwrap().max, (2.0, 2.0).into());
+
+        let xml = r#"<node id="1" lat="1.0" lon="1.0" visible="yes"/>"#;
+        let error = XmlReader::new(xml.as_bytes()).read().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 1: The 'visible' attribute contains invalid data 'yes'."
+        );
+    }
+
+    #[test]
+    fn read_header() {
+        let xml = r#"<osm version="0.6" generator="Overpass API 0.7.56" replication_sequence="42">
+                       <note>The data included in this document is from www.openstreetmap.org.</note>
+                       <meta osm_base="2020-01-01T00:00:00Z"/>
+                       <node id="1" lat="1.0" lon="1.0"/>
+                     </osm>"#;
+        let osm = XmlReader::new(xml.as_bytes()).read().unwrap();
+
+        assert_eq!(
+            osm.header,
+            OsmHeader {
+                generator: Some("Overpass API 0.7.56".to_owned()),
+                version: Some("0.6".to_owned()),
+                timestamp: Some(Timestamp(1577836800)),
+                replication_sequence: Some(42),
+                source: None,
+                copyright: Some(
+                    "The data included in this document is from www.openstreetmap.org.".to_owned()
+                ),
+                copyright_form: CopyrightForm::Note,
+                upload: None,
+            }
+        );
+        assert_eq!(osm.nodes.len(), 1);
+    }
+
+    #[test]
+    fn read_non_empty_content() {
+        let xml = r#"<osm>
+                       <node id="1" lat="1.0" lon="1.0">
+                         <tag k="a" v="b"></tag>
+                         <tag k="c" v="d"> </tag>
+                       </node>
+                       <way id="2"><nd ref="1"></nd><tag k="e" v="f"></tag></way>
+                     </osm>"#;
+        let osm = XmlReader::new(xml.as_bytes()).read().unwrap();
+
+        assert_eq!(
+            osm.nodes[0].meta.tags,
+            vec![("a", "b").into(), ("c", "d").into()]
+        );
+        assert_eq!(osm.ways[0].refs, vec![1]);
+        assert_eq!(osm.ways[0].meta.tags, vec![("e", "f").into()]);
+    }
+
+    #[test]
+    fn read_foreign_elements() {
+        let xml = r#"<osm>
+                       <changeset id="5"><tag k="comment" v="ignored"/></changeset>
+                       <way id="1">
+                         <nd ref="1"/>
+                         <extra>
+                           <nd ref="99"/>
+                           <extra><tag k="x" v="y"/></extra>
+                         </extra>
+                         <nd ref="2"/>
+                         <tag k="highway" v="primary"><extra/></tag>
+                       </way>
+                       <node id="2" lat="1.0" lon="1.0"/>
+                     </osm>"#;
+        let osm = XmlReader::new(xml.as_bytes()).read().unwrap();
+
+        assert_eq!(osm.ways.len(), 1);
+        assert_eq!(osm.ways[0].refs, vec![1, 2]);
+        assert_eq!(osm.ways[0].meta.tags, vec![("highway", "primary").into()]);
+        assert_eq!(osm.nodes.len(), 1);
+        assert_eq!(osm.nodes[0].meta.tags, vec![]);
+    }
+
+    #[test]
+    fn read_unclosed_elements() {
+        let xml = "<osm>\n<way id=\"1\">\n<nd ref=\"1\"/>";
+        let error = XmlReader::new(xml.as_bytes()).read().unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::TruncatedDataset));
+        assert_eq!(
+            error.to_string(),
+            "Line 2: Unexpected end of file, expected </way>."
+        );
+        assert_eq!(error.location().element, Some(ElementId::Way(WayId(1))));
+
+        let xml = "<osm>\n<node id=\"1\" lat=\"1.0\" lon=\"1.0\"/>\n";
+        let error = XmlReader::new(xml.as_bytes()).read().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 3: Unexpected end of file, expected </osm>."
+        );
+    }
+
+    #[test]
+    fn read_lenient() {
+        let xml = r#"<osm>
+                       <node id="1" lat="1.0"/>
+                       <node id="2" lat="1.0" lon="1.0" version="x"/>
+                       <way id="3">
+                         <nd ref="2"/>
+                         <nd ref="x"/>
+                         <tag v="value"/>
+                         <tag k="highway" v="primary"/>
+                       </way>
+                       <relation id="4">
+                         <member type="area" ref="3"/>
+                         <member type="way" ref="3" role="outer"/>
+                       </relation>
+                     </osm>"#;
+        let options = ReaderOptions {
+            mode: ParseMode::Lenient,
+        };
+        let mut reader = XmlReader::with_options(xml.as_bytes(), options);
+        let (osm, warnings) = reader.read_with_warnings().unwrap();
+
+        assert_eq!(osm.nodes.len(), 1);
+        assert_eq!(osm.nodes[0].meta, Meta::default());
+        assert_eq!(osm.ways[0].refs, vec![2]);
+        assert_eq!(osm.ways[0].meta.tags, vec![("highway", "primary").into()]);
+        assert_eq!(
+            osm.relations[0].members,
+            vec![RelationMember::Way(3, "outer".to_owned())]
+        );
+
+        let warnings: Vec<_> = warnings
+            .iter()
+            .map(|w| (w.location().line.unwrap(), w.location().element))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (2, Some(ElementId::Node(NodeId(1)))),
+                (3, Some(ElementId::Node(NodeId(2)))),
+                (4, Some(ElementId::Way(WayId(3)))),
+                (4, Some(ElementId::Way(WayId(3)))),
+                (10, Some(ElementId::Relation(RelationId(4)))),
+            ]
+        );
+
+        // Strict mode fails on the first error.
+        let error = XmlReader::new(xml.as_bytes()).read().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 2: Required attribute 'lon' missing."
+        );
+    }
+
     fn validate_missing_attributes(data: Vec<(&str, &str)>) {
         for (field, xml) in data.iter() {
             let error = XmlReader::new(xml.as_bytes()).read().unwrap_err();
             match error.kind() {
-                ErrorKind::ParseError => assert_eq!(
-                    error.to_string(),
-                    format!("Line 1: Required attribute '{}' missing.", field)
-                ),
+                ErrorKind::MissingAttribute(attribute) => assert_eq!(attribute, field),
                 e => panic!("Unexpected kind {:?}", e),
             }
+            assert_eq!(
+                error.to_string(),
+                format!("Line 1: Required attribute '{}' missing.", field)
+            );
         }
     }
 
@@ -616,15 +1126,17 @@ mod tests {
         for (field, value, xml) in data.iter() {
             let error = XmlReader::new(xml.as_bytes()).read().unwrap_err();
             match error.kind() {
-                ErrorKind::ParseError => assert_eq!(
-                    error.to_string(),
-                    format!(
-                        "Line 1: The '{}' attribute contains invalid data '{}'.",
-                        field, value
-                    )
-                ),
+                ErrorKind::InvalidAttributeValue { attribute, .. } => assert_eq!(attribute, field),
+                ErrorKind::UnknownMemberType(t) => assert_eq!(t, value),
                 e => panic!("Unexpected kind {:?}", e),
             }
+            assert_eq!(
+                error.to_string(),
+                format!(
+                    "Line 1: The '{}' attribute contains invalid data '{}'.",
+                    field, value
+                )
+            );
         }
     }
 }
@@ -1,10 +1,13 @@
-use super::super::chrono::{TimeZone, Utc};
-use super::quick_xml::events::{BytesDecl, BytesEnd, BytesStart, Event};
+use super::quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
 use super::quick_xml::Writer;
-use crate::geo::Boundary;
-use crate::osm_io::error::{Error, Result};
-use crate::osm_io::OsmWrite;
-use crate::{Meta, Node, Osm, Relation, RelationMember, Tag, Way};
+use crate::geo::{Boundary, Coordinate};
+use crate::osm_io::error::{Error, ErrorKind, Result};
+use crate::osm_io::meta_filter::MetaFilter;
+use crate::osm_io::{AttributeOrder, Indent, OsmWrite, WriterOptions, XmlWriterOptions};
+use crate::{
+    Action, CopyrightForm, Meta, Node, Osm, OsmHeader, Relation, RelationMember, Tag, Timestamp,
+    UploadPolicy, Way,
+};
 use std::io::Write;
 
 const OSM_VERSION: &str = "0.6";
@@ -12,31 +15,119 @@ const OSM_GENERATOR: &str = "Vadeen OSM";
 const XML_VERSION: &[u8] = b"1.0";
 const XML_ENCODING: &[u8] = b"UTF-8";
 
+const VADEEN_ORDER: &[&str] = &[
+    "id",
+    "action",
+    "lat",
+    "lon",
+    "version",
+    "uid",
+    "user",
+    "changeset",
+    "timestamp",
+    "visible",
+];
+const OSMIUM_ORDER: &[&str] = &[
+    "id",
+    "action",
+    "version",
+    "timestamp",
+    "uid",
+    "user",
+    "changeset",
+    "visible",
+    "lat",
+    "lon",
+];
+const JOSM_ORDER: &[&str] = &[
+    "id",
+    "action",
+    "timestamp",
+    "uid",
+    "user",
+    "visible",
+    "version",
+    "changeset",
+    "lat",
+    "lon",
+];
+
 /// A writer for the xml format.
 pub struct XmlWriter<W: Write> {
     writer: Writer<W>,
+    meta_filter: MetaFilter,
+    options: XmlWriterOptions,
 }
 
 impl<W: Write> XmlWriter<W> {
     pub fn new(inner: W) -> XmlWriter<W> {
+        Self::with_options(inner, WriterOptions::default())
+    }
+
+    pub fn with_options(inner: W, options: WriterOptions) -> XmlWriter<W> {
         XmlWriter {
             writer: Writer::new(inner),
+            options: options.xml.clone(),
+            meta_filter: MetaFilter::new(options),
         }
     }
 
-    /// Write the start tags: Xml header and <osm>-tag.
-    fn write_start(&mut self) -> Result<()> {
+    /// Write the start tags: Xml header, <osm>-tag and the header elements.
+    fn write_start(&mut self, header: &OsmHeader) -> Result<()> {
         self.writer.write_event(Event::Decl(BytesDecl::new(
             XML_VERSION,
             Some(XML_ENCODING),
             None,
         )))?;
-        self.writer.write(b"\n")?;
-
-        let elem = BytesStart::owned_name(b"osm".to_vec())
-            .with_attributes(vec![("version", OSM_VERSION), ("generator", OSM_GENERATOR)]);
+        self.write_newline()?;
+
+        let generator = self
+            .options
+            .generator
+            .as_ref()
+            .or(header.generator.as_ref())
+            .map_or(OSM_GENERATOR, String::as_str);
+        let mut elem = BytesStart::owned_name(b"osm".to_vec()).with_attributes(vec![
+            ("version", header.version.as_deref().unwrap_or(OSM_VERSION)),
+            ("generator", generator),
+        ]);
+        if let Some(upload) = header.upload {
+            let upload = match upload {
+                UploadPolicy::Normal => "true",
+                UploadPolicy::Discouraged => "false",
+                UploadPolicy::Blocked => "never",
+            };
+            elem.push_attribute(("upload", upload));
+        }
+        if let Some(sequence) = header.replication_sequence {
+            elem.push_attribute(("replication_sequence", sequence.to_string().as_ref()));
+        }
+        if let Some(source) = &header.source {
+            elem.push_attribute(("source", source.as_ref()));
+        }
+        let copyright = header.copyright.as_ref();
+        if let (Some(copyright), CopyrightForm::Attribute) = (copyright, header.copyright_form) {
+            elem.push_attribute(("copyright", copyright.as_ref()));
+        }
         self.writer.write_event(Event::Start(elem))?;
-        self.writer.write(b"\n")?;
+        self.write_newline()?;
+
+        if let (Some(copyright), CopyrightForm::Note) = (copyright, header.copyright_form) {
+            self.write_indent(1)?;
+            self.writer
+                .write_event(Event::Start(BytesStart::borrowed_name(b"note")))?;
+            self.writer
+                .write_event(Event::Text(BytesText::from_plain_str(copyright)))?;
+            self.writer
+                .write_event(Event::End(BytesEnd::borrowed(b"note")))?;
+            self.write_newline()?;
+        }
+
+        if let Some(timestamp) = header.timestamp {
+            let elem = BytesStart::owned_name(b"meta".to_vec())
+                .with_attributes(vec![("osm_base", format_timestamp(timestamp)?.as_ref())]);
+            self.write_empty(elem, 1)?;
+        }
         Ok(())
     }
 
@@ -49,112 +140,200 @@ impl<W: Write> XmlWriter<W> {
 
     /// Optional bounds box tag.
     fn write_bounds(&mut self, bounds: &Boundary) -> Result<()> {
+        let (min_lat, min_lon) = self.format_coordinate(bounds.min);
+        let (max_lat, max_lon) = self.format_coordinate(bounds.max);
         let elem = BytesStart::owned_name(b"bounds".to_vec()).with_attributes(vec![
-            ("minlat", bounds.min.lat().to_string().as_ref()),
-            ("minlon", bounds.min.lon().to_string().as_ref()),
-            ("maxlat", bounds.max.lat().to_string().as_ref()),
-            ("maxlon", bounds.max.lon().to_string().as_ref()),
+            ("minlat", min_lat.as_ref()),
+            ("minlon", min_lon.as_ref()),
+            ("maxlat", max_lat.as_ref()),
+            ("maxlon", max_lon.as_ref()),
         ]);
 
-        self.writer.write(b"\t")?;
-        self.writer.write_event(Event::Empty(elem))?;
-        self.writer.write(b"\n")?;
-        Ok(())
+        self.write_empty(elem, 1)
     }
 
     /// See: https://wiki.openstreetmap.org/wiki/Node
     fn write_node(&mut self, node: &Node) -> Result<()> {
-        let mut elem = BytesStart::owned_name(b"node".to_vec()).with_attributes(vec![
-            ("id", node.id.to_string().as_ref()),
-            ("lat", node.coordinate.lat().to_string().as_ref()),
-            ("lon", node.coordinate.lon().to_string().as_ref()),
-        ]);
+        let mut attributes = vec![("id", node.id.to_string())];
+
+        // Deleted nodes have no coordinate.
+        if node.meta.visible {
+            let (lat, lon) = self.format_coordinate(node.coordinate);
+            attributes.push(("lat", lat));
+            attributes.push(("lon", lon));
+        }
 
-        add_meta_attributes(&mut elem, &node.meta);
+        self.add_meta_attributes(&mut attributes, node.id, &node.meta)?;
+        let elem = self.create_element(b"node", attributes);
 
         if node.meta.tags.is_empty() {
-            self.writer.write(b"\t")?;
-            self.writer.write_event(Event::Empty(elem))?;
+            self.write_empty(elem, 1)?;
         } else {
-            self.writer.write(b"\t")?;
+            self.write_indent(1)?;
             self.writer.write_event(Event::Start(elem))?;
-            self.writer.write(b"\n")?;
+            self.write_newline()?;
 
             self.write_tags(&node.meta.tags)?;
 
-            self.writer.write(b"\t")?;
+            self.write_indent(1)?;
             self.writer
                 .write_event(Event::End(BytesEnd::owned(b"node".to_vec())))?;
+            self.write_newline()?;
         }
-        self.writer.write(b"\n")?;
         Ok(())
     }
 
     /// See: https://wiki.openstreetmap.org/wiki/Way
     fn write_way(&mut self, way: &Way) -> Result<()> {
-        let mut elem = BytesStart::owned_name(b"way".to_vec());
-        elem.push_attribute(("id", way.id.to_string().as_ref()));
+        let mut attributes = vec![("id", way.id.to_string())];
+        self.add_meta_attributes(&mut attributes, way.id, &way.meta)?;
+        let elem = self.create_element(b"way", attributes);
 
-        add_meta_attributes(&mut elem, &way.meta);
-
-        self.writer.write(b"\t")?;
+        self.write_indent(1)?;
         self.writer.write_event(Event::Start(elem))?;
-        self.writer.write(b"\n")?;
+        self.write_newline()?;
 
         for r in &way.refs {
             let mut nd = BytesStart::owned_name(b"nd".to_vec());
             nd.push_attribute(("ref", r.to_string().as_ref()));
-            self.writer.write(b"\t\t")?;
-            self.writer.write_event(Event::Empty(nd))?;
-            self.writer.write(b"\n")?;
+            self.write_empty(nd, 2)?;
         }
 
         self.write_tags(&way.meta.tags)?;
 
-        self.writer.write(b"\t")?;
+        self.write_indent(1)?;
         self.writer
             .write_event(Event::End(BytesEnd::owned(b"way".to_vec())))?;
-        self.writer.write(b"\n")?;
+        self.write_newline()?;
         Ok(())
     }
 
     /// See: https://wiki.openstreetmap.org/wiki/Relation
     fn write_relation(&mut self, rel: &Relation) -> Result<()> {
-        let mut elem = BytesStart::owned_name(b"relation".to_vec());
-        elem.push_attribute(("id", rel.id.to_string().as_ref()));
+        let mut attributes = vec![("id", rel.id.to_string())];
+        self.add_meta_attributes(&mut attributes, rel.id, &rel.meta)?;
+        let elem = self.create_element(b"relation", attributes);
 
-        add_meta_attributes(&mut elem, &rel.meta);
-
-        self.writer.write(b"\t")?;
+        self.write_indent(1)?;
         self.writer.write_event(Event::Start(elem))?;
-        self.writer.write(b"\n")?;
+        self.write_newline()?;
 
         for m in &rel.members {
             let mut mem = BytesStart::owned_name(b"member".to_vec());
             add_member_attributes(&mut mem, m);
-
-            self.writer.write(b"\t\t")?;
-            self.writer.write_event(Event::Empty(mem))?;
-            self.writer.write(b"\n")?;
+            self.write_empty(mem, 2)?;
         }
 
         self.write_tags(&rel.meta.tags)?;
 
-        self.writer.write(b"\t")?;
+        self.write_indent(1)?;
         self.writer
             .write_event(Event::End(BytesEnd::owned(b"relation".to_vec())))?;
-        self.writer.write(b"\n")?;
+        self.write_newline()?;
         Ok(())
     }
 
+    /// Add the meta attributes of the element with id `id` to `attributes`.
+    fn add_meta_attributes(
+        &mut self,
+        attributes: &mut Vec<(&str, String)>,
+        id: i64,
+        meta: &Meta,
+    ) -> Result<()> {
+        if let Some(output) = self.meta_filter.apply(meta) {
+            match output.version {
+                Some(version) => attributes.push(("version", version.to_string())),
+                // New elements have no version, JOSM rejects them if they do.
+                None if self.options.write_missing_version && id >= 0 => {
+                    attributes.push(("version", "1".to_owned()))
+                }
+                None => {}
+            }
+
+            if let Some(author) = &output.author {
+                attributes.push(("uid", author.uid.to_string()));
+                attributes.push(("user", author.user.clone()));
+                attributes.push(("changeset", author.change_set.to_string()));
+                attributes.push(("timestamp", format_timestamp(author.created)?));
+            }
+        }
+
+        if !meta.visible {
+            attributes.push(("visible", "false".to_owned()));
+        }
+
+        match meta.action {
+            Some(Action::Modify) => attributes.push(("action", "modify".to_owned())),
+            Some(Action::Delete) => attributes.push(("action", "delete".to_owned())),
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Create an element with `attributes` in the configured attribute order.
+    fn create_element(
+        &self,
+        name: &[u8],
+        mut attributes: Vec<(&str, String)>,
+    ) -> BytesStart<'static> {
+        let order = match self.options.attribute_order {
+            AttributeOrder::Vadeen => VADEEN_ORDER,
+            AttributeOrder::Osmium => OSMIUM_ORDER,
+            AttributeOrder::Josm => JOSM_ORDER,
+        };
+        attributes.sort_by_key(|(key, _)| order.iter().position(|k| k == key));
+
+        let mut elem = BytesStart::owned_name(name.to_vec());
+        for (key, value) in &attributes {
+            elem.push_attribute((*key, value.as_ref()));
+        }
+        elem
+    }
+
+    /// Format latitude and longitude of `coordinate`.
+    fn format_coordinate(&self, coordinate: Coordinate) -> (String, String) {
+        if self.options.fixed_precision {
+            (
+                format!("{:.7}", coordinate.lat()),
+                format!("{:.7}", coordinate.lon()),
+            )
+        } else {
+            (coordinate.lat().to_string(), coordinate.lon().to_string())
+        }
+    }
+
     /// See: https://wiki.openstreetmap.org/wiki/Tags
     fn write_tags(&mut self, tags: &[Tag]) -> Result<()> {
         for tag in tags {
             let tag_elem = BytesStart::owned_name(b"tag".to_vec())
                 .with_attributes(vec![("k", tag.key.as_ref()), ("v", tag.value.as_ref())]);
+            self.write_empty(tag_elem, 2)?;
+        }
+        Ok(())
+    }
 
-            self.writer.write(b"\t\t")?;
-            self.writer.write_event(Event::Empty(tag_elem))?;
+    /// Write an empty element on its own line.
+    fn write_empty(&mut self, elem: BytesStart, depth: usize) -> Result<()> {
+        self.write_indent(depth)?;
+        self.writer.write_event(Event::Empty(elem))?;
+        self.write_newline()
+    }
+
+    /// Indent an element at `depth`, if pretty printing.
+    fn write_indent(&mut self, depth: usize) -> Result<()> {
+        if self.options.pretty {
+            let indent = match self.options.indent {
+                Indent::Tabs => "\t".repeat(depth),
+                Indent::Spaces(n) => " ".repeat(n * depth),
+            };
+            self.writer.write(indent.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// End the line, if pretty printing.
+    fn write_newline(&mut self) -> Result<()> {
+        if self.options.pretty {
             self.writer.write(b"\n")?;
         }
         Ok(())
@@ -163,9 +342,9 @@ impl<W: Write> XmlWriter<W> {
 
 impl<W: Write> OsmWrite<W> for XmlWriter<W> {
     fn write(&mut self, osm: &Osm) -> std::result::Result<(), Error> {
-        self.write_start()?;
+        self.write_start(&osm.header)?;
 
-        if let Some(boundary) = &osm.boundary {
+        if let Some(boundary) = osm.boundary.as_ref().filter(|b| !b.is_empty()) {
             self.write_bounds(boundary)?;
         }
 
@@ -205,21 +384,13 @@ fn add_member_attributes(elem: &mut BytesStart, mem: &RelationMember) {
     ]);
 }
 
-/// Add the meta attributes to an element.
-fn add_meta_attributes(elem: &mut BytesStart, meta: &Meta) {
-    let version = meta.version;
-    elem.push_attribute(("version", version.unwrap_or(1).to_string().as_ref()));
-
-    if let Some(author) = &meta.author {
-        let dt = Utc.timestamp(author.created, 0);
-        let time_str = dt.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string();
-        elem.extend_attributes(vec![
-            ("uid", author.uid.to_string().as_ref()),
-            ("user", author.user.as_ref()),
-            ("changeset", author.change_set.to_string().as_ref()),
-            ("timestamp", time_str.as_ref()),
-        ]);
-    }
+/// Format a timestamp as ISO 8601, timestamps that can not be read back are an error.
+fn format_timestamp(timestamp: Timestamp) -> Result<String> {
+    timestamp.to_iso8601().ok_or_else(|| {
+        let seconds = timestamp.seconds().to_string();
+        let message = format!("Timestamp '{}' is outside of the years 0-9999.", seconds);
+        Error::new(ErrorKind::InvalidTimestamp(seconds), Some(message))
+    })
 }
 
 #[cfg(test)]
@@ -228,7 +399,11 @@ mod tests {
 
     use crate::geo::Boundary;
     use crate::osm_io::xml::XmlWriter;
-    use crate::{AuthorInformation, Meta, Node, Relation, RelationMember, Way};
+    use crate::osm_io::{AttributeOrder, Indent, OsmWrite, WriterOptions, XmlWriterOptions};
+    use crate::{
+        AuthorInformation, CopyrightForm, Meta, Node, Osm, OsmHeader, Relation, RelationMember,
+        Timestamp, Way,
+    };
 
     use super::OSM_GENERATOR;
     use super::OSM_VERSION;
@@ -236,7 +411,7 @@ mod tests {
     #[test]
     fn write_start() {
         let mut writer = XmlWriter::new(Cursor::new(Vec::new()));
-        writer.write_start().unwrap();
+        writer.write_start(&OsmHeader::default()).unwrap();
 
         let xml = writer.writer.into_inner().into_inner();
         assert_eq!(
@@ -249,6 +424,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn write_start_with_header_generator() {
+        let header = OsmHeader {
+            generator: Some("other-tool".to_owned()),
+            copyright: Some("OpenStreetMap and contributors".to_owned()),
+            copyright_form: CopyrightForm::Attribute,
+            ..OsmHeader::default()
+        };
+        let mut writer = XmlWriter::new(Cursor::new(Vec::new()));
+        writer.write_start(&header).unwrap();
+
+        let xml = writer.writer.into_inner().into_inner();
+        assert_eq!(
+            String::from_utf8_lossy(&xml),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <osm version=\"0.6\" generator=\"other-tool\" \
+             copyright=\"OpenStreetMap and contributors\">\n"
+        );
+    }
+
+    #[test]
+    fn write_start_with_header() {
+        let header = OsmHeader {
+            generator: Some("other-tool".to_owned()),
+            timestamp: Some(Timestamp(1577836800)),
+            source: Some("https://example.com".to_owned()),
+            copyright: Some("Data & more".to_owned()),
+            ..OsmHeader::default()
+        };
+        let options = WriterOptions {
+            xml: XmlWriterOptions {
+                generator: Some("my-tool".to_owned()),
+                ..XmlWriterOptions::default()
+            },
+            ..WriterOptions::default()
+        };
+        let mut writer = XmlWriter::with_options(Cursor::new(Vec::new()), options);
+        writer.write_start(&header).unwrap();
+
+        let xml = writer.writer.into_inner().into_inner();
+        assert_eq!(
+            String::from_utf8_lossy(&xml),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <osm version=\"0.6\" generator=\"my-tool\" source=\"https://example.com\">\n\
+             \t<note>Data &amp; more</note>\n\
+             \t<meta osm_base=\"2020-01-01T00:00:00Z\"/>\n"
+        );
+    }
+
     #[test]
     fn write_end() {
         let mut writer = XmlWriter::new(Cursor::new(Vec::new()));
@@ -269,12 +493,13 @@ mod tests {
                     tags: vec![],
                     version: None,
                     author: Some(AuthorInformation {
-                        created: 1285874610,
+                        created: Timestamp(1285874610),
                         change_set: 1234,
                         uid: 4321,
                         user: "osm".to_owned(),
                     }),
-                    ..Meta::default()
+                    visible: true,
+                    action: None,
                 },
             })
             .unwrap();
@@ -287,6 +512,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn write_node_with_formatting_options() {
+        let node = Node {
+            id: 10,
+            coordinate: (65.12, 55.21).into(),
+            meta: Meta {
+                tags: vec![("a", "b").into()],
+                version: None,
+                author: Some(AuthorInformation {
+                    created: Timestamp(1285874610),
+                    change_set: 1234,
+                    uid: 4321,
+                    user: "osm".to_owned(),
+                }),
+                visible: true,
+                action: None,
+            },
+        };
+        let options = WriterOptions {
+            xml: XmlWriterOptions {
+                indent: Indent::Spaces(2),
+                fixed_precision: true,
+                write_missing_version: false,
+                attribute_order: AttributeOrder::Osmium,
+                ..XmlWriterOptions::default()
+            },
+            ..WriterOptions::default()
+        };
+        let mut writer = XmlWriter::with_options(Cursor::new(Vec::new()), options);
+        writer.write_node(&node).unwrap();
+
+        let xml = writer.writer.into_inner().into_inner();
+        assert_eq!(
+            String::from_utf8_lossy(&xml),
+            "  <node id=\"10\" timestamp=\"2010-09-30T19:23:30Z\" uid=\"4321\" user=\"osm\" \
+             changeset=\"1234\" lat=\"65.1200000\" lon=\"55.2100000\">\n\
+             \x20   <tag k=\"a\" v=\"b\"/>\n\
+             \x20 </node>\n"
+        );
+    }
+
+    #[test]
+    fn write_compact_josm_order() {
+        let mut osm = Osm::default();
+        osm.add_way(Way {
+            id: 1,
+            refs: vec![2],
+            meta: Meta {
+                version: Some(4),
+                author: Some(AuthorInformation {
+                    created: Timestamp(1285874610),
+                    change_set: 1234,
+                    uid: 4321,
+                    user: "osm".to_owned(),
+                }),
+                ..Meta::default()
+            },
+        });
+        let options = WriterOptions {
+            xml: XmlWriterOptions {
+                pretty: false,
+                attribute_order: AttributeOrder::Josm,
+                ..XmlWriterOptions::default()
+            },
+            ..WriterOptions::default()
+        };
+        let mut writer = XmlWriter::with_options(Cursor::new(Vec::new()), options);
+        writer.write(&osm).unwrap();
+
+        let xml = writer.writer.into_inner().into_inner();
+        assert_eq!(
+            String::from_utf8_lossy(&xml),
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+                 <osm version=\"{}\" generator=\"{}\">\
+                 <way id=\"1\" timestamp=\"2010-09-30T19:23:30Z\" uid=\"4321\" user=\"osm\" \
+                 version=\"4\" changeset=\"1234\"><nd ref=\"2\"/></way></osm>",
+                OSM_VERSION, OSM_GENERATOR
+            )
+        );
+    }
+
+    #[test]
+    fn write_deleted_node() {
+        let mut writer = XmlWriter::new(Cursor::new(Vec::new()));
+        writer
+            .write_node(&Node {
+                id: 10,
+                meta: Meta {
+                    version: Some(3),
+                    visible: false,
+                    ..Meta::default()
+                },
+                ..Node::default()
+            })
+            .unwrap();
+
+        let xml = writer.writer.into_inner().into_inner();
+        assert_eq!(
+            String::from_utf8_lossy(&xml),
+            "\t<node id=\"10\" version=\"3\" visible=\"false\"/>\n"
+        );
+    }
+
     #[test]
     fn write_node_with_tags() {
         let mut writer = XmlWriter::new(Cursor::new(Vec::new()));
@@ -301,12 +630,13 @@ mod tests {
                     ],
                     version: Some(1),
                     author: Some(AuthorInformation {
-                        created: 1577934245,
+                        created: Timestamp(1577934245),
                         change_set: 1234,
                         uid: 4321,
                         user: "osm".to_owned(),
                     }),
-                    ..Meta::default()
+                    visible: true,
+                    action: None,
                 },
             })
             .unwrap();
@@ -336,12 +666,13 @@ mod tests {
                     ],
                     version: Some(2),
                     author: Some(AuthorInformation {
-                        created: 1285874610,
+                        created: Timestamp(1285874610),
                         change_set: 12,
                         uid: 222,
                         user: "mos".to_owned(),
                     }),
-                    ..Meta::default()
+                    visible: true,
+                    action: None,
                 },
             })
             .unwrap();
@@ -378,12 +709,13 @@ mod tests {
                     ],
                     version: Some(2),
                     author: Some(AuthorInformation {
-                        created: 1285874610,
+                        created: Timestamp(1285874610),
                         change_set: 12,
                         uid: 222,
                         user: "mos".to_owned(),
                     }),
-                    ..Meta::default()
+                    visible: true,
+                    action: None,
                 },
             })
             .unwrap();
@@ -413,4 +745,36 @@ mod tests {
             "\t<bounds minlat=\"-90\" minlon=\"-180\" maxlat=\"90\" maxlon=\"180\"/>\n"
         )
     }
+
+    #[test]
+    fn write_empty_boundary() {
+        let mut writer = XmlWriter::new(Cursor::new(Vec::new()));
+        writer.write(&Osm::default()).unwrap();
+
+        let xml = writer.writer.into_inner().into_inner();
+        assert!(!String::from_utf8_lossy(&xml).contains("bounds"));
+    }
+
+    #[test]
+    fn write_out_of_range_timestamp() {
+        let mut writer = XmlWriter::new(Cursor::new(Vec::new()));
+        let node = Node {
+            meta: Meta {
+                author: Some(AuthorInformation {
+                    created: Timestamp(i64::MAX),
+                    change_set: 1,
+                    uid: 1,
+                    user: "osm".to_owned(),
+                }),
+                ..Meta::default()
+            },
+            ..Node::default()
+        };
+
+        let error = writer.write_node(&node).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("Timestamp '{}' is outside of the years 0-9999.", i64::MAX)
+        );
+    }
 }
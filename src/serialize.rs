@@ -0,0 +1,534 @@
+//! Serde support for the data model, enabled by the `serde` feature.
+//!
+//! Tags are represented as a map, relation members as `{"type": "way", "ref": 1, "role": ""}` and
+//! coordinates as `{"lat": 59.3293, "lon": 18.0686}`. Use [`raw_coordinate`] to represent
+//! coordinates by their internal integers instead, or wrap a map, node or boundary in
+//! [`RawCoordinates`] to do so for all coordinates in it.
+//!
+//! [`raw_coordinate`]: raw_coordinate/index.html
+//! [`RawCoordinates`]: struct.RawCoordinates.html
+use crate::geo::{Boundary, Coordinate};
+use crate::{Meta, Node, Osm, OsmHeader, Relation, RelationMember, Way};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Coordinate")]
+struct Degrees {
+    lat: f64,
+    lon: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Coordinate")]
+struct Raw {
+    lat: i32,
+    lon: i32,
+}
+
+impl Serialize for Coordinate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Degrees {
+            lat: self.lat(),
+            lon: self.lon(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Degrees are rounded to the nearest coordinate, so serialized coordinates round trip exactly.
+impl<'de> Deserialize<'de> for Coordinate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let Degrees { lat, lon } = Degrees::deserialize(deserializer)?;
+        Ok(Coordinate::rounded(lat, lon))
+    }
+}
+
+/// Serialize a coordinate as its internal integers, i.e. degrees multiplied by 10^7, for use
+/// with `#[serde(with = "vadeen_osm::raw_coordinate")]`. More compact in binary formats.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::geo::Coordinate;
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Stop {
+///     #[serde(with = "vadeen_osm::raw_coordinate")]
+///     position: Coordinate,
+/// }
+///
+/// let stop = Stop {
+///     position: Coordinate::new(59.3293, 18.0686),
+/// };
+/// let json = serde_json::to_string(&stop).unwrap();
+/// assert_eq!(json, r#"{"position":{"lat":593293000,"lon":180686000}}"#);
+///
+/// let parsed: Stop = serde_json::from_str(&json).unwrap();
+/// assert_eq!(parsed.position, stop.position);
+/// ```
+pub mod raw_coordinate {
+    use super::Raw;
+    use crate::geo::Coordinate;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        coordinate: &Coordinate,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Raw {
+            lat: coordinate.lat,
+            lon: coordinate.lon,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Coordinate, D::Error> {
+        let Raw { lat, lon } = Raw::deserialize(deserializer)?;
+        Ok(Coordinate { lat, lon })
+    }
+}
+
+/// Tags as a map from key to value, in order.
+pub(crate) mod tags {
+    use crate::Tag;
+    use serde::de::{MapAccess, Visitor};
+    use serde::ser::SerializeMap;
+    use serde::{Deserializer, Serializer};
+    use std::fmt::Formatter;
+
+    pub fn serialize<S: Serializer>(tags: &[Tag], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(tags.len()))?;
+        for tag in tags {
+            map.serialize_entry(&tag.key, &tag.value)?;
+        }
+        map.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Tag>, D::Error> {
+        deserializer.deserialize_map(TagsVisitor)
+    }
+
+    struct TagsVisitor;
+
+    impl<'de> Visitor<'de> for TagsVisitor {
+        type Value = Vec<Tag>;
+
+        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+            write!(f, "a map of tags")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut tags = Vec::with_capacity(map.size_hint().unwrap_or(0));
+            while let Some((key, value)) = map.next_entry()? {
+                tags.push(Tag { key, value });
+            }
+            Ok(tags)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MemberType {
+    Node,
+    Way,
+    Relation,
+}
+
+/// Relation members tagged by type.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "RelationMember")]
+pub(crate) struct Member {
+    #[serde(rename = "type")]
+    member_type: MemberType,
+    #[serde(rename = "ref")]
+    id: i64,
+    role: String,
+}
+
+impl From<RelationMember> for Member {
+    fn from(member: RelationMember) -> Self {
+        let (member_type, id, role) = match member {
+            RelationMember::Node(id, role) => (MemberType::Node, id, role),
+            RelationMember::Way(id, role) => (MemberType::Way, id, role),
+            RelationMember::Relation(id, role) => (MemberType::Relation, id, role),
+        };
+        Member {
+            member_type,
+            id,
+            role,
+        }
+    }
+}
+
+impl From<Member> for RelationMember {
+    fn from(member: Member) -> Self {
+        match member.member_type {
+            MemberType::Node => RelationMember::Node(member.id, member.role),
+            MemberType::Way => RelationMember::Way(member.id, member.role),
+            MemberType::Relation => RelationMember::Relation(member.id, member.role),
+        }
+    }
+}
+
+impl Serialize for Osm {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut osm = serializer.serialize_struct("Osm", 5)?;
+        osm.serialize_field("header", &self.header)?;
+        osm.serialize_field("boundary", &self.boundary)?;
+        osm.serialize_field("nodes", &self.nodes)?;
+        osm.serialize_field("ways", &self.ways)?;
+        osm.serialize_field("relations", &self.relations)?;
+        osm.end()
+    }
+}
+
+/// The public fields of `Osm`, the indices are rebuilt after deserialization.
+#[derive(Deserialize)]
+#[serde(
+    rename = "Osm",
+    bound(deserialize = "B: Deserialize<'de>, N: Deserialize<'de>")
+)]
+struct OsmData<B, N> {
+    #[serde(default)]
+    header: OsmHeader,
+    #[serde(default)]
+    boundary: Option<B>,
+    #[serde(default)]
+    nodes: Vec<N>,
+    #[serde(default)]
+    ways: Vec<Way>,
+    #[serde(default)]
+    relations: Vec<Relation>,
+}
+
+impl<B: Into<Boundary>, N: Into<Node>> From<OsmData<B, N>> for Osm {
+    fn from(data: OsmData<B, N>) -> Self {
+        let mut osm = Osm {
+            header: data.header,
+            boundary: data.boundary.map(Into::into),
+            nodes: data.nodes.into_iter().map(Into::into).collect(),
+            ways: data.ways,
+            relations: data.relations,
+            ..Osm::default()
+        };
+        osm.rebuild_indices();
+        osm
+    }
+}
+
+impl<'de> Deserialize<'de> for Osm {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = OsmData::<Boundary, Node>::deserialize(deserializer)?;
+        Ok(data.into())
+    }
+}
+
+/// Serializes the wrapped map, node or boundary with all coordinates as their internal
+/// integers, like [`raw_coordinate`]. Everything else is serialized as without the wrapper.
+///
+/// Both owned values and references can be serialized, owned values can be deserialized.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::{Osm, OsmBuilder, RawCoordinates};
+/// let mut builder = OsmBuilder::default();
+/// builder.add_point((59.3293, 18.0686), vec![("name", "Stockholm")]);
+/// let osm = builder.build();
+///
+/// let json = serde_json::to_string(&RawCoordinates(&osm)).unwrap();
+/// assert!(json.contains(r#""coordinate":{"lat":593293000,"lon":180686000}"#));
+///
+/// let RawCoordinates(parsed): RawCoordinates<Osm> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(parsed.nodes, osm.nodes);
+/// assert_eq!(parsed.boundary, osm.boundary);
+/// ```
+///
+/// [`raw_coordinate`]: raw_coordinate/index.html
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
+pub struct RawCoordinates<T>(pub T);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Boundary")]
+struct RawBoundary {
+    #[serde(with = "raw_coordinate")]
+    min: Coordinate,
+    #[serde(with = "raw_coordinate")]
+    max: Coordinate,
+    freeze: bool,
+}
+
+impl From<&Boundary> for RawBoundary {
+    fn from(boundary: &Boundary) -> Self {
+        RawBoundary {
+            min: boundary.min,
+            max: boundary.max,
+            freeze: boundary.freeze,
+        }
+    }
+}
+
+impl From<RawBoundary> for Boundary {
+    fn from(boundary: RawBoundary) -> Self {
+        Boundary {
+            min: boundary.min,
+            max: boundary.max,
+            freeze: boundary.freeze,
+        }
+    }
+}
+
+/// A node with borrowed or owned meta data.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Node")]
+struct RawNode<M> {
+    id: i64,
+    #[serde(with = "raw_coordinate")]
+    coordinate: Coordinate,
+    meta: M,
+}
+
+impl<'a> From<&'a Node> for RawNode<&'a Meta> {
+    fn from(node: &'a Node) -> Self {
+        RawNode {
+            id: node.id,
+            coordinate: node.coordinate,
+            meta: &node.meta,
+        }
+    }
+}
+
+impl From<RawNode<Meta>> for Node {
+    fn from(node: RawNode<Meta>) -> Self {
+        Node {
+            id: node.id,
+            coordinate: node.coordinate,
+            meta: node.meta,
+        }
+    }
+}
+
+impl Serialize for RawCoordinates<&Osm> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let nodes = RawNodes(&self.0.nodes);
+        let mut osm = serializer.serialize_struct("Osm", 5)?;
+        osm.serialize_field("header", &self.0.header)?;
+        osm.serialize_field("boundary", &self.0.boundary.as_ref().map(RawBoundary::from))?;
+        osm.serialize_field("nodes", &nodes)?;
+        osm.serialize_field("ways", &self.0.ways)?;
+        osm.serialize_field("relations", &self.0.relations)?;
+        osm.end()
+    }
+}
+
+/// Nodes serialized as a sequence of `RawNode` without collecting them first.
+struct RawNodes<'a>(&'a [Node]);
+
+impl Serialize for RawNodes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter().map(RawNode::from))
+    }
+}
+
+impl Serialize for RawCoordinates<&Node> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RawNode::from(self.0).serialize(serializer)
+    }
+}
+
+impl Serialize for RawCoordinates<&Boundary> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RawBoundary::from(self.0).serialize(serializer)
+    }
+}
+
+impl Serialize for RawCoordinates<Osm> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RawCoordinates(&self.0).serialize(serializer)
+    }
+}
+
+impl Serialize for RawCoordinates<Node> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RawCoordinates(&self.0).serialize(serializer)
+    }
+}
+
+impl Serialize for RawCoordinates<Boundary> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RawCoordinates(&self.0).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RawCoordinates<Osm> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = OsmData::<RawBoundary, RawNode<Meta>>::deserialize(deserializer)?;
+        Ok(RawCoordinates(data.into()))
+    }
+}
+
+impl<'de> Deserialize<'de> for RawCoordinates<Node> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let node = RawNode::<Meta>::deserialize(deserializer)?;
+        Ok(RawCoordinates(node.into()))
+    }
+}
+
+impl<'de> Deserialize<'de> for RawCoordinates<Boundary> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let boundary = RawBoundary::deserialize(deserializer)?;
+        Ok(RawCoordinates(boundary.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geo::{Boundary, Coordinate};
+    use crate::{
+        AuthorInformation, Meta, Node, Osm, OsmBuilder, RawCoordinates, RelationMember, Timestamp,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn serialize_elements() {
+        let mut builder = OsmBuilder::default();
+        let node = builder.add_point((1.0, 2.0), vec![("highway", "bus_stop"), ("name", "A")]);
+        builder.add_relation(vec![(node, "stop")], vec![("type", "route")]);
+        *builder.meta_mut(node).unwrap() = Meta {
+            version: Some(2),
+            author: Some(AuthorInformation {
+                created: Timestamp(1),
+                change_set: 2,
+                uid: 3,
+                user: "user".to_owned(),
+            }),
+            ..builder.meta_mut(node).unwrap().clone()
+        };
+        let osm = builder.build();
+
+        let value = serde_json::to_value(&osm.nodes[0]).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "id": 1,
+                "coordinate": { "lat": 1.0, "lon": 2.0 },
+                "meta": {
+                    "tags": { "highway": "bus_stop", "name": "A" },
+                    "version": 2,
+                    "author": { "created": 1, "change_set": 2, "uid": 3, "user": "user" },
+                    "visible": true,
+                },
+            })
+        );
+
+        let value = serde_json::to_value(&osm.relations[0].members).unwrap();
+        assert_eq!(value, json!([{ "type": "node", "ref": 1, "role": "stop" }]));
+    }
+
+    #[test]
+    fn osm_round_trip() {
+        let mut builder = OsmBuilder::default();
+        builder.add_polyline(vec![(1.0, 1.0), (2.0, 2.0)], vec![("highway", "primary")]);
+        builder.add_polygon(
+            vec![
+                vec![(0.0, 0.0), (0.0, 3.0), (3.0, 3.0), (0.0, 0.0)],
+                vec![(1.0, 1.0), (1.0, 2.0), (2.0, 2.0), (1.0, 1.0)],
+            ],
+            vec![("natural", "water")],
+        );
+        let mut osm = builder.build();
+        osm.header.generator = Some("test".to_owned());
+
+        let json = serde_json::to_string(&osm).unwrap();
+        let mut parsed: Osm = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.header, osm.header);
+        assert_eq!(parsed.boundary, osm.boundary);
+        assert_eq!(parsed.nodes, osm.nodes);
+        assert_eq!(parsed.ways, osm.ways);
+        assert_eq!(parsed.relations, osm.relations);
+
+        // Indices are rebuilt.
+        assert_eq!(parsed.find_way(3).unwrap().refs, vec![1, 2]);
+        assert_eq!(parsed.find_node_id((3.0, 3.0).into()), Some(6));
+        assert_eq!(
+            parsed.find_relation(10).unwrap().members[0],
+            RelationMember::Way(7, "outer".to_owned())
+        );
+    }
+
+    #[test]
+    fn coordinate_round_trip() {
+        let coordinate = Coordinate {
+            lat: 100_000_010,
+            lon: -100_000_010,
+        };
+        let json = serde_json::to_string(&coordinate).unwrap();
+        assert_eq!(json, r#"{"lat":10.000001,"lon":-10.000001}"#);
+        assert_eq!(
+            serde_json::from_str::<Coordinate>(&json).unwrap(),
+            coordinate
+        );
+
+        for value in (0..100_000).map(|i| 100_000_000 + i * 7) {
+            let coordinate = Coordinate {
+                lat: value,
+                lon: -value,
+            };
+            let json = serde_json::to_string(&coordinate).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Coordinate>(&json).unwrap(),
+                coordinate
+            );
+        }
+    }
+
+    #[test]
+    fn deserialize_partial() {
+        let osm: Osm = serde_json::from_value(json!({
+            "boundary": { "min": { "lat": 0.0, "lon": 0.0 }, "max": { "lat": 1.0, "lon": 1.0 }, "freeze": false },
+            "nodes": [{ "id": 5, "coordinate": { "lat": 0.5, "lon": 0.5 }, "meta": {} }],
+        }))
+        .unwrap();
+
+        assert_eq!(osm.boundary, Some(Boundary::new((0.0, 0.0), (1.0, 1.0))));
+        assert_eq!(osm.find_node(5).unwrap().meta, Meta::default());
+        assert!(osm.ways.is_empty());
+    }
+
+    #[test]
+    fn raw_coordinates() {
+        let mut builder = OsmBuilder::default();
+        builder.add_polyline(vec![(1.0, 2.0), (3.0, 4.0)], vec![("highway", "primary")]);
+        let osm = builder.build();
+
+        let value = serde_json::to_value(RawCoordinates(&osm)).unwrap();
+        assert_eq!(
+            value["boundary"],
+            json!({
+                "min": { "lat": 10_000_000, "lon": 20_000_000 },
+                "max": { "lat": 30_000_000, "lon": 40_000_000 },
+                "freeze": false,
+            })
+        );
+        assert_eq!(
+            value["nodes"][1]["coordinate"],
+            json!({ "lat": 30_000_000, "lon": 40_000_000 })
+        );
+        assert_eq!(value["ways"], serde_json::to_value(&osm.ways).unwrap());
+
+        let RawCoordinates(mut parsed) =
+            serde_json::from_value::<RawCoordinates<Osm>>(value).unwrap();
+        assert_eq!(parsed.boundary, osm.boundary);
+        assert_eq!(parsed.nodes, osm.nodes);
+        assert_eq!(parsed.ways, osm.ways);
+        assert_eq!(parsed.find_node_id((3.0, 4.0).into()), Some(2));
+
+        let value = serde_json::to_value(RawCoordinates(osm.nodes[0].clone())).unwrap();
+        assert_eq!(
+            value["coordinate"],
+            json!({ "lat": 10_000_000, "lon": 20_000_000 })
+        );
+        let RawCoordinates(node) = serde_json::from_value::<RawCoordinates<Node>>(value).unwrap();
+        assert_eq!(node, osm.nodes[0]);
+    }
+}
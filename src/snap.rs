@@ -0,0 +1,119 @@
+//! Snapping of coordinates in the `OsmBuilder`, so nearby coordinates share nodes.
+use crate::geo::Coordinate;
+use std::collections::HashMap;
+
+/// Snapping of coordinates added to an [`OsmBuilder`].
+///
+/// [`OsmBuilder`]: struct.OsmBuilder.html
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Snapping {
+    /// Round coordinates to a grid, the value is the size of the grid cells in degrees.
+    /// Coordinates rounded to the same point share node.
+    Grid(f64),
+
+    /// Coordinates within a distance in metres of an existing node reuse that node. The closest
+    /// node is used if there are several.
+    Metres(f64),
+}
+
+/// Index of nodes for distance based snapping. The nodes are put in cells of about the size of
+/// the tolerance, so only a few cells must be searched for each coordinate.
+#[derive(Debug)]
+pub(crate) struct SnapIndex {
+    tolerance: f64,
+    cell_size: i64,
+    cells: HashMap<(i64, i64), Vec<(Coordinate, i64)>>,
+}
+
+impl Snapping {
+    /// Round a coordinate to the grid. Coordinates are not changed by `Metres` snapping.
+    pub(crate) fn snap(self, coordinate: Coordinate) -> Coordinate {
+        match self {
+            Snapping::Grid(size) => {
+                let size = (size * 10_000_000.0).round().max(1.0);
+                let round = |v: i32| ((v as f64 / size).round() * size) as i32;
+                Coordinate {
+                    lat: round(coordinate.lat),
+                    lon: round(coordinate.lon),
+                }
+            }
+            Snapping::Metres(_) => coordinate,
+        }
+    }
+}
+
+impl SnapIndex {
+    pub(crate) fn new(tolerance: f64) -> Self {
+        let degrees = (tolerance / crate::geo::EARTH_RADIUS).to_degrees();
+        SnapIndex {
+            tolerance,
+            cell_size: (degrees * 10_000_000.0).ceil().max(1.0) as i64,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, coordinate: Coordinate, id: i64) {
+        let cell = self.cell(coordinate);
+        self.cells.entry(cell).or_default().push((coordinate, id));
+    }
+
+    /// Find the closest node within the tolerance.
+    pub(crate) fn find(&self, coordinate: Coordinate) -> Option<i64> {
+        let (lat, lon) = self.cell(coordinate);
+
+        // Longitude degrees are shorter further from equator, so more cells must be searched.
+        let cos = coordinate.lat().abs().min(89.9).to_radians().cos();
+        let lon_cells = (1.0 / cos).ceil() as i64;
+
+        let mut closest: Option<(f64, i64)> = None;
+        for lat in lat - 1..=lat + 1 {
+            for lon in lon - lon_cells..=lon + lon_cells {
+                for (c, id) in self.cells.get(&(lat, lon)).into_iter().flatten() {
+                    let distance = coordinate.distance(*c);
+                    if distance <= self.tolerance && closest.is_none_or(|(d, _)| distance < d) {
+                        closest = Some((distance, *id));
+                    }
+                }
+            }
+        }
+        closest.map(|(_, id)| id)
+    }
+
+    fn cell(&self, coordinate: Coordinate) -> (i64, i64) {
+        (
+            i64::from(coordinate.lat).div_euclid(self.cell_size),
+            i64::from(coordinate.lon).div_euclid(self.cell_size),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snap::SnapIndex;
+    use crate::Snapping;
+
+    #[test]
+    fn snap_to_grid() {
+        let snapping = Snapping::Grid(0.001);
+        assert_eq!(
+            snapping.snap((59.00049, 18.00051).into()),
+            (59.0, 18.001).into()
+        );
+        assert_eq!(
+            snapping.snap((-59.00049, -18.00051).into()),
+            (-59.0, -18.001).into()
+        );
+    }
+
+    #[test]
+    fn snap_index() {
+        let mut index = SnapIndex::new(1.0);
+        index.insert((60.0, 18.0).into(), 1);
+        index.insert((60.0, 18.000015).into(), 2);
+
+        assert_eq!(index.find((60.0, 18.000001).into()), Some(1));
+        assert_eq!(index.find((60.0, 18.000014).into()), Some(2));
+        assert_eq!(index.find((60.0, 18.00004).into()), None);
+        assert_eq!(index.find((60.00001, 18.0).into()), None);
+    }
+}
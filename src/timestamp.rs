@@ -0,0 +1,201 @@
+//! Timestamps of elements and maps.
+use chrono::{DateTime, Datelike, Duration, SecondsFormat, Utc};
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::str::FromStr;
+
+/// A point in time, in whole seconds since the unix epoch (UTC).
+///
+/// OSM timestamps have second precision, sub-seconds are truncated when parsing. Timestamps are
+/// formatted as ISO 8601 in UTC like the OSM API, e.g. `2020-01-02T03:04:05Z`.
+///
+/// # Examples
+/// ```
+/// # use vadeen_osm::Timestamp;
+/// # use chrono::Duration;
+/// let timestamp: Timestamp = "2020-01-02T04:04:05.678+01:00".parse().unwrap();
+/// assert_eq!(timestamp, Timestamp(1577934245));
+/// assert_eq!(timestamp.to_string(), "2020-01-02T03:04:05Z");
+///
+/// let later = timestamp + Duration::hours(1);
+/// assert!(later > timestamp);
+/// assert_eq!(later - timestamp, Duration::hours(1));
+/// ```
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Timestamp(pub i64);
+
+impl Timestamp {
+    /// The current time.
+    pub fn now() -> Self {
+        Utc::now().into()
+    }
+
+    /// Seconds since the unix epoch.
+    pub fn seconds(self) -> i64 {
+        self.0
+    }
+
+    /// Convert to a chrono date time. Returns `None` if the timestamp is out of chrono's range.
+    pub fn to_date_time(self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.0, 0)
+    }
+
+    /// Format as `YYYY-MM-DDTHH:MM:SSZ`. Returns `None` if the year does not fit in four digits,
+    /// since such timestamps can not be read back.
+    pub(crate) fn to_iso8601(self) -> Option<String> {
+        self.to_date_time()
+            .filter(|time| (0..=9999).contains(&time.year()))
+            .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+    }
+
+    /// Round down to a multiple of `precision` since the epoch, e.g. to the start of the hour. A
+    /// precision shorter than a second leaves the timestamp unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// # use vadeen_osm::Timestamp;
+    /// # use chrono::Duration;
+    /// let timestamp: Timestamp = "2020-01-02T03:04:05Z".parse().unwrap();
+    /// let rounded = timestamp.truncate(Duration::hours(1));
+    /// assert_eq!(rounded.to_string(), "2020-01-02T03:00:00Z");
+    /// ```
+    pub fn truncate(self, precision: Duration) -> Self {
+        let seconds = precision.num_seconds();
+        if seconds <= 0 {
+            return self;
+        }
+        Timestamp(self.0 - self.0.rem_euclid(seconds))
+    }
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(time: DateTime<Utc>) -> Self {
+        Timestamp(time.timestamp())
+    }
+}
+
+impl From<i64> for Timestamp {
+    fn from(seconds: i64) -> Self {
+        Timestamp(seconds)
+    }
+}
+
+/// Parses ISO 8601 (RFC 3339) with any time zone, e.g. `2020-01-02T03:04:05Z`.
+impl FromStr for Timestamp {
+    type Err = chrono::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.parse::<DateTime<Utc>>()?.into())
+    }
+}
+
+/// Formats as `YYYY-MM-DDTHH:MM:SSZ`. Timestamps outside of the years 0-9999 are formatted as
+/// seconds, writers return an error for those instead of writing them.
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.to_iso8601() {
+            Some(time) => write!(f, "{}", time),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// Whole seconds of the duration are added.
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, duration: Duration) -> Self::Output {
+        Timestamp(self.0 + duration.num_seconds())
+    }
+}
+
+impl AddAssign<Duration> for Timestamp {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+/// Whole seconds of the duration are subtracted.
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, duration: Duration) -> Self::Output {
+        Timestamp(self.0 - duration.num_seconds())
+    }
+}
+
+impl SubAssign<Duration> for Timestamp {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+impl Sub for Timestamp {
+    type Output = Duration;
+
+    fn sub(self, other: Timestamp) -> Self::Output {
+        Duration::seconds(self.0 - other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Timestamp;
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[test]
+    fn parse() {
+        let expected = Timestamp(1169984426);
+        assert_eq!("2007-01-28T11:40:26Z".parse(), Ok(expected));
+        assert_eq!("2007-01-28T11:40:26.999Z".parse(), Ok(expected));
+        assert_eq!("2007-01-28T13:40:26+02:00".parse(), Ok(expected));
+        assert!("2007-01-28".parse::<Timestamp>().is_err());
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(Timestamp(0).to_string(), "1970-01-01T00:00:00Z");
+        assert_eq!(Timestamp(1169984426).to_string(), "2007-01-28T11:40:26Z");
+        assert_eq!(Timestamp(i64::MAX).to_string(), i64::MAX.to_string());
+    }
+
+    #[test]
+    fn iso8601_range() {
+        let first = Timestamp(-62167219200);
+        let last = Timestamp(253402300799);
+        assert_eq!(first.to_iso8601().unwrap(), "0000-01-01T00:00:00Z");
+        assert_eq!(last.to_iso8601().unwrap(), "9999-12-31T23:59:59Z");
+        assert_eq!(last.to_string().parse(), Ok(last));
+        assert_eq!((first - chrono::Duration::seconds(1)).to_iso8601(), None);
+        assert_eq!(Timestamp(253402300800).to_iso8601(), None);
+    }
+
+    #[test]
+    fn chrono_conversion() {
+        let time = Utc.with_ymd_and_hms(2007, 1, 28, 11, 40, 26).unwrap();
+        assert_eq!(Timestamp::from(time), Timestamp(1169984426));
+        assert_eq!(Timestamp(1169984426).to_date_time(), Some(time));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let mut timestamp = Timestamp(100);
+        timestamp += Duration::minutes(1);
+        assert_eq!(timestamp, Timestamp(160));
+        timestamp -= Duration::milliseconds(1500);
+        assert_eq!(timestamp, Timestamp(159));
+        assert_eq!(Timestamp(10) - Timestamp(20), Duration::seconds(-10));
+    }
+
+    #[test]
+    fn truncate() {
+        assert_eq!(Timestamp(3599).truncate(Duration::hours(1)), Timestamp(0));
+        assert_eq!(Timestamp(-1).truncate(Duration::hours(1)), Timestamp(-3600));
+        assert_eq!(Timestamp(59).truncate(Duration::zero()), Timestamp(59));
+    }
+}
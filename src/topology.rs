@@ -0,0 +1,325 @@
+//! Shared edges between polygon rings in the `OsmBuilder`.
+//!
+//! When enabled, rings sharing edges with previously added rings reuse the ways of the shared
+//! parts. Ways are split where the sharing starts and ends, so each way is either completely
+//! shared or not shared at all. This is how e.g. administrative boundaries are mapped in OSM.
+use crate::{OsmBuilder, RelationMember, Way};
+use std::collections::{BTreeSet, HashSet};
+
+/// An edge between two nodes, independent of direction.
+pub(crate) type Edge = (i64, i64);
+
+pub(crate) fn edge(a: i64, b: i64) -> Edge {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+impl OsmBuilder {
+    /// Add a ring, reusing ways for edges shared with rings added earlier. Returns the ids of
+    /// the ways making up the ring.
+    pub(crate) fn add_shared_ring(&mut self, refs: Vec<i64>) -> Vec<i64> {
+        if refs.len() < 4 || refs.first() != refs.last() {
+            return self.add_ring_ways(refs);
+        }
+
+        let edges: HashSet<Edge> = refs.windows(2).map(|w| edge(w[0], w[1])).collect();
+        let owners: BTreeSet<i64> = edges
+            .iter()
+            .filter_map(|e| self.edge_ways.get(e))
+            .cloned()
+            .collect();
+        for id in owners {
+            self.split_shared(id, &edges);
+        }
+
+        // Walk the ring from a point where the owner changes, so no run wraps around the end.
+        let ring = &refs[..refs.len() - 1];
+        let len = ring.len();
+        let owners: Vec<Option<i64>> = (0..len)
+            .map(|i| {
+                self.edge_ways
+                    .get(&edge(ring[i], ring[(i + 1) % len]))
+                    .cloned()
+            })
+            .collect();
+        let start = (0..len)
+            .find(|i| owners[*i] != owners[(i + len - 1) % len])
+            .unwrap_or(0);
+
+        let mut ids = Vec::new();
+        let mut i = 0;
+        while i < len {
+            let owner = owners[(start + i) % len];
+            let mut j = i;
+            while j < len && owners[(start + j) % len] == owner {
+                j += 1;
+            }
+
+            match owner {
+                // A ring sharing an edge with itself passes the same way more than once.
+                Some(id) if ids.contains(&id) => {}
+                Some(id) => ids.push(id),
+                None => {
+                    let refs = (i..=j).map(|k| ring[(start + k) % len]).collect();
+                    ids.extend(self.add_ring_ways(refs));
+                }
+            }
+            i = j;
+        }
+        ids
+    }
+
+    /// Add untagged ways and register their edges.
+    fn add_ring_ways(&mut self, refs: Vec<i64>) -> Vec<i64> {
+        let ids = self.add_ways(refs, vec![]);
+        for id in &ids {
+            self.register_edges(*id);
+        }
+        ids
+    }
+
+    fn register_edges(&mut self, id: i64) {
+        let refs = match self.osm.find_way(id) {
+            Some(way) => way.refs.clone(),
+            None => return,
+        };
+        for w in refs.windows(2) {
+            self.edge_ways.insert(edge(w[0], w[1]), id);
+        }
+    }
+
+    /// Split a way where it starts or stops sharing edges with `edges`. The first part keeps the
+    /// id of the way, relations referencing the way are updated to reference all parts.
+    fn split_shared(&mut self, id: i64, edges: &HashSet<Edge>) {
+        let index = match self.osm.way_index.get(&id) {
+            Some(index) => *index,
+            None => {
+                // The way has been removed, its edges are no longer shared.
+                self.edge_ways.retain(|_, owner| *owner != id);
+                return;
+            }
+        };
+        let way = self.osm.ways[index].clone();
+        let shared: Vec<bool> = way
+            .refs
+            .windows(2)
+            .map(|w| edges.contains(&edge(w[0], w[1])))
+            .collect();
+
+        let closed = way.refs.len() >= 4 && way.refs.first() == way.refs.last();
+        let parts = if closed {
+            let len = shared.len();
+            let changes: Vec<usize> = (0..len)
+                .filter(|i| shared[*i] != shared[(i + len - 1) % len])
+                .collect();
+            if changes.is_empty() {
+                return;
+            }
+
+            // Rotate the ring to start at the first change.
+            let ring = &way.refs[..len];
+            let start = changes[0];
+            let mut cuts: Vec<usize> = changes.iter().map(|c| c - start).collect();
+            cuts.push(len);
+            cuts.windows(2)
+                .map(|c| (c[0]..=c[1]).map(|k| ring[(start + k) % len]).collect())
+                .collect::<Vec<Vec<i64>>>()
+        } else {
+            let mut cuts: Vec<usize> = (1..shared.len())
+                .filter(|i| shared[*i] != shared[i - 1])
+                .collect();
+            if cuts.is_empty() {
+                return;
+            }
+
+            cuts.insert(0, 0);
+            cuts.push(way.refs.len() - 1);
+            cuts.windows(2)
+                .map(|c| way.refs[c[0]..=c[1]].to_vec())
+                .collect()
+        };
+
+        let mut ids = vec![id];
+        let mut parts = parts.into_iter();
+        self.osm.ways[index].refs = parts.next().unwrap();
+        for refs in parts {
+            let part = self.next_way_id();
+            self.osm.add_way(Way {
+                id: part,
+                refs,
+                meta: way.meta.clone(),
+            });
+            ids.push(part);
+        }
+
+        for id in &ids {
+            self.register_edges(*id);
+        }
+
+        for relation in &mut self.osm.relations {
+            let mut members = Vec::new();
+            for member in relation.members.drain(..) {
+                match member {
+                    RelationMember::Way(way_id, role) if way_id == id => {
+                        for id in &ids {
+                            members.push(RelationMember::Way(*id, role.clone()));
+                        }
+                    }
+                    member => members.push(member),
+                }
+            }
+            relation.members = members;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geo::MultiPolygon;
+    use crate::{OsmBuilder, RelationMember};
+
+    fn way_ids(members: &[RelationMember]) -> Vec<i64> {
+        members
+            .iter()
+            .map(|m| match m {
+                RelationMember::Way(id, _) => *id,
+                _ => panic!("Unexpected member {:?}", m),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shared_edges() {
+        let mut builder = OsmBuilder::default();
+        builder.set_shared_edges(true);
+        builder.add_polygon(
+            vec![vec![
+                (0.0, 0.0),
+                (0.0, 1.0),
+                (1.0, 1.0),
+                (1.0, 0.0),
+                (0.0, 0.0),
+            ]],
+            vec![("landuse", "forest")],
+        );
+        builder.add_polygon(
+            vec![vec![
+                (0.0, 1.0),
+                (0.0, 2.0),
+                (1.0, 2.0),
+                (1.0, 1.0),
+                (0.0, 1.0),
+            ]],
+            vec![("landuse", "meadow")],
+        );
+
+        let osm = builder.build();
+        assert_eq!(osm.nodes.len(), 6);
+        assert_eq!(osm.ways.len(), 3);
+        assert_eq!(osm.relations.len(), 2);
+
+        let first = way_ids(&osm.relations[0].members);
+        let second = way_ids(&osm.relations[1].members);
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 2);
+
+        let shared: Vec<&i64> = first.iter().filter(|id| second.contains(id)).collect();
+        assert_eq!(shared.len(), 1);
+        assert_eq!(osm.find_way(*shared[0]).unwrap().refs.len(), 2);
+
+        for relation in &osm.relations {
+            let polygon = MultiPolygon::from_relation(&osm, relation).unwrap();
+            assert_eq!(polygon.polygons.len(), 1);
+            assert_eq!(polygon.polygons[0].outer.len(), 5);
+        }
+    }
+
+    #[test]
+    fn shared_edges_hole() {
+        let mut builder = OsmBuilder::default();
+        builder.set_shared_edges(true);
+        builder.add_polygon(
+            vec![
+                vec![(0.0, 0.0), (0.0, 3.0), (3.0, 3.0), (3.0, 0.0), (0.0, 0.0)],
+                vec![(1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (1.0, 2.0), (1.0, 1.0)],
+            ],
+            vec![("landuse", "forest")],
+        );
+        builder.add_polygon(
+            vec![vec![
+                (1.0, 1.0),
+                (2.0, 1.0),
+                (2.0, 2.0),
+                (1.0, 2.0),
+                (1.0, 1.0),
+            ]],
+            vec![("natural", "water")],
+        );
+
+        let osm = builder.build();
+        assert_eq!(osm.ways.len(), 2);
+        assert_eq!(way_ids(&osm.relations[1].members), vec![osm.ways[1].id]);
+    }
+
+    #[test]
+    fn shared_edges_ring_sharing_edge_with_itself() {
+        let mut builder = OsmBuilder::default();
+        builder.set_shared_edges(true);
+        builder.add_polygon(
+            vec![vec![
+                (0.0, 0.0),
+                (0.0, 1.0),
+                (1.0, 1.0),
+                (1.0, 0.0),
+                (0.0, 0.0),
+            ]],
+            vec![("landuse", "forest")],
+        );
+
+        // Passes the edge shared with the first ring back and forth.
+        builder.add_polygon(
+            vec![vec![
+                (0.0, 1.0),
+                (1.0, 1.0),
+                (1.0, 2.0),
+                (1.0, 1.0),
+                (0.0, 1.0),
+                (0.0, 2.0),
+                (0.0, 1.0),
+            ]],
+            vec![("landuse", "meadow")],
+        );
+
+        let osm = builder.build();
+        let mut ids = way_ids(&osm.relations[1].members);
+        let len = ids.len();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), len);
+    }
+
+    #[test]
+    fn shared_edges_removed_way() {
+        let mut builder = OsmBuilder::default();
+        builder.set_shared_edges(true);
+        let square = vec![vec![
+            (0.0, 0.0),
+            (0.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 0.0),
+            (0.0, 0.0),
+        ]];
+        builder.add_polygon(square.clone(), vec![("landuse", "forest")]);
+
+        builder.osm.ways.clear();
+        builder.osm.rebuild_indices();
+
+        builder.add_polygon(square, vec![("landuse", "meadow")]);
+        let osm = builder.build();
+        assert_eq!(osm.ways.len(), 1);
+        assert_eq!(way_ids(&osm.relations[1].members), vec![osm.ways[0].id]);
+    }
+}
@@ -5,6 +5,7 @@ use std::path::Path;
 use vadeen_osm::geo::Coordinate;
 use vadeen_osm::osm_io::{create_reader, create_writer, read, FileFormat};
 use vadeen_osm::RelationMember::Way;
+use vadeen_osm::Timestamp;
 
 /// real_map.o5m is real_map.osm converted with osmconvert. There seems to be coordinate drifting
 /// in that converter, so coordinates do not match up with the .osm version.
@@ -28,7 +29,7 @@ fn read_o5m_file() {
         assert_eq!("Dalkvist", author.user);
         assert_eq!(12140, author.uid);
         assert_eq!(7035827, author.change_set);
-        assert_eq!(1295564363, author.created);
+        assert_eq!(Timestamp(1295564363), author.created);
     }
 
     // Assert a node with tags.
@@ -49,7 +50,7 @@ fn read_o5m_file() {
         assert_eq!("Ice25T", author.user);
         assert_eq!(157205, author.uid);
         assert_eq!(63422528, author.change_set);
-        assert_eq!(1539268691, author.created);
+        assert_eq!(Timestamp(1539268691), author.created);
     }
 
     // Assert a way.
@@ -70,7 +71,7 @@ fn read_o5m_file() {
         assert_eq!(author.user, "maxugglan");
         assert_eq!(author.uid, 107681);
         assert_eq!(author.change_set, 8280205);
-        assert_eq!(author.created, 1306670457);
+        assert_eq!(author.created, Timestamp(1306670457));
     }
 
     // Assert a relation.
@@ -94,7 +95,7 @@ fn read_o5m_file() {
         assert_eq!("AndersAndersson", author.user);
         assert_eq!(113813, author.uid);
         assert_eq!(11221181, author.change_set);
-        assert_eq!(1333869214, author.created);
+        assert_eq!(Timestamp(1333869214), author.created);
     }
 }
 
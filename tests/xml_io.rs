@@ -5,11 +5,22 @@ use std::path::Path;
 use vadeen_osm::geo::Coordinate;
 use vadeen_osm::osm_io::{create_reader, create_writer, read};
 use vadeen_osm::RelationMember::Way;
+use vadeen_osm::Timestamp;
 
 #[test]
 fn read_osm_file() {
     let osm = read("./tests/test_data/real_map.osm").unwrap();
 
+    assert_eq!(osm.header.version.as_deref(), Some("0.6"));
+    assert_eq!(
+        osm.header.generator.as_deref(),
+        Some("CGImap 0.7.5 (30720 thorn-03.openstreetmap.org)")
+    );
+    assert_eq!(
+        osm.header.copyright.as_deref(),
+        Some("OpenStreetMap and contributors")
+    );
+
     let boundary = osm.boundary.as_ref().unwrap();
     assert_eq!(boundary.min, (60.6750500, 17.1362500).into());
     assert_eq!(boundary.max, (60.6763100, 17.1389800).into());
@@ -26,7 +37,7 @@ fn read_osm_file() {
         assert_eq!("Dalkvist", author.user);
         assert_eq!(12140, author.uid);
         assert_eq!(7035827, author.change_set);
-        assert_eq!(1295564363, author.created);
+        assert_eq!(Timestamp(1295564363), author.created);
     }
 
     // Assert a node with tags.
@@ -47,7 +58,7 @@ fn read_osm_file() {
         assert_eq!("Ice25T", author.user);
         assert_eq!(157205, author.uid);
         assert_eq!(63422528, author.change_set);
-        assert_eq!(1539268691, author.created);
+        assert_eq!(Timestamp(1539268691), author.created);
     }
 
     // Assert a way.
@@ -68,7 +79,7 @@ fn read_osm_file() {
         assert_eq!("maxugglan", author.user);
         assert_eq!(107681, author.uid);
         assert_eq!(8280205, author.change_set);
-        assert_eq!(1306670457, author.created);
+        assert_eq!(Timestamp(1306670457), author.created);
     }
 
     // Assert a relation.
@@ -92,7 +103,7 @@ fn read_osm_file() {
         assert_eq!("AndersAndersson", author.user);
         assert_eq!(113813, author.uid);
         assert_eq!(11221181, author.change_set);
-        assert_eq!(1333869214, author.created);
+        assert_eq!(Timestamp(1333869214), author.created);
     }
 }
 